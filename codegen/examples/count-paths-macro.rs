@@ -0,0 +1,25 @@
+use scallop_codegen::scallop;
+
+scallop! {
+  CountPaths {
+    decl edge(Symbol, Symbol).
+    decl path(Symbol, Symbol).
+    path(A, B) :- edge(A, B).
+    path(A, C) :- edge(A, B), path(B, C).
+  }
+}
+
+fn main() {
+  let mut prog = CountPaths::<DerivationCount<1000>>::new();
+
+  // Initialize data
+  prog.edge().insert(vec![(0, 1), (0, 2), (1, 3), (2, 3)]);
+
+  // Execute the program
+  prog.run();
+
+  // Investigate the results; each tag counts the derivations of the tuple
+  for elem in prog.path().complete().into_iter() {
+    println!("{:?}", elem);
+  }
+}
@@ -100,10 +100,14 @@ struct ModifyLocation {
 
 impl visitor::NodeVisitorMut for ModifyLocation {
   fn visit_location(&mut self, loc: &mut location::Location) -> Result<(), CompileError> {
+    // The nodes of the imported files already have their locations
+    if loc.file != 0 {
+      return Ok(());
+    }
     if let Some(span) = self.offset_span_map.get(&loc.byte_offset) {
       let start = span.start();
-      loc.row = start.line;
-      loc.col = start.column;
+      loc.row = start.line();
+      loc.col = start.column();
     }
     Ok(())
   }
@@ -111,7 +115,13 @@ impl visitor::NodeVisitorMut for ModifyLocation {
 
 fn token_stream_to_ast(tokens: TokenStream) -> Result<ast::Program, CodegenError> {
   let (prog_str, offset_span_map) = token_stream_to_string_and_offset_span_map(tokens);
-  let mut ast = parser::parse_str(&prog_str).map_err(CodegenError::CopmileError)?;
+  // Imports are relative to the file invoking the macro; when the compiler
+  // does not know that file, they are relative to the invoking crate
+  let dir = match proc_macro::Span::call_site().local_file() {
+    Some(file) => file.parent().map(|dir| dir.to_path_buf()).unwrap_or_default(),
+    None => std::env::var("CARGO_MANIFEST_DIR").map(std::path::PathBuf::from).unwrap_or_default(),
+  };
+  let mut ast = parser::parse_str_in_dir(&prog_str, &dir).map_err(CodegenError::CopmileError)?;
   let mut modify_location = ModifyLocation { offset_span_map };
   visitor::visit_program_mut(&mut modify_location, &mut ast).map_err(CodegenError::CopmileError)?;
   Ok(ast)
@@ -2,6 +2,7 @@ use scallop_compiler::{ast::*, ast2ram::*, ast_analysis::*, error::*, ram2rs::*,
 
 fn main() -> Result<(), CompileError> {
   let path_ast = Program {
+    type_decls: vec![],
     decls: vec![
       Decl::new((
         "edge".to_string(),
@@ -2,6 +2,7 @@ use scallop_compiler::{ast::*, ast2ram::*, ast_analysis::*, common::BinaryOp, ra
 
 fn main() {
   let sum2_ast = Program {
+    type_decls: vec![],
     decls: vec![
       Decl::new((
         "digit".to_string(),
@@ -10,6 +10,7 @@ fn main() {
           VarType::Base(Type::Symbol),
           VarType::Base(Type::Integer),
         ]),
+        field_names: None,
       },
       Variable {
         is_temporary: false,
@@ -21,6 +22,7 @@ fn main() {
           ]),
           VarType::Base(Type::Integer),
         ]),
+        field_names: None,
       },
     ],
     updates: vec![Update {
@@ -45,6 +47,7 @@ fn main() {
     }],
     facts: vec![],
     disjunctions: vec![],
+    symbols: vec![],
   };
 
   let options = CompileOptions::default();
@@ -3,8 +3,9 @@ pub use super::common::Type as TypeNode;
 use super::common::{BinaryOp, UnaryOp};
 use super::location::*;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Program {
+  pub type_decls: Vec<TypeDecl>,
   pub decls: Vec<Decl>,
   pub rules: Vec<Rule>,
   pub facts: Vec<Fact>,
@@ -14,12 +15,23 @@ pub struct Program {
 
 impl Program {
   pub fn codify(&self) -> String {
+    let type_decls = self.type_decls.iter().map(TypeDecl::codify).collect::<Vec<_>>();
     let decls = self.decls.iter().map(Decl::codify).collect::<Vec<_>>();
     let facts = self.facts.iter().map(Fact::codify).collect::<Vec<_>>();
     let rules = self.rules.iter().map(Rule::codify).collect::<Vec<_>>();
     let disjs = self.disjunctions.iter().map(Disjunction::codify).collect::<Vec<_>>();
     let queries = self.queries.iter().map(Query::codify).collect::<Vec<_>>();
-    vec![decls, facts, rules, disjs, queries].into_iter().flatten().collect::<Vec<_>>().join("\n")
+    vec![type_decls, decls, facts, rules, disjs, queries].into_iter().flatten().collect::<Vec<_>>().join("\n")
+  }
+
+  /// Append the items of another program, e.g. an imported one
+  pub fn extend(&mut self, other: Program) {
+    self.type_decls.extend(other.type_decls);
+    self.decls.extend(other.decls);
+    self.rules.extend(other.rules);
+    self.facts.extend(other.facts);
+    self.disjunctions.extend(other.disjunctions);
+    self.queries.extend(other.queries);
   }
 }
 
@@ -39,10 +51,35 @@ impl Type {
   }
 }
 
+/// `type Person <: Symbol.`, a type checked apart from its base type
+#[derive(Clone, Debug)]
+pub struct TypeDeclNode {
+  pub name: String,
+  pub base: Type,
+}
+
+impl Node for TypeDeclNode {
+  type T = (String, Type);
+
+  fn new((name, base): Self::T) -> Self {
+    Self { name, base }
+  }
+}
+
+pub type TypeDecl = Located<TypeDeclNode>;
+
+impl TypeDecl {
+  pub fn codify(&self) -> String {
+    format!("type {} <: {}.", self.node.name, self.node.base.codify())
+  }
+}
+
 #[derive(Clone, Debug)]
 pub struct DeclNode {
   pub predicate: String,
   pub arg_types: Vec<Type>,
+  /// The names of the fields, e.g. `from` in `decl edge(from: Int, to: Int)`
+  pub arg_names: Vec<Option<String>>,
 }
 
 impl Node for DeclNode {
@@ -51,27 +88,43 @@ impl Node for DeclNode {
   fn new((predicate, arg_types): Self::T) -> Self {
     Self {
       predicate,
+      arg_names: vec![None; arg_types.len()],
       arg_types,
     }
   }
 }
 
+impl DeclNode {
+  /// The names of the fields, if all of them are named
+  pub fn field_names(&self) -> Option<Vec<String>> {
+    if self.arg_types.is_empty() {
+      None
+    } else {
+      self.arg_names.iter().cloned().collect()
+    }
+  }
+}
+
 pub type Decl = Located<DeclNode>;
 
 impl Decl {
   pub fn codify(&self) -> String {
-    format!("decl {}({}).", self.node.predicate, self.node.arg_types.iter().map(Type::codify).collect::<Vec<_>>().join(", "))
+    let fields = self.node.arg_names.iter().zip(self.node.arg_types.iter()).map(|(name, ty)| match name {
+      Some(name) => format!("{}: {}", name, ty.codify()),
+      None => ty.codify(),
+    });
+    format!("decl {}({}).", self.node.predicate, fields.collect::<Vec<_>>().join(", "))
   }
 }
 
 #[derive(Clone, Debug)]
 pub struct FactNode {
-  pub prob: Option<f32>,
+  pub prob: Option<f64>,
   pub head: Atom,
 }
 
 impl Node for FactNode {
-  type T = (Option<f32>, Atom);
+  type T = (Option<f64>, Atom);
 
   fn new((prob, head): Self::T) -> Self {
     Self { prob, head }
@@ -136,13 +189,16 @@ impl Rule {
 pub struct AtomNode {
   pub predicate: String,
   pub args: Vec<Argument>,
+  /// The arguments given by field name, e.g. `to: Y` in `edge(X, to: Y)`;
+  /// the parser turns them into positional ones
+  pub named_args: Vec<(String, Argument)>,
 }
 
 impl Node for AtomNode {
   type T = (String, Vec<Argument>);
 
   fn new((predicate, args): Self::T) -> Self {
-    Self { predicate, args }
+    Self { predicate, args, named_args: vec![] }
   }
 }
 
@@ -150,7 +206,9 @@ pub type Atom = Located<AtomNode>;
 
 impl Atom {
   pub fn codify(&self) -> String {
-    format!("{}({})", self.node.predicate, self.node.args.iter().map(Argument::codify).collect::<Vec<_>>().join(", "))
+    let args = self.node.args.iter().map(Argument::codify);
+    let named_args = self.node.named_args.iter().map(|(name, arg)| format!("{}: {}", name, arg.codify()));
+    format!("{}({})", self.node.predicate, args.chain(named_args).collect::<Vec<_>>().join(", "))
   }
 }
 
@@ -408,3 +466,29 @@ impl Query {
     format!("query {}.", self.node.atom.codify())
   }
 }
+
+/// `import "path.scl".` or `import "path.scl" as ns.`, resolved by the parser
+#[derive(Clone, Debug)]
+pub struct ImportNode {
+  pub path: String,
+  pub namespace: Option<String>,
+}
+
+impl Node for ImportNode {
+  type T = (String, Option<String>);
+
+  fn new((path, namespace): Self::T) -> Self {
+    Self { path, namespace }
+  }
+}
+
+pub type Import = Located<ImportNode>;
+
+impl Import {
+  pub fn codify(&self) -> String {
+    match &self.node.namespace {
+      Some(ns) => format!("import \"{}\" as {}.", self.node.path, ns),
+      None => format!("import \"{}\".", self.node.path),
+    }
+  }
+}
@@ -1,51 +1,86 @@
 use std::collections::*;
 
-use super::{ast, common::*, error::*, ram};
+use super::{ast, common::*, error::*, ram, symbols};
+use super::visitor::*;
 
 pub type SymbolIdMap = HashMap<String, usize>;
 
-static MAX_TUPLE_SIZE : usize = 10;
+/// The widest tuple stored flat; the columns of wider relations are split
+/// into nested halves, as Rust only implements the traits of tuples up to
+/// 12 elements
+pub const MAX_TUPLE_SIZE: usize = 10;
+
+/// Arrange the columns of a relation into its tuple layout; a single column
+/// is stored as is
+pub fn nest_columns<T>(mut columns: Vec<T>, tuple: &impl Fn(Vec<T>) -> T) -> T {
+  match columns.len() {
+    1 => columns.pop().unwrap(),
+    length if length > MAX_TUPLE_SIZE => {
+      let second_half = columns.split_off(length / 2);
+      tuple(vec![nest_columns(columns, tuple), nest_columns(second_half, tuple)])
+    }
+    _ => tuple(columns),
+  }
+}
+
+/// The indices of the `i`-th of `length` columns in the tuple layout
+pub fn column_indices(length: usize, i: usize) -> Vec<usize> {
+  if length == 1 {
+    vec![]
+  } else if length > MAX_TUPLE_SIZE {
+    let half = length / 2;
+    if i < half {
+      std::iter::once(0).chain(column_indices(half, i)).collect()
+    } else {
+      std::iter::once(1).chain(column_indices(length - half, i - half)).collect()
+    }
+  } else {
+    vec![i]
+  }
+}
 
+/// Gather the named arguments into a tuple with the layout of relations,
+/// returning the indices of the names in the tuple
+fn nest_named_args(mut args: Vec<(String, ram::Argument)>) -> (ram::Argument, HashMap<String, Vec<usize>>) {
+  let length = args.len();
+  let indices = args
+    .iter()
+    .enumerate()
+    .map(|(i, (name, _))| (name.clone(), column_indices(length, i)))
+    .collect();
+  let arg = create_project_arg(&args.drain(..).map(|(_, arg)| arg).collect::<Vec<_>>());
+  (arg, indices)
+}
+
+/// The ids of all the symbol constants of the program, interned in the
+/// global symbol table
 pub fn identifier_map(ast: &ast::Program) -> SymbolIdMap {
-  let mut identifier = 0;
-  let mut map = SymbolIdMap::new();
-  for rule in &ast.rules {
-    for arg in &rule.node.head.node.args {
-      match arg {
-        ast::Argument::Constant(c) => match &c.node {
-          ast::ConstantNode::Symbol(s) => {
-            let id = identifier;
-            identifier += 1;
-            map.insert(s.clone(), id);
-          }
-          _ => {}
-        },
-        _ => {}
+  let mut collector = SymbolCollector(SymbolIdMap::new());
+
+  // Use unwrap because this cannot fail
+  visit_program(&mut collector, ast).unwrap();
+  collector.0
+}
+
+struct SymbolCollector(SymbolIdMap);
+
+impl NodeVisitor for SymbolCollector {
+  fn visit_constant(&mut self, c: &ast::Constant) -> Result<(), CompileError> {
+    if let ast::ConstantNode::Symbol(s) = &c.node {
+      if !self.0.contains_key(s) {
+        self.0.insert(s.clone(), symbols::intern(s));
       }
     }
+    Ok(())
   }
-  map
 }
 
 pub fn ast_arg_types_to_var_type(arg_types: &[ast::Type]) -> ram::VarType {
-  match arg_types.len() {
-    0 => ram::VarType::Empty,
-    1 => ram::VarType::Base(arg_types[0].node.clone()),
-    length => {
-      if length > MAX_TUPLE_SIZE {
-        let first_part = ast_arg_types_to_var_type(&arg_types[0..length / 2]);
-        let second_part = ast_arg_types_to_var_type(&arg_types[length / 2..]);
-        ram::VarType::Tuple(vec![first_part, second_part])
-      } else {
-        ram::VarType::Tuple(
-          arg_types
-            .iter()
-            .cloned()
-            .map(|e| ram::VarType::Base(e.node))
-            .collect::<Vec<_>>(),
-        )
-      }
-    }
+  if arg_types.is_empty() {
+    ram::VarType::Empty
+  } else {
+    let columns = arg_types.iter().map(|ty| ram::VarType::Base(ty.node.clone())).collect();
+    nest_columns(columns, &ram::VarType::Tuple)
   }
 }
 
@@ -55,7 +90,8 @@ pub fn ast_const_to_ram_const(
 ) -> ram::Constant {
   match c {
     ast::ConstantNode::Symbol(s) => {
-      let symbol_id = id_map[s];
+      // Symbols of rules compiled on their own are interned on the fly
+      let symbol_id = id_map.get(s).cloned().unwrap_or_else(|| symbols::intern(s));
       ram::Constant::Symbol(symbol_id)
     }
     ast::ConstantNode::SymbolId(i) => ram::Constant::Symbol(i.clone()),
@@ -98,6 +134,7 @@ pub fn ast_to_ram_variables(ast: &ast::Program) -> Vec<ram::Variable> {
       is_temporary: false,
       name: decl.node.predicate.clone(),
       arg_types: ast_arg_types_to_var_type(&decl.node.arg_types),
+      field_names: decl.node.field_names(),
     });
   }
   variables
@@ -249,6 +286,7 @@ pub fn add_temporary_variable_from_flow(
     is_temporary: true,
     name: name.clone(),
     arg_types: ty,
+    field_names: None,
   };
   vars.push(var);
   name
@@ -322,37 +360,16 @@ pub fn projected_join_var(
   updates: &mut Vec<ram::Update>,
   tmp_counter: &mut usize,
 ) -> (String, HashMap<String, Vec<usize>>) {
-  let t_a_all = vars
+  let mut t_a_all = vars
     .iter()
-    .filter_map(|(name, indices)| {
-      if itsct.contains(name) {
-        None
-      } else {
-        Some((name, ram::Argument::Element(indices.clone())))
-      }
-    })
+    .filter(|(name, _)| !itsct.contains(name))
     .collect::<Vec<_>>();
-  let (t_a, t_a_vars) = if t_a_all.len() == 1 {
-    let (name, arg) = t_a_all[0].clone();
-    let vars = vec![(name.clone(), vec![])]
-      .into_iter()
-      .collect::<HashMap<_, _>>();
-    (arg, vars)
-  } else {
-    let arg = ram::Argument::Tuple(
-      t_a_all
-        .iter()
-        .map(|(_, arg)| arg)
-        .cloned()
-        .collect::<Vec<_>>(),
-    );
-    let vars = t_a_all
-      .iter()
-      .enumerate()
-      .map(|(i, (name, _))| (name.clone().clone(), vec![i]))
-      .collect::<HashMap<_, _>>();
-    (arg, vars)
-  };
+  t_a_all.sort_by_key(|(_, indices)| *indices);
+  let t_a_all = t_a_all
+    .into_iter()
+    .map(|(name, indices)| (name.clone(), ram::Argument::Element(indices.clone())))
+    .collect::<Vec<_>>();
+  let (t_a, t_a_vars) = nest_named_args(t_a_all);
   match (&flow, &key, &t_a) {
     (
       ram::Flow::Variable(v),
@@ -387,7 +404,7 @@ pub fn body_atom_to_flow_variable(
   let mut filter_constants: Vec<(Vec<usize>, ast::ConstantNode)> = vec![];
 
   for (i, arg) in atom.node.args.iter().enumerate() {
-    let indicies = if num_args == 1 { vec![] } else { vec![i] };
+    let indicies = column_indices(num_args, i);
     match arg {
       ast::Argument::Variable(v) => {
         if variables.contains_key(&v.node.name) {
@@ -462,35 +479,13 @@ pub fn body_atom_to_flow_variable(
     }
   };
 
-  if variables.len() == atom.node.args.len() && atom.node.args.len() <= MAX_TUPLE_SIZE {
+  if variables.len() == atom.node.args.len() {
     return Ok((equality_filtered_flow, variables));
   } else {
-    use std::iter::FromIterator;
-    let (project_arg, vars) = if variables.len() == 1 {
-      let arg = ram::Argument::Element(variables.iter().next().unwrap().1.clone());
-      let vars = HashMap::from_iter(variables.iter().map(|(name, _)| (name.clone(), vec![])));
-      (arg, vars)
-    } else {
-      if atom.node.args.len() <= MAX_TUPLE_SIZE {
-        let mut args = variables.iter().collect::<Vec<_>>();
-        args.sort_by(|(_, i1), (_, i2)| i1.partial_cmp(i2).unwrap());
-        let arg = ram::Argument::Tuple(
-          args
-            .iter()
-            .map(|(_, indices)| ram::Argument::Element((*indices).clone()))
-            .collect::<Vec<_>>(),
-        );
-        let vars = HashMap::from_iter(
-          args
-            .iter()
-            .enumerate()
-            .map(|(i, (name, _))| ((*name).clone(), vec![i])),
-        );
-        (arg, vars)
-      } else {
-        return Err(CompileError::NotImplemented);
-      }
-    };
+    let mut args = variables.into_iter().collect::<Vec<_>>();
+    args.sort_by(|(_, i1), (_, i2)| i1.cmp(i2));
+    let args = args.into_iter().map(|(name, indices)| (name, ram::Argument::Element(indices)));
+    let (project_arg, vars) = nest_named_args(args.collect());
     let flow = ram::Flow::Project(Box::new(equality_filtered_flow), project_arg);
     Ok((flow, vars))
   }
@@ -587,29 +582,18 @@ pub fn combine_flows(
             .unwrap()
         });
 
-        let k_a = ram::Argument::Tuple(
+        let (k_a, vars) = nest_named_args(
           itsct_vec
             .iter()
-            .map(|name| {
-              let indices = agg_vars[*name].clone();
-              ram::Argument::Element(indices)
-            })
-            .collect::<Vec<_>>(),
+            .map(|name| ((*name).clone(), ram::Argument::Element(agg_vars[*name].clone())))
+            .collect(),
         );
-        let k_b = ram::Argument::Tuple(
+        let (k_b, _) = nest_named_args(
           itsct_vec
             .iter()
-            .map(|name| {
-              let indices = curr_vars[*name].clone();
-              ram::Argument::Element(indices)
-            })
-            .collect::<Vec<_>>(),
+            .map(|name| ((*name).clone(), ram::Argument::Element(curr_vars[*name].clone())))
+            .collect(),
         );
-        let vars = itsct_vec
-          .into_iter()
-          .enumerate()
-          .map(|(i, name)| (name.clone(), vec![i]))
-          .collect::<HashMap<_, _>>();
         (k_a, k_b, vars)
       }
     };
@@ -695,18 +679,10 @@ pub fn combine_flows(
 }
 
 pub fn create_project_arg(args: &[ram::Argument]) -> ram::Argument {
-  match args.len() {
-    0 => ram::Argument::Tuple(vec![]),
-    1 => args[0].clone(),
-    length => {
-      if length > MAX_TUPLE_SIZE {
-        let first_part = create_project_arg(&args[0..length / 2]);
-        let second_part = create_project_arg(&args[length / 2..]);
-        ram::Argument::Tuple(vec![first_part, second_part])
-      } else {
-        ram::Argument::Tuple(args.iter().cloned().collect::<Vec<_>>())
-      }
-    }
+  if args.is_empty() {
+    ram::Argument::Tuple(vec![])
+  } else {
+    nest_columns(args.to_vec(), &ram::Argument::Tuple)
   }
 }
 
@@ -765,6 +741,7 @@ pub fn ast_rule_to_ram_updates(
       is_temporary: true,
       name: tmp_name.clone(),
       arg_types: head_var.arg_types.clone(),
+      field_names: None,
     };
     vars.push(var);
     let fact = ram::Fact {
@@ -851,7 +828,7 @@ pub fn ast_rule_to_ram_updates(
     .iter()
     .enumerate()
     .filter_map(|(i, arg)| match arg {
-      ast::Argument::Variable(v) => Some((v.node.name.clone(), vec![i])),
+      ast::Argument::Variable(v) => Some((v.node.name.clone(), column_indices(head_arity, i))),
       _ => None,
     })
     .collect::<HashMap<_, _>>();
@@ -902,11 +879,16 @@ pub fn ast2ram(ast: &ast::Program) -> Result<ram::Program, CompileError> {
     updates.extend(rule_updates);
   }
 
+  // The symbols used by the program, ordered by id
+  let mut symbols = id_map.into_iter().map(|(name, id)| (id, name)).collect::<Vec<_>>();
+  symbols.sort();
+
   // Generate program
   Ok(ram::Program {
     variables,
     facts,
     disjunctions,
     updates,
+    symbols,
   })
 }
@@ -11,6 +11,7 @@ use super::options::CompileOptions;
 pub struct AnalysisResult {
   pub is_probabilistic: bool,
   pub decls: Decls,
+  pub type_aliases: TypeAliases,
   pub node_types: NodeTypeMap,
   pub disj_rela_map: DisjunctionRelationMap,
   pub demands: Demands,
@@ -21,6 +22,7 @@ impl Default for AnalysisResult {
     Self {
       is_probabilistic: false,
       decls: Decls::new(),
+      type_aliases: TypeAliases::new(),
       node_types: NodeTypeMap::new(),
       disj_rela_map: DisjunctionRelationMap::new(),
       demands: Demands::new(),
@@ -303,6 +305,37 @@ pub type Decls = HashMap<String, Vec<Type>>;
 
 pub type NodeTypeMap = HashMap<usize, Type>;
 
+/// The base type of each declared type, e.g. `Symbol` for `type Person <: Symbol.`
+pub type TypeAliases = HashMap<String, Type>;
+
+/// The builtin type a declared type stands for
+pub fn erase_type(ty: &Type, type_aliases: &TypeAliases) -> Type {
+  match ty {
+    Type::Named(n) => erase_type(&type_aliases[n], type_aliases),
+    ty => ty.clone(),
+  }
+}
+
+fn is_subtype(ty: &Type, sup: &Type, type_aliases: &TypeAliases) -> bool {
+  match ty {
+    _ if ty == sup => true,
+    Type::Named(n) => is_subtype(&type_aliases[n], sup, type_aliases),
+    _ => false,
+  }
+}
+
+/// The type of the values of both types, i.e. the more specific one; declared
+/// types are only compatible with their base types, never with each other
+fn unify_types(ty_1: &Type, ty_2: &Type, type_aliases: &TypeAliases) -> Option<Type> {
+  if is_subtype(ty_1, ty_2, type_aliases) {
+    Some(ty_1.clone())
+  } else if is_subtype(ty_2, ty_1, type_aliases) {
+    Some(ty_2.clone())
+  } else {
+    None
+  }
+}
+
 #[derive(Debug)]
 pub enum ToUnifyArg {
   /// Constant Node Id
@@ -316,6 +349,8 @@ pub type ToUnifyArgs = Vec<(usize, ToUnifyArg, ToUnifyArg)>;
 
 pub struct TypeAssign {
   pub decls: Decls,
+  pub type_aliases: TypeAliases,
+  pub field_names: HashMap<String, Vec<Option<String>>>,
   pub node_types: NodeTypeMap,
   pub rule_arg_map: HashMap<usize, usize>,
   pub to_unify_args: ToUnifyArgs,
@@ -325,6 +360,8 @@ impl TypeAssign {
   pub fn new() -> Self {
     Self {
       decls: Decls::new(),
+      type_aliases: TypeAliases::new(),
+      field_names: HashMap::new(),
       node_types: NodeTypeMap::new(),
       rule_arg_map: HashMap::new(),
       to_unify_args: ToUnifyArgs::new(),
@@ -521,7 +558,9 @@ fn unify_arg_type(
       (ast::ConstantNode::Boolean(_), Type::Boolean) => {
         node_types.insert(c.location.id, Type::Boolean);
       }
-      (ast::ConstantNode::Integer(_), Type::Symbol) => {
+      (ast::ConstantNode::Integer(_), Type::Symbol)
+      | (ast::ConstantNode::Symbol(_), Type::Symbol)
+      | (ast::ConstantNode::SymbolId(_), Type::Symbol) => {
         node_types.insert(c.location.id, Type::Symbol);
       }
       (ast::ConstantNode::Integer(_), Type::Integer) => {
@@ -621,6 +660,33 @@ fn unify_arg_type(
 }
 
 impl NodeVisitor for TypeAssign {
+  fn visit_type_decl(&mut self, type_decl: &ast::TypeDecl) -> Result<(), CompileError> {
+    let base = &type_decl.node.base;
+    self.visit_type(base)?;
+    match self.type_aliases.get(&type_decl.node.name) {
+      // The same declaration may come from a file imported twice
+      Some(ty) if ty == &base.node => Ok(()),
+      Some(_) => Err(CompileError::DuplicatedTypeDeclaration {
+        dup: type_decl.location,
+        type_name: type_decl.node.name.clone(),
+      }),
+      None => {
+        self.type_aliases.insert(type_decl.node.name.clone(), base.node.clone());
+        Ok(())
+      }
+    }
+  }
+
+  fn visit_type(&mut self, ty: &ast::Type) -> Result<(), CompileError> {
+    match &ty.node {
+      Type::Named(n) if !self.type_aliases.contains_key(n) => Err(CompileError::UnknownType {
+        loc: ty.location,
+        type_name: n.clone(),
+      }),
+      _ => Ok(()),
+    }
+  }
+
   fn visit_decl(&mut self, decl: &ast::Decl) -> Result<(), CompileError> {
     if self.decls.contains_key(&decl.node.predicate) {
       Err(CompileError::DuplicatedDeclaration {
@@ -628,6 +694,28 @@ impl NodeVisitor for TypeAssign {
         rela_name: decl.node.predicate.clone(),
       })
     } else {
+      let names = decl.node.arg_names.iter().zip(decl.node.arg_types.iter());
+      for (i, (name, ty)) in names.enumerate() {
+        if let Some(name) = name {
+          // The fields become the fields of a record struct in Rust, where
+          // the other keywords are written as raw identifiers
+          if ["self", "super", "crate"].contains(&name.as_str()) {
+            return Err(CompileError::ReservedField {
+              loc: ty.location,
+              rela_name: decl.node.predicate.clone(),
+              field: name.clone(),
+            });
+          }
+          if decl.node.arg_names[..i].contains(&Some(name.clone())) {
+            return Err(CompileError::DuplicatedField {
+              loc: ty.location,
+              rela_name: decl.node.predicate.clone(),
+              field: name.clone(),
+            });
+          }
+        }
+      }
+      self.field_names.insert(decl.node.predicate.clone(), decl.node.arg_names.clone());
       self.decls.insert(
         decl.node.predicate.clone(),
         decl
@@ -666,14 +754,29 @@ impl NodeVisitor for TypeAssign {
     if self.decls.contains_key(&atom.node.predicate) {
       let arg_types = &self.decls[&atom.node.predicate];
       if arg_types.len() == atom.node.args.len() {
-        for (arg, arg_type) in atom.node.args.iter().zip(arg_types.iter()) {
+        let field_names = self.field_names.get(&atom.node.predicate);
+        for (i, (arg, arg_type)) in atom.node.args.iter().zip(arg_types.iter()).enumerate() {
+          // Constants are of the base type of declared types
+          let arg_type = match arg {
+            ast::Argument::Constant(_) => erase_type(arg_type, &self.type_aliases),
+            _ => arg_type.clone(),
+          };
           unify_arg_type(
             &mut self.node_types,
             &mut self.to_unify_args,
             &self.rule_arg_map,
             arg,
-            arg_type,
-          )?;
+            &arg_type,
+          )
+          .map_err(|e| match (e, field_names.and_then(|names| names[i].as_ref())) {
+            (CompileError::TypeMismatch { loc, ty }, Some(field)) => CompileError::FieldTypeMismatch {
+              loc,
+              rela_name: atom.node.predicate.clone(),
+              field: field.clone(),
+              ty,
+            },
+            (e, _) => e,
+          })?;
         }
         return Ok(());
       } else {
@@ -726,24 +829,46 @@ impl NodeVisitor for TypeAssign {
   }
 }
 
+/// The node ids of the variables directly used as columns of the atoms
+fn column_var_ids<'a>(atoms: impl Iterator<Item = &'a ast::Atom>) -> HashSet<usize> {
+  atoms
+    .flat_map(|atom| atom.node.args.iter())
+    .filter_map(|arg| match arg {
+      ast::Argument::Variable(v) => Some(v.location.id),
+      _ => None,
+    })
+    .collect()
+}
+
 pub struct TypeUnification<'a> {
   node_types: &'a mut NodeTypeMap,
   to_unify_args: ToUnifyArgs,
+  type_aliases: &'a TypeAliases,
 }
 
 impl<'a> TypeUnification<'a> {
-  pub fn new(node_types: &'a mut NodeTypeMap, to_unify_args: ToUnifyArgs) -> Self {
+  pub fn new(node_types: &'a mut NodeTypeMap, to_unify_args: ToUnifyArgs, type_aliases: &'a TypeAliases) -> Self {
     Self {
       node_types,
       to_unify_args,
+      type_aliases,
     }
   }
+
+  fn unify(&self, (ty_1, loc_1): (&Type, &Location), (ty_2, loc_2): (&Type, &Location)) -> Result<Type, CompileError> {
+    unify_types(ty_1, ty_2, self.type_aliases).ok_or_else(|| CompileError::TypeUnificationError {
+      loc_1: *loc_1,
+      loc_2: *loc_2,
+      ty_1: ty_1.clone(),
+      ty_2: ty_2.clone(),
+    })
+  }
 }
 
 impl<'a> NodeVisitor for TypeUnification<'a> {
   fn visit_rule(&mut self, rule: &ast::Rule) -> Result<(), CompileError> {
     struct VarFinder {
-      vars: HashMap<String, Vec<usize>>,
+      vars: HashMap<String, Vec<Location>>,
     }
     impl NodeVisitor for VarFinder {
       fn visit_arg(&mut self, arg: &ast::Argument) -> Result<(), CompileError> {
@@ -753,7 +878,7 @@ impl<'a> NodeVisitor for TypeUnification<'a> {
               .vars
               .entry(v.node.name.clone())
               .or_insert(vec![])
-              .push(v.location.id);
+              .push(v.location);
           }
           _ => {}
         }
@@ -761,43 +886,63 @@ impl<'a> NodeVisitor for TypeUnification<'a> {
       }
     }
 
-    // Find all the variables
+    // Find all the variables, and the columns of the head and of the negated
+    // atoms they are used as
     let mut var_finder = VarFinder {
       vars: HashMap::new(),
     };
     visit_rule(&mut var_finder, rule)?;
-
-    // Unify the same variable
+    let head_columns = column_var_ids(std::iter::once(&rule.node.head));
+    let neg_columns = column_var_ids(rule.node.body.iter().filter_map(|l| match &l.node {
+      ast::LiteralNode::Neg(atom) => Some(atom),
+      _ => None,
+    }));
+
+    // Unify the same variable; the instances typed by different declared
+    // types are reported together. The values of a variable come from the
+    // positive body, so the head and the negated atoms do not narrow its type
     let mut var_types = HashMap::new();
-    for (var_name, instance_ids) in var_finder.vars {
-      let mut first_type = None;
-      for instance_id in &instance_ids {
-        match self.node_types.get(&instance_id) {
-          Some(instance_ty) => {
-            first_type = Some(instance_ty.clone());
-            break;
-          }
-          _ => {}
+    for (var_name, instances) in var_finder.vars {
+      let is_checked = |instance: &Location| head_columns.contains(&instance.id) || neg_columns.contains(&instance.id);
+      let narrowing = instances.iter().filter(|instance| !is_checked(instance)).collect::<Vec<_>>();
+      let narrowing = if narrowing.is_empty() { instances.iter().collect() } else { narrowing };
+      let mut var_type: Option<(Type, &Location)> = None;
+      for instance in narrowing {
+        if let Some(instance_ty) = self.node_types.get(&instance.id) {
+          var_type = Some(match var_type {
+            Some((ty, loc)) => (self.unify((&ty, loc), (instance_ty, instance))?, loc),
+            None => (instance_ty.clone(), instance),
+          });
         }
       }
-      if let Some(first_type) = first_type {
-        var_types.insert(var_name, first_type.clone());
-        for instance_id in &instance_ids {
-          match self.node_types.get(&instance_id) {
-            Some(instance_ty) => {
-              if &first_type != instance_ty {
-                return Err(CompileError::CannotUnifyType);
+      match var_type {
+        Some((ty, loc)) => {
+          // The head columns take the values as their supertypes, the negated
+          // atoms only check the values of compatible types
+          for instance in &instances {
+            match self.node_types.get(&instance.id) {
+              Some(expected) if head_columns.contains(&instance.id) => {
+                if !is_subtype(&ty, expected, self.type_aliases) {
+                  return Err(CompileError::SubtypeMismatch {
+                    loc: *instance,
+                    var_loc: *loc,
+                    ty: ty.clone(),
+                    expected: expected.clone(),
+                  });
+                }
               }
-            }
-            None => {
-              self
-                .node_types
-                .insert(instance_id.clone(), first_type.clone());
+              Some(instance_ty) if neg_columns.contains(&instance.id) => {
+                self.unify((&ty, loc), (instance_ty, instance))?;
+              }
+              _ => {}
             }
           }
+          for instance in &instances {
+            self.node_types.insert(instance.id, ty.clone());
+          }
+          var_types.insert(var_name, (ty, *loc));
         }
-      } else {
-        return Err(CompileError::CannotInferType);
+        None => return Err(CompileError::CannotInferType),
       }
     }
 
@@ -806,19 +951,17 @@ impl<'a> NodeVisitor for TypeUnification<'a> {
       if rule_id == &rule.location.id {
         match (var_a, var_b) {
           (ToUnifyArg::Variable(_, var_a), ToUnifyArg::Variable(_, var_b)) => {
-            let ty_a = var_types.get(var_a);
-            let ty_b = var_types.get(var_b);
-            match (ty_a, ty_b) {
-              (Some(ty_a), Some(ty_b)) if ty_a == ty_b => {
-                // Good
+            match (var_types.get(var_a), var_types.get(var_b)) {
+              (Some((ty_a, loc_a)), Some((ty_b, loc_b))) => {
+                self.unify((ty_a, loc_a), (ty_b, loc_b))?;
               }
               _ => return Err(CompileError::CannotUnifyType),
             }
           }
           (ToUnifyArg::Variable(_, var), ToUnifyArg::Integer(int_id))
           | (ToUnifyArg::Integer(int_id), ToUnifyArg::Variable(_, var)) => {
-            let var_ty = var_types.get(var).unwrap();
-            match var_ty {
+            let (var_ty, _) = var_types.get(var).unwrap();
+            match erase_type(var_ty, self.type_aliases) {
               Type::Integer => {
                 self.node_types.insert(int_id.clone(), Type::Integer);
               }
@@ -1066,10 +1209,251 @@ fn analyze_demand_patterns(
   Ok(())
 }
 
+/// The atoms and constraints of a fact, rule or query, sharing variables
+struct Clause<'a> {
+  atoms: Vec<&'a ast::Atom>,
+  constraints: Vec<&'a ast::Constraint>,
+}
+
+/// The type of an argument, if it can be told without unification; integer
+/// constants may as well be symbols
+fn type_of_inferred_arg(arg: &ast::Argument, var_types: &HashMap<String, Type>) -> Option<Type> {
+  match arg {
+    ast::Argument::Constant(c) => match &c.node {
+      ast::ConstantNode::Integer(_) => None,
+      ast::ConstantNode::Boolean(_) => Some(Type::Boolean),
+      ast::ConstantNode::Symbol(_) | ast::ConstantNode::SymbolId(_) => Some(Type::Symbol),
+      ast::ConstantNode::String(_) => Some(Type::String),
+    },
+    ast::Argument::Variable(v) => var_types.get(&v.node.name).cloned(),
+    ast::Argument::Binary(b) => match &b.node.op {
+      BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mult | BinaryOp::Div => Some(Type::Integer),
+      _ => Some(Type::Boolean),
+    },
+    ast::Argument::Unary(u) => match &u.node.op {
+      UnaryOp::Not => Some(Type::Boolean),
+      UnaryOp::Neg | UnaryOp::Pos => Some(Type::Integer),
+    },
+    ast::Argument::Wildcard(_) => None,
+  }
+}
+
+/// Assign the types of the variables in an argument expected to be of `ty`
+fn expect_arg_type(arg: &ast::Argument, ty: &Type, var_types: &mut HashMap<String, Type>) {
+  match arg {
+    ast::Argument::Variable(v) => {
+      var_types.entry(v.node.name.clone()).or_insert_with(|| ty.clone());
+    }
+    ast::Argument::Binary(b) => expect_operand_types(&b.node.op, &b.node.op1, &b.node.op2, var_types),
+    ast::Argument::Unary(u) => match &u.node.op {
+      UnaryOp::Not => expect_arg_type(&u.node.op1, &Type::Boolean, var_types),
+      UnaryOp::Neg | UnaryOp::Pos => expect_arg_type(&u.node.op1, &Type::Integer, var_types),
+    },
+    _ => {}
+  }
+}
+
+fn expect_operand_types(
+  op: &BinaryOp,
+  op1: &ast::Argument,
+  op2: &ast::Argument,
+  var_types: &mut HashMap<String, Type>,
+) {
+  let ty = match op {
+    BinaryOp::Eq | BinaryOp::Ne => {
+      match type_of_inferred_arg(op1, var_types).or_else(|| type_of_inferred_arg(op2, var_types)) {
+        Some(ty) => ty,
+        None => return,
+      }
+    }
+    BinaryOp::And | BinaryOp::Or => Type::Boolean,
+    _ => Type::Integer,
+  };
+  expect_arg_type(op1, &ty, var_types);
+  expect_arg_type(op2, &ty, var_types);
+}
+
+/// Infers the column types of the relations used without a declaration, by
+/// propagating the types between the relations through the variables of the
+/// facts, rules and queries until nothing changes
+struct RelationTypeInference<'a> {
+  decls: Decls,
+  clauses: Vec<Clause<'a>>,
+  columns: BTreeMap<String, Vec<Option<Type>>>,
+  /// The first atom of each undeclared relation, to locate the errors
+  first_atoms: HashMap<String, &'a ast::Atom>,
+  /// The columns holding integer constants, which are `Int` by default
+  integer_columns: BTreeSet<(String, usize)>,
+}
+
+impl<'a> RelationTypeInference<'a> {
+  fn new(prog: &'a ast::Program) -> Self {
+    let decls = prog
+      .decls
+      .iter()
+      .map(|d| (d.node.predicate.clone(), d.node.arg_types.iter().map(|t| t.node.clone()).collect()))
+      .collect::<Decls>();
+
+    // Gather the clauses
+    let mut clauses = vec![];
+    let facts = prog.facts.iter().chain(prog.disjunctions.iter().flat_map(|d| d.node.facts.iter()));
+    for fact in facts {
+      clauses.push(Clause { atoms: vec![&fact.node.head], constraints: vec![] });
+    }
+    for rule in &prog.rules {
+      let mut clause = Clause { atoms: vec![&rule.node.head], constraints: vec![] };
+      for literal in &rule.node.body {
+        match &literal.node {
+          ast::LiteralNode::Pos(atom) | ast::LiteralNode::Neg(atom) => clause.atoms.push(atom),
+          ast::LiteralNode::Constraint(c) => clause.constraints.push(c),
+        }
+      }
+      clauses.push(clause);
+    }
+    for query in &prog.queries {
+      clauses.push(Clause { atoms: vec![&query.node.atom], constraints: vec![] });
+    }
+
+    // The arity of an undeclared relation is the one of its first atom
+    let mut columns = BTreeMap::new();
+    let mut first_atoms = HashMap::new();
+    let mut integer_columns = BTreeSet::new();
+    for atom in clauses.iter().flat_map(|c| c.atoms.iter()) {
+      let pred = &atom.node.predicate;
+      if decls.contains_key(pred) {
+        continue;
+      }
+      first_atoms.entry(pred.clone()).or_insert(*atom);
+      let arity = columns.entry(pred.clone()).or_insert_with(|| vec![None; atom.node.args.len()]).len();
+      if arity == atom.node.args.len() {
+        for (i, arg) in atom.node.args.iter().enumerate() {
+          if let ast::Argument::Constant(ast::Constant { node: ast::ConstantNode::Integer(_), .. }) = arg {
+            integer_columns.insert((pred.clone(), i));
+          }
+        }
+      }
+    }
+
+    Self {
+      decls,
+      clauses,
+      columns,
+      first_atoms,
+      integer_columns,
+    }
+  }
+
+  /// The known column types of the relation of the atom, if the arity matches
+  fn column_types(&self, atom: &ast::Atom) -> Option<Vec<Option<Type>>> {
+    let pred = &atom.node.predicate;
+    let types = match self.decls.get(pred) {
+      Some(types) => types.iter().cloned().map(Some).collect::<Vec<_>>(),
+      None => self.columns.get(pred)?.clone(),
+    };
+    if types.len() == atom.node.args.len() {
+      Some(types)
+    } else {
+      None
+    }
+  }
+
+  /// Propagate the types through every clause once; returns whether a new
+  /// column type is found
+  fn propagate(&mut self) -> bool {
+    let mut changed = false;
+    for clause in &self.clauses {
+      // Find the types of the variables from the known columns and constraints
+      let mut var_types = HashMap::new();
+      loop {
+        let num_typed_vars = var_types.len();
+        for atom in &clause.atoms {
+          for (arg, ty) in atom.node.args.iter().zip(self.column_types(atom).unwrap_or_default()) {
+            if let Some(ty) = ty {
+              expect_arg_type(arg, &ty, &mut var_types);
+            }
+          }
+        }
+        for constraint in &clause.constraints {
+          match constraint {
+            ast::Constraint::Binary(b) => expect_operand_types(&b.node.op, &b.node.op1, &b.node.op2, &mut var_types),
+            ast::Constraint::Unary(u) => match &u.node.op {
+              UnaryOp::Not => expect_arg_type(&u.node.op1, &Type::Boolean, &mut var_types),
+              UnaryOp::Neg | UnaryOp::Pos => expect_arg_type(&u.node.op1, &Type::Integer, &mut var_types),
+            },
+          }
+        }
+        if var_types.len() == num_typed_vars {
+          break;
+        }
+      }
+
+      // Then the unknown columns of the undeclared relations
+      for atom in &clause.atoms {
+        let types = self.columns.get_mut(&atom.node.predicate);
+        if let Some(types) = types.filter(|types| types.len() == atom.node.args.len()) {
+          for (arg, ty) in atom.node.args.iter().zip(types.iter_mut()) {
+            if ty.is_none() {
+              *ty = type_of_inferred_arg(arg, &var_types);
+              changed |= ty.is_some();
+            }
+          }
+        }
+      }
+    }
+    changed
+  }
+
+  /// Type the unknown columns holding integer constants as `Int`; returns
+  /// whether there is any
+  fn default_integer_columns(&mut self) -> bool {
+    let mut changed = false;
+    for (pred, i) in &self.integer_columns {
+      let ty = &mut self.columns.get_mut(pred).unwrap()[*i];
+      if ty.is_none() {
+        *ty = Some(Type::Integer);
+        changed = true;
+      }
+    }
+    changed
+  }
+
+  fn inferred_decls(self) -> Result<Decls, CompileError> {
+    let mut decls = Decls::new();
+    let first_atoms = self.first_atoms;
+    for (pred, types) in self.columns {
+      let types = types.into_iter().enumerate().map(|(i, ty)| {
+        ty.ok_or_else(|| CompileError::AmbiguousRelationType {
+          loc: *first_atoms[&pred].node.args[i].location(),
+          rela_name: pred.clone(),
+          column: i,
+        })
+      });
+      decls.insert(pred.clone(), types.collect::<Result<Vec<_>, _>>()?);
+    }
+    Ok(decls)
+  }
+}
+
+/// The column types of the relations used without a declaration in the program
+pub fn infer_relation_types(prog: &ast::Program) -> Result<Decls, CompileError> {
+  let mut inference = RelationTypeInference::new(prog);
+  loop {
+    while inference.propagate() {}
+    if !inference.default_integer_columns() {
+      break;
+    }
+  }
+  inference.inferred_decls()
+}
+
 pub fn analyze(prog: &ast::Program, options: &CompileOptions) -> Result<AnalysisResult, CompileError> {
+  // Infer the types of the undeclared relations
+  let mut type_assign = TypeAssign::new();
+  type_assign.decls = infer_relation_types(prog)?;
+
   // Run analysis first pass
   let mut first_pass = (
-    TypeAssign::new(),
+    type_assign,
     IsProbabilisticAnalyzer::new(),
     DisjunctionOnSameRelationChecker::new(),
     DemandCollector::new(),
@@ -1081,6 +1465,7 @@ pub fn analyze(prog: &ast::Program, options: &CompileOptions) -> Result<Analysis
   visit_program(&mut first_pass, prog)?;
   let type_assign = first_pass.0;
   let decls = type_assign.decls;
+  let type_aliases = type_assign.type_aliases;
   let mut node_types = type_assign.node_types;
   let to_unify_args = type_assign.to_unify_args;
   let is_probabilistic = first_pass.1.is_probabilistic;
@@ -1088,7 +1473,7 @@ pub fn analyze(prog: &ast::Program, options: &CompileOptions) -> Result<Analysis
   let mut demand_collector = first_pass.3;
 
   // Run second pass to unify types
-  let mut second_pass = (TypeUnification::new(&mut node_types, to_unify_args),);
+  let mut second_pass = (TypeUnification::new(&mut node_types, to_unify_args, &type_aliases),);
   visit_program(&mut second_pass, prog)?;
 
   // Propagate demand patterns if enabled
@@ -1102,6 +1487,7 @@ pub fn analyze(prog: &ast::Program, options: &CompileOptions) -> Result<Analysis
   Ok(AnalysisResult {
     is_probabilistic,
     decls,
+    type_aliases,
     node_types,
     disj_rela_map,
     demands,
@@ -0,0 +1,376 @@
+use std::collections::*;
+
+use super::ast;
+use super::common::*;
+use super::error::*;
+use super::symbols;
+
+/// A concrete value of a relation tuple
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Value {
+  Symbol(String),
+  SymbolId(usize),
+  Integer(i64),
+  Boolean(bool),
+  String(String),
+}
+
+impl Value {
+  pub fn from_constant(c: &ast::ConstantNode) -> Self {
+    match c {
+      ast::ConstantNode::Symbol(s) => Self::Symbol(s.clone()),
+      ast::ConstantNode::SymbolId(i) => Self::SymbolId(*i),
+      ast::ConstantNode::Integer(i) => Self::Integer(*i),
+      ast::ConstantNode::Boolean(b) => Self::Boolean(*b),
+      ast::ConstantNode::String(s) => Self::String(s.clone()),
+    }
+  }
+}
+
+impl std::fmt::Display for Value {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Symbol(s) => write!(f, "{}", s),
+      Self::SymbolId(i) => write!(f, "{}", symbols::format_symbol(*i)),
+      Self::Integer(i) => write!(f, "{}", i),
+      Self::Boolean(b) => write!(f, "{}", b),
+      Self::String(s) => write!(f, "\"{}\"", s),
+    }
+  }
+}
+
+pub type ValueTuple = Vec<Value>;
+
+/// Format a tuple of a relation as an atom, e.g. `edge(0, 1)`
+pub fn format_fact(predicate: &str, tuple: &[Value]) -> String {
+  let args = tuple.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+  format!("{}({})", predicate, args.join(", "))
+}
+
+/// Assignment of the variables of a rule
+pub type Bindings = BTreeMap<String, Value>;
+
+/// An input fact, along with its probability
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputFact {
+  pub prob: Option<f64>,
+  pub disjunction: Option<usize>,
+}
+
+/// The contents of all the relations after the evaluation
+///
+/// Every tuple is associated with the round in which it was first derived;
+/// input facts are derived in round 0, and a tuple derived in round `r` has
+/// a derivation whose body tuples are all derived before `r`.
+#[derive(Clone, Debug, Default)]
+pub struct Database {
+  pub relations: BTreeMap<String, BTreeMap<ValueTuple, usize>>,
+  pub input_facts: HashMap<(String, ValueTuple), InputFact>,
+}
+
+impl Database {
+  pub fn tuples(&self, predicate: &str) -> impl Iterator<Item = (&ValueTuple, &usize)> {
+    self.relations.get(predicate).into_iter().flat_map(|r| r.iter())
+  }
+
+  /// The round in which a tuple was first derived, if it is derived at all
+  pub fn round_of(&self, predicate: &str, tuple: &[Value]) -> Option<usize> {
+    self.relations.get(predicate).and_then(|r| r.get(tuple)).cloned()
+  }
+
+  pub fn input_fact(&self, predicate: &str, tuple: &[Value]) -> Option<&InputFact> {
+    self.input_facts.get(&(predicate.to_string(), tuple.to_vec()))
+  }
+
+  fn insert(&mut self, predicate: &str, tuple: ValueTuple, round: usize) -> bool {
+    match self.relations.entry(predicate.to_string()).or_default().entry(tuple) {
+      btree_map::Entry::Occupied(_) => false,
+      btree_map::Entry::Vacant(e) => {
+        e.insert(round);
+        true
+      }
+    }
+  }
+}
+
+/// Evaluate the program until fixpoint
+///
+/// This is a semi-naive evaluation over concrete values, not meant to be
+/// fast; it is used by the analyses relating the output tuples back to the
+/// rules, such as derivation-tree explanations. The program is expected to be
+/// analyzed, with its integers used as symbols coerced.
+pub fn evaluate(prog: &ast::Program) -> Result<Database, CompileError> {
+  let mut db = Database::default();
+  for decl in &prog.decls {
+    db.relations.entry(decl.node.predicate.clone()).or_default();
+  }
+
+  // Input facts
+  let facts = prog.facts.iter().map(|f| (f, None));
+  let disj_facts = prog
+    .disjunctions
+    .iter()
+    .enumerate()
+    .flat_map(|(i, d)| d.node.facts.iter().map(move |f| (f, Some(i))));
+  for (fact, disjunction) in facts.chain(disj_facts) {
+    let tuple = fact
+      .node
+      .head
+      .node
+      .args
+      .iter()
+      .map(|arg| eval_arg(arg, &Bindings::new()))
+      .collect::<Option<Vec<_>>>()
+      .ok_or(CompileError::FactWithNonConstant { loc: fact.location })?;
+    let predicate = &fact.node.head.node.predicate;
+    db.insert(predicate, tuple.clone(), 0);
+    let input = InputFact { prob: fact.node.prob, disjunction };
+    db.input_facts.insert((predicate.clone(), tuple), input);
+  }
+
+  // Negation is not supported by the compiler either
+  if prog
+    .rules
+    .iter()
+    .any(|r| r.node.body.iter().any(|l| matches!(l.node, ast::LiteralNode::Neg(_))))
+  {
+    return Err(CompileError::NegationNotImplemented);
+  }
+
+  // Semi-naive evaluation; every round only sees the tuples of the previous
+  // rounds, and only the matches using a tuple of the last round are new
+  let mut round = 1;
+  loop {
+    let mut new_tuples = vec![];
+    for rule in &prog.rules {
+      let num_atoms = body_atoms(rule).len();
+      let mut matches = vec![];
+      if num_atoms == 0 && round == 1 {
+        matches.extend(rule_matches(&db, rule, &Bindings::new()));
+      }
+      for delta in 0..num_atoms {
+        // The atoms before the one matching the last round match older tuples
+        let rounds = |i: usize, r: usize| match i.cmp(&delta) {
+          std::cmp::Ordering::Less => r + 1 < round,
+          std::cmp::Ordering::Equal => r + 1 == round,
+          std::cmp::Ordering::Greater => true,
+        };
+        matches.extend(rule_matches_in_rounds(&db, rule, &Bindings::new(), &rounds));
+      }
+      for m in matches {
+        if let Some(tuple) = eval_head(rule, &m.bindings) {
+          let predicate = &rule.node.head.node.predicate;
+          if db.round_of(predicate, &tuple).is_none() {
+            new_tuples.push((predicate.clone(), tuple));
+          }
+        }
+      }
+    }
+    let mut changed = false;
+    for (predicate, tuple) in new_tuples {
+      changed |= db.insert(&predicate, tuple, round);
+    }
+    if !changed {
+      break;
+    }
+    round += 1;
+  }
+  Ok(db)
+}
+
+/// The head tuple of a rule under the bindings of its body
+pub fn eval_head(rule: &ast::Rule, bindings: &Bindings) -> Option<ValueTuple> {
+  rule.node.head.node.args.iter().map(|arg| eval_arg(arg, bindings)).collect()
+}
+
+/// A way to satisfy the body of a rule: the bindings of its variables and the
+/// tuples matched by its body atoms, in order
+#[derive(Clone, Debug)]
+pub struct RuleMatch {
+  pub bindings: Bindings,
+  pub body_tuples: Vec<ValueTuple>,
+}
+
+/// All the matches of the body of a rule, extending the bindings `init`
+pub fn rule_matches(db: &Database, rule: &ast::Rule, init: &Bindings) -> Vec<RuleMatch> {
+  rule_matches_in_rounds(db, rule, init, &|_, _| true)
+}
+
+/// The matches of the body of a rule where the `i`-th body atom only matches
+/// the tuples derived in a round `r` such that `rounds(i, r)`
+fn rule_matches_in_rounds(
+  db: &Database,
+  rule: &ast::Rule,
+  init: &Bindings,
+  rounds: &dyn Fn(usize, usize) -> bool,
+) -> Vec<RuleMatch> {
+  let atoms = body_atoms(rule);
+  let mut all = vec![];
+  join_atoms(db, &atoms, rounds, init.clone(), &mut vec![], &mut |bindings, body_tuples| {
+    if body_constraints(rule).all(|c| eval_constraint(c, bindings) == Some(true)) {
+      all.push(RuleMatch {
+        bindings: bindings.clone(),
+        body_tuples: body_tuples.to_vec(),
+      });
+    }
+  });
+  all
+}
+
+/// The bindings of the variables of the head of a rule so that it may produce
+/// the tuple; the arguments that are expressions are left unchecked
+pub fn head_bindings(rule: &ast::Rule, tuple: &[Value]) -> Option<Bindings> {
+  let head = &rule.node.head.node;
+  if head.args.len() != tuple.len() {
+    return None;
+  }
+  let mut bindings = Bindings::new();
+  for (arg, value) in head.args.iter().zip(tuple) {
+    match arg {
+      ast::Argument::Variable(v) => match bindings.get(&v.node.name) {
+        Some(bound) if bound != value => return None,
+        Some(_) => {}
+        None => {
+          bindings.insert(v.node.name.clone(), value.clone());
+        }
+      },
+      ast::Argument::Constant(c) if &Value::from_constant(&c.node) != value => return None,
+      _ => {}
+    }
+  }
+  Some(bindings)
+}
+
+/// The positive atoms in the body of a rule
+pub fn body_atoms(rule: &ast::Rule) -> Vec<&ast::Atom> {
+  rule
+    .node
+    .body
+    .iter()
+    .filter_map(|l| match &l.node {
+      ast::LiteralNode::Pos(a) => Some(a),
+      _ => None,
+    })
+    .collect()
+}
+
+/// The constraints in the body of a rule
+pub fn body_constraints(rule: &ast::Rule) -> impl Iterator<Item = &ast::Constraint> {
+  rule.node.body.iter().filter_map(|l| match &l.node {
+    ast::LiteralNode::Constraint(c) => Some(c),
+    _ => None,
+  })
+}
+
+fn join_atoms<F>(
+  db: &Database,
+  atoms: &[&ast::Atom],
+  rounds: &dyn Fn(usize, usize) -> bool,
+  bindings: Bindings,
+  matched: &mut Vec<ValueTuple>,
+  f: &mut F,
+) where
+  F: FnMut(&Bindings, &[ValueTuple]),
+{
+  match atoms.get(matched.len()) {
+    None => f(&bindings, matched),
+    Some(atom) => {
+      for (tuple, round) in db.tuples(&atom.node.predicate) {
+        if !rounds(matched.len(), *round) {
+          continue;
+        }
+        if let Some(bindings) = match_atom(atom, tuple, &bindings) {
+          matched.push(tuple.clone());
+          join_atoms(db, atoms, rounds, bindings, matched, f);
+          matched.pop();
+        }
+      }
+    }
+  }
+}
+
+/// Extend the bindings so that the atom matches the tuple
+pub fn match_atom(atom: &ast::Atom, tuple: &[Value], bindings: &Bindings) -> Option<Bindings> {
+  if atom.node.args.len() != tuple.len() {
+    return None;
+  }
+  let mut bindings = bindings.clone();
+  for (arg, value) in atom.node.args.iter().zip(tuple) {
+    match arg {
+      ast::Argument::Wildcard(_) => {}
+      ast::Argument::Variable(v) => match bindings.get(&v.node.name) {
+        Some(bound) if bound != value => return None,
+        Some(_) => {}
+        None => {
+          bindings.insert(v.node.name.clone(), value.clone());
+        }
+      },
+      other => {
+        if eval_arg(other, &bindings).as_ref() != Some(value) {
+          return None;
+        }
+      }
+    }
+  }
+  Some(bindings)
+}
+
+/// Evaluate an argument; returns `None` if a variable is unbound or if the
+/// operation is not defined on the values
+pub fn eval_arg(arg: &ast::Argument, bindings: &Bindings) -> Option<Value> {
+  match arg {
+    ast::Argument::Wildcard(_) => None,
+    ast::Argument::Constant(c) => Some(Value::from_constant(&c.node)),
+    ast::Argument::Variable(v) => bindings.get(&v.node.name).cloned(),
+    ast::Argument::Unary(u) => eval_unary(&u.node.op, eval_arg(&u.node.op1, bindings)?),
+    ast::Argument::Binary(b) => {
+      let op1 = eval_arg(&b.node.op1, bindings)?;
+      let op2 = eval_arg(&b.node.op2, bindings)?;
+      eval_binary(&b.node.op, op1, op2)
+    }
+  }
+}
+
+/// Evaluate a constraint; returns `None` if it cannot be evaluated
+pub fn eval_constraint(c: &ast::Constraint, bindings: &Bindings) -> Option<bool> {
+  let value = match c {
+    ast::Constraint::Binary(b) => {
+      let op1 = eval_arg(&b.node.op1, bindings)?;
+      let op2 = eval_arg(&b.node.op2, bindings)?;
+      eval_binary(&b.node.op, op1, op2)?
+    }
+    ast::Constraint::Unary(u) => eval_unary(&u.node.op, eval_arg(&u.node.op1, bindings)?)?,
+  };
+  match value {
+    Value::Boolean(b) => Some(b),
+    _ => None,
+  }
+}
+
+fn eval_unary(op: &UnaryOp, v: Value) -> Option<Value> {
+  match (op, v) {
+    (UnaryOp::Not, Value::Boolean(b)) => Some(Value::Boolean(!b)),
+    (UnaryOp::Pos, Value::Integer(i)) => Some(Value::Integer(i)),
+    (UnaryOp::Neg, Value::Integer(i)) => i.checked_neg().map(Value::Integer),
+    _ => None,
+  }
+}
+
+fn eval_binary(op: &BinaryOp, v1: Value, v2: Value) -> Option<Value> {
+  use Value::*;
+  match (op, v1, v2) {
+    (BinaryOp::Eq, a, b) => Some(Boolean(a == b)),
+    (BinaryOp::Ne, a, b) => Some(Boolean(a != b)),
+    (BinaryOp::Lt, Integer(a), Integer(b)) => Some(Boolean(a < b)),
+    (BinaryOp::Lte, Integer(a), Integer(b)) => Some(Boolean(a <= b)),
+    (BinaryOp::Gt, Integer(a), Integer(b)) => Some(Boolean(a > b)),
+    (BinaryOp::Gte, Integer(a), Integer(b)) => Some(Boolean(a >= b)),
+    (BinaryOp::And, Boolean(a), Boolean(b)) => Some(Boolean(a && b)),
+    (BinaryOp::Or, Boolean(a), Boolean(b)) => Some(Boolean(a || b)),
+    (BinaryOp::Add, Integer(a), Integer(b)) => a.checked_add(b).map(Integer),
+    (BinaryOp::Sub, Integer(a), Integer(b)) => a.checked_sub(b).map(Integer),
+    (BinaryOp::Mult, Integer(a), Integer(b)) => a.checked_mul(b).map(Integer),
+    (BinaryOp::Div, Integer(a), Integer(b)) => a.checked_div(b).map(Integer),
+    _ => None,
+  }
+}
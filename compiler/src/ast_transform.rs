@@ -12,7 +12,7 @@ pub struct ConstIntegerToConstSymbol<'a> {
 }
 
 impl<'a> ConstIntegerToConstSymbol<'a> {
-  fn new(node_types: &'a NodeTypeMap) -> Self {
+  pub fn new(node_types: &'a NodeTypeMap) -> Self {
     Self { node_types }
   }
 }
@@ -32,6 +32,25 @@ impl<'a> NodeVisitorMut for ConstIntegerToConstSymbol<'a> {
   }
 }
 
+/// Replaces the declared types by their base types, e.g. `Symbol` for `Person`
+/// declared with `type Person <: Symbol.`
+pub struct TypeErasure<'a> {
+  type_aliases: &'a TypeAliases,
+}
+
+impl<'a> TypeErasure<'a> {
+  fn new(type_aliases: &'a TypeAliases) -> Self {
+    Self { type_aliases }
+  }
+}
+
+impl<'a> NodeVisitorMut for TypeErasure<'a> {
+  fn visit_type(&mut self, ty: &mut ast::Type) -> Result<(), CompileError> {
+    ty.node = erase_type(&ty.node, self.type_aliases);
+    Ok(())
+  }
+}
+
 fn demand_transform(prog: &mut ast::Program, anal: &mut AnalysisResult) -> Result<(), CompileError> {
   let demands = &anal.demands;
 
@@ -152,6 +171,21 @@ pub fn transform(
   anal: &mut AnalysisResult,
   options: &CompileOptions,
 ) -> Result<(), CompileError> {
+  // Declare the relations whose types are inferred
+  let declared = prog.decls.iter().map(|d| d.node.predicate.clone()).collect::<HashSet<_>>();
+  let mut inferred = anal.decls.iter().filter(|(pred, _)| !declared.contains(*pred)).collect::<Vec<_>>();
+  inferred.sort_by_key(|(pred, _)| *pred);
+  for (pred, types) in inferred {
+    let types = types.iter().map(|ty| ast::Type::new(ty.clone())).collect::<Vec<_>>();
+    prog.decls.push(ast::Decl::new((pred.clone(), types)));
+  }
+
+  // Erase the declared types
+  let aliases = &anal.type_aliases;
+  visit_program_mut(&mut TypeErasure::new(aliases), prog)?;
+  anal.node_types.values_mut().for_each(|ty| *ty = erase_type(ty, aliases));
+  anal.decls.values_mut().flatten().for_each(|ty| *ty = erase_type(ty, aliases));
+
   // First pass: in AST transformation
   let mut transfs = (
     ConstIntegerToConstSymbol::new(&anal.node_types),
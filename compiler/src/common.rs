@@ -4,6 +4,9 @@ pub enum Type {
   Integer,
   Boolean,
   String,
+  /// A type declared with `type Person <: Symbol`, erased into its base
+  /// type after the analysis
+  Named(String),
 }
 
 impl std::fmt::Display for Type {
@@ -13,6 +16,7 @@ impl std::fmt::Display for Type {
       Self::Integer => write!(f, "Int"),
       Self::Boolean => write!(f, "Bool"),
       Self::String => write!(f, "String"),
+      Self::Named(n) => write!(f, "{}", n),
     }
   }
 }
@@ -10,9 +10,19 @@ pub enum CompileError {
   // File related
   CannotOpenFile,
   CannotReadFile,
+  CannotImportFile {
+    loc: Location,
+    path: String,
+  },
+  CyclicImport {
+    loc: Location,
+    cycle: Vec<String>,
+  },
 
   // Parse errors
-  SyntaxError,
+  SyntaxError {
+    file: Option<String>,
+  },
 
   // Compile errors
   FactWithNonConstant {
@@ -26,12 +36,35 @@ pub enum CompileError {
     loc: Location,
     rela_name: String,
   },
+  DuplicatedTypeDeclaration {
+    dup: Location,
+    type_name: String,
+  },
+  UnknownType {
+    loc: Location,
+    type_name: String,
+  },
   IncorrectArity {
     loc: Location,
     rela_name: String,
     found: usize,
     expected: usize,
   },
+  UnknownField {
+    loc: Location,
+    rela_name: String,
+    field: String,
+  },
+  DuplicatedField {
+    loc: Location,
+    rela_name: String,
+    field: String,
+  },
+  ReservedField {
+    loc: Location,
+    rela_name: String,
+    field: String,
+  },
   ExpressionInBodyLiteral {
     loc: Location,
   },
@@ -61,6 +94,19 @@ pub enum CompileError {
     ty: Type,
   },
 
+  FieldTypeMismatch {
+    loc: Location,
+    rela_name: String,
+    field: String,
+    ty: Type,
+  },
+
+  AmbiguousRelationType {
+    loc: Location,
+    rela_name: String,
+    column: usize,
+  },
+
   TypeUnificationError {
     loc_1: Location,
     loc_2: Location,
@@ -68,6 +114,13 @@ pub enum CompileError {
     ty_2: Type,
   },
 
+  SubtypeMismatch {
+    loc: Location,
+    var_loc: Location,
+    ty: Type,
+    expected: Type,
+  },
+
   CannotInferType,
   CannotUnifyType,
   UnnecessaryIdentityComparison,
@@ -90,9 +143,16 @@ impl std::fmt::Display for CompileError {
       // File related
       Self::CannotOpenFile => write!(f, "Cannot open file"),
       Self::CannotReadFile => write!(f, "Cannot read file"),
+      Self::CannotImportFile { loc, path } => {
+        write!(f, "[{}] Cannot import file \"{}\"", loc, path)
+      }
+      Self::CyclicImport { loc, cycle } => {
+        write!(f, "[{}] Cyclic import: {}", loc, cycle.join(" -> "))
+      }
 
       // Parse Errors
-      Self::SyntaxError => write!(f, "Syntax error"),
+      Self::SyntaxError { file: Some(file) } => write!(f, "Syntax error in file {}", file),
+      Self::SyntaxError { file: None } => write!(f, "Syntax error"),
 
       // Compile Errors
       Self::FactWithNonConstant { loc } => {
@@ -108,6 +168,12 @@ impl std::fmt::Display for CompileError {
       Self::UnknownRelation { loc, rela_name } => {
         write!(f, "[{}] Unknown relation {}", loc, rela_name)
       }
+      Self::DuplicatedTypeDeclaration { dup, type_name } => {
+        write!(f, "[{}] Duplicated declaration of type {}", dup, type_name)
+      }
+      Self::UnknownType { loc, type_name } => {
+        write!(f, "[{}] Unknown type {}", loc, type_name)
+      }
       Self::IncorrectArity {
         loc,
         rela_name,
@@ -123,9 +189,27 @@ impl std::fmt::Display for CompileError {
       Self::TypeMismatch { loc, ty } => {
         write!(f, "[{}] Type mismatch: expected {} type", loc, ty)
       },
+      Self::FieldTypeMismatch { loc, rela_name, field, ty } => {
+        write!(f, "[{}] Type mismatch: expected {} type for field `{}` of relation {}", loc, ty, field, rela_name)
+      },
+      Self::UnknownField { loc, rela_name, field } => {
+        write!(f, "[{}] Unknown field `{}` of relation {}", loc, field, rela_name)
+      },
+      Self::DuplicatedField { loc, rela_name, field } => {
+        write!(f, "[{}] Duplicated field `{}` of relation {}", loc, field, rela_name)
+      },
+      Self::ReservedField { loc, rela_name, field } => {
+        write!(f, "[{}] Reserved name `{}` for a field of relation {}", loc, field, rela_name)
+      },
+      Self::AmbiguousRelationType { loc, rela_name, column } => {
+        write!(f, "[{}] Cannot infer the type of argument {} of relation {}; consider declaring it", loc, column + 1, rela_name)
+      },
       Self::TypeUnificationError { loc_1, loc_2, ty_1, ty_2 } => {
         write!(f, "Cannot unify two types: {} at [{}] and {} at [{}]", ty_1, loc_1, ty_2, loc_2)
       },
+      Self::SubtypeMismatch { loc, var_loc, ty, expected } => {
+        write!(f, "[{}] Type mismatch: expected {} type, found {} from [{}]", loc, expected, ty, var_loc)
+      },
       Self::ExpressionInBodyLiteral { loc } => {
         write!(f, "[{}] Disallow expression in body literals", loc)
       }
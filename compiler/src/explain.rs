@@ -0,0 +1,217 @@
+use super::ast;
+use super::ast_analysis;
+use super::ast_eval::*;
+use super::ast_transform::ConstIntegerToConstSymbol;
+use super::error::*;
+use super::options::CompileOptions;
+use super::visitor::*;
+
+/// A derivation tree of a tuple
+#[derive(Clone, Debug, PartialEq)]
+pub enum Derivation {
+  /// An input fact, with its probability if any
+  Fact {
+    predicate: String,
+    tuple: ValueTuple,
+    prob: Option<f64>,
+  },
+
+  /// A tuple derived by a rule from the tuples matched by its body atoms
+  Rule {
+    predicate: String,
+    tuple: ValueTuple,
+    rule: String,
+    row: usize,
+    body: Vec<Derivation>,
+  },
+}
+
+impl Derivation {
+  pub fn predicate(&self) -> &str {
+    match self {
+      Self::Fact { predicate, .. } | Self::Rule { predicate, .. } => predicate,
+    }
+  }
+
+  pub fn tuple(&self) -> &[Value] {
+    match self {
+      Self::Fact { tuple, .. } | Self::Rule { tuple, .. } => tuple,
+    }
+  }
+
+  /// The leaves of the tree, from left to right
+  pub fn input_facts(&self) -> Vec<(&str, &[Value])> {
+    match self {
+      Self::Fact { predicate, tuple, .. } => vec![(predicate.as_str(), tuple.as_slice())],
+      Self::Rule { body, .. } => body.iter().flat_map(Derivation::input_facts).collect(),
+    }
+  }
+
+  /// The number of rule applications along the longest branch
+  pub fn depth(&self) -> usize {
+    match self {
+      Self::Fact { .. } => 0,
+      Self::Rule { body, .. } => 1 + body.iter().map(Derivation::depth).max().unwrap_or(0),
+    }
+  }
+
+  fn fmt_indented(&self, f: &mut std::fmt::Formatter<'_>, indent: usize) -> std::fmt::Result {
+    let fact = format_fact(self.predicate(), self.tuple());
+    match self {
+      Self::Fact { prob: Some(prob), .. } => writeln!(f, "{:indent$}{} [fact {}]", "", fact, prob, indent = indent),
+      Self::Fact { prob: None, .. } => writeln!(f, "{:indent$}{} [fact]", "", fact, indent = indent),
+      Self::Rule { rule, row, body, .. } => {
+        writeln!(f, "{:indent$}{} <- {} (line {})", "", fact, rule, row, indent = indent)?;
+        for child in body {
+          child.fmt_indented(f, indent + 2)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// Indented text, one tuple per line, e.g.
+///
+/// ```text
+/// path(0, 2) <- path(A, C) :- edge(A, B), path(B, C). (line 5)
+///   edge(0, 1) [fact]
+///   path(1, 2) <- path(A, B) :- edge(A, B). (line 4)
+///     edge(1, 2) [fact 0.5]
+/// ```
+impl std::fmt::Display for Derivation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.fmt_indented(f, 0)
+  }
+}
+
+/// Computes derivation trees for the tuples of a program
+///
+/// The program is analyzed as by the compiler, then evaluated on concrete
+/// values before any other transformation, so that the rules in the
+/// explanations are the ones written by the user.
+pub struct Explainer {
+  prog: ast::Program,
+  db: Database,
+}
+
+impl Explainer {
+  pub fn new(prog: &ast::Program) -> Result<Self, CompileError> {
+    let analysis = ast_analysis::analyze(prog, &CompileOptions::default())?;
+    let mut prog = prog.clone();
+    visit_program_mut(&mut ConstIntegerToConstSymbol::new(&analysis.node_types), &mut prog)?;
+    let db = evaluate(&prog)?;
+    Ok(Self { prog, db })
+  }
+
+  pub fn program(&self) -> &ast::Program {
+    &self.prog
+  }
+
+  pub fn database(&self) -> &Database {
+    &self.db
+  }
+
+  /// One derivation for each tuple matching the atom, e.g. `path(0, X)`
+  pub fn explain(&self, atom: &ast::Atom) -> Vec<Derivation> {
+    self
+      .db
+      .tuples(&atom.node.predicate)
+      .filter(|(tuple, _)| match_atom(atom, tuple, &Bindings::new()).is_some())
+      .filter_map(|(tuple, _)| self.explain_tuple(&atom.node.predicate, tuple))
+      .collect()
+  }
+
+  /// A shallowest derivation of the tuple, if it is derived at all
+  ///
+  /// The body tuples of the chosen rule application are all derived in
+  /// earlier rounds than the tuple itself, so the tree is finite even for
+  /// recursive rules.
+  pub fn explain_tuple(&self, predicate: &str, tuple: &[Value]) -> Option<Derivation> {
+    let round = self.db.round_of(predicate, tuple)?;
+    if let Some(fact) = self.db.input_fact(predicate, tuple) {
+      return Some(Derivation::Fact {
+        predicate: predicate.to_string(),
+        tuple: tuple.to_vec(),
+        prob: fact.prob,
+      });
+    }
+    for rule in self.prog.rules.iter().filter(|r| r.node.head.node.predicate == predicate) {
+      let init = match head_bindings(rule, tuple) {
+        Some(init) => init,
+        None => continue,
+      };
+      let atoms = body_atoms(rule);
+      for m in rule_matches(&self.db, rule, &init) {
+        let earlier = atoms
+          .iter()
+          .zip(&m.body_tuples)
+          .all(|(atom, t)| self.db.round_of(&atom.node.predicate, t).is_some_and(|r| r < round));
+        if earlier && eval_head(rule, &m.bindings).as_deref() == Some(tuple) {
+          let body = atoms
+            .iter()
+            .zip(&m.body_tuples)
+            .map(|(atom, t)| self.explain_tuple(&atom.node.predicate, t))
+            .collect::<Option<Vec<_>>>()?;
+          return Some(Derivation::Rule {
+            predicate: predicate.to_string(),
+            tuple: tuple.to_vec(),
+            rule: rule.codify(),
+            row: rule.location.row,
+            body,
+          });
+        }
+      }
+    }
+    None
+  }
+}
+
+/// Graphviz graph of the derivations, where tuples point to the rule
+/// applications deriving them, which point to their body tuples
+pub fn to_dot(derivations: &[Derivation]) -> String {
+  let mut lines = vec!["digraph derivations {".to_string()];
+  let mut counter = 0;
+  for derivation in derivations {
+    dot_node(derivation, &mut counter, &mut lines);
+  }
+  lines.push("}".to_string());
+  lines.join("\n") + "\n"
+}
+
+/// Add the nodes of a derivation; returns the id of its root
+fn dot_node(derivation: &Derivation, counter: &mut usize, lines: &mut Vec<String>) -> usize {
+  let id = *counter;
+  *counter += 1;
+  let fact = escape_dot(&format_fact(derivation.predicate(), derivation.tuple()));
+  match derivation {
+    Derivation::Fact { prob, .. } => {
+      let label = match prob {
+        Some(prob) => format!("{}\\n{}", fact, prob),
+        None => fact,
+      };
+      lines.push(format!("  n{} [label=\"{}\", shape=box];", id, label));
+    }
+    Derivation::Rule { rule, row, body, .. } => {
+      let rule_id = *counter;
+      *counter += 1;
+      lines.push(format!("  n{} [label=\"{}\"];", id, fact));
+      lines.push(format!(
+        "  n{} [label=\"{}\\nline {}\", shape=plaintext];",
+        rule_id,
+        escape_dot(rule),
+        row
+      ));
+      lines.push(format!("  n{} -> n{};", id, rule_id));
+      for child in body {
+        let child_id = dot_node(child, counter, lines);
+        lines.push(format!("  n{} -> n{};", rule_id, child_id));
+      }
+    }
+  }
+  id
+}
+
+fn escape_dot(s: &str) -> String {
+  s.replace('\\', "\\\\").replace('"', "\\\"")
+}
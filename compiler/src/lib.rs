@@ -1,14 +1,18 @@
 pub mod ast;
 pub mod ast2ram;
 pub mod ast_analysis;
+pub mod ast_eval;
 pub mod ast_transform;
 pub mod common;
 pub mod error;
+pub mod explain;
 pub mod location;
 pub mod parser;
 pub mod ram;
 pub mod ram2rs;
+pub mod symbols;
 pub mod visitor;
+pub mod why_not;
 pub mod options;
 
 mod syntax;
@@ -1,3 +1,5 @@
+use std::sync::{OnceLock, RwLock};
+
 pub trait Node: Clone {
   type T;
 
@@ -11,11 +13,17 @@ pub struct Location {
   pub length: usize,
   pub row: usize,
   pub col: usize,
+  /// The source file, as registered with `register_source_file`; 0 if the
+  /// node does not come from a file
+  pub file: usize,
 }
 
 impl std::fmt::Display for Location {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(f, "{}:{}", self.row, self.col)
+    match source_file_name(self.file) {
+      Some(file) => write!(f, "{}:{}:{}", file, self.row, self.col),
+      None => write!(f, "{}:{}", self.row, self.col),
+    }
   }
 }
 
@@ -27,6 +35,7 @@ impl Location {
       length: 0,
       row: 0,
       col: 0,
+      file: 0,
     }
   }
 
@@ -37,6 +46,7 @@ impl Location {
       length: 0,
       row: 0,
       col: 0,
+      file: 0,
     }
   }
 
@@ -47,6 +57,7 @@ impl Location {
       length: 0,
       row: 0,
       col: 0,
+      file: 0,
     }
   }
 
@@ -57,10 +68,37 @@ impl Location {
       length: end - begin,
       row: 0,
       col: 0,
+      file: 0,
     }
   }
 }
 
+/// The source files of the program being compiled, e.g. the imported ones
+fn source_files() -> &'static RwLock<Vec<String>> {
+  static FILES: OnceLock<RwLock<Vec<String>>> = OnceLock::new();
+  FILES.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// The id of the source file to be stored in the locations, starting from 1
+pub fn register_source_file(name: &str) -> usize {
+  let mut files = source_files().write().unwrap();
+  match files.iter().position(|file| file == name) {
+    Some(i) => i + 1,
+    None => {
+      files.push(name.to_string());
+      files.len()
+    }
+  }
+}
+
+pub fn source_file_name(id: usize) -> Option<String> {
+  if id == 0 {
+    None
+  } else {
+    source_files().read().unwrap().get(id - 1).cloned()
+  }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Located<T: Node> {
   pub location: Location,
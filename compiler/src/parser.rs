@@ -1,5 +1,7 @@
+use std::collections::*;
 use std::fs::File;
 use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 use termion::color;
 
 use super::ast::*;
@@ -9,11 +11,13 @@ use super::syntax;
 use super::visitor::*;
 
 pub enum Item {
+  TypeDecl(TypeDecl),
   Decl(Decl),
   Fact(Fact),
   Disjunction(Disjunction),
   Rule(Rule),
   Query(Query),
+  Import(Import),
 }
 
 fn row_col(src: &str, byte_offset: usize) -> (usize, usize) {
@@ -33,15 +37,20 @@ fn row_col(src: &str, byte_offset: usize) -> (usize, usize) {
 
 type ParseError<'a> = lalrpop_util::ParseError<usize, lalrpop_util::lexer::Token<'a>, &'a str>;
 
-fn print_syntax_error(s: &str, e: ParseError) -> CompileError {
+/// Print a syntax error of the source `s` of the registered source `file`
+/// (`0` if the source is not from a file)
+fn print_syntax_error(s: &str, file: usize, e: ParseError) -> CompileError {
+  let file = source_file_name(file);
+  let in_file = file.as_ref().map(|name| format!(" in file {}", name)).unwrap_or_default();
   match &e {
     ParseError::UnrecognizedToken { token, .. } => {
       let offset = token.0;
       let (row, col) = row_col(s, offset);
       println!(
-        "{}[Error]{} Syntax error at row {} col {}:",
+        "{}[Error]{} Syntax error{} at row {} col {}:",
         color::Fg(color::Red),
         color::Fg(color::Reset),
+        in_file,
         row,
         col,
       );
@@ -50,18 +59,26 @@ fn print_syntax_error(s: &str, e: ParseError) -> CompileError {
       println!("{}", e);
     }
     _ => {
+      println!("{}[Error]{} Syntax error{}:", color::Fg(color::Red), color::Fg(color::Reset), in_file);
       println!("{}", e);
     }
   }
-  CompileError::SyntaxError
+  CompileError::SyntaxError { file }
 }
 
+/// Parse a rule; the relations of its atoms have no named fields
 pub fn parse_rule(s: &str) -> Result<Rule, CompileError> {
+  parse_rule_with_decls(s, &[])
+}
+
+/// Parse a rule whose atoms may name the fields of the declared relations
+pub fn parse_rule_with_decls(s: &str, decls: &[Decl]) -> Result<Rule, CompileError> {
   let parser = syntax::RuleParser::new();
-  let result = parser.parse(&s).map_err(|e| {
-    print_syntax_error(s, e)
-  });
-  result
+  let mut rule = parser.parse(s).map_err(|e| {
+    print_syntax_error(s, 0, e)
+  })?;
+  visit_rule_mut(&mut NamedArgumentResolver::new(decls, 0), &mut rule)?;
+  Ok(rule)
 }
 
 /// Parse a query atom
@@ -71,7 +88,7 @@ pub fn parse_rule(s: &str) -> Result<Rule, CompileError> {
 pub fn parse_query(s: &str) -> Result<Query, CompileError> {
   let parser = syntax::AtomParser::new();
   let result = parser.parse(&s).map_err(|e| {
-    print_syntax_error(s, e)
+    print_syntax_error(s, 0, e)
   });
   result.map(|a| {
     Query {
@@ -81,35 +98,55 @@ pub fn parse_query(s: &str) -> Result<Query, CompileError> {
   })
 }
 
+/// Parse an item; the relations of its atoms have no named fields
 pub fn parse_item(s: &str) -> Result<Item, CompileError> {
+  parse_item_with_decls(s, &[])
+}
+
+/// Parse an item whose atoms may name the fields of the declared relations
+pub fn parse_item_with_decls(s: &str, decls: &[Decl]) -> Result<Item, CompileError> {
   let parser = syntax::ItemParser::new();
-  let result = parser.parse(&s).map_err(|e| {
-    print_syntax_error(s, e)
-  });
-  result
+  let mut item = parser.parse(s).map_err(|e| {
+    print_syntax_error(s, 0, e)
+  })?;
+  let mut resolver = NamedArgumentResolver::new(decls, 0);
+  match &mut item {
+    Item::Fact(f) => visit_fact_mut(&mut resolver, f)?,
+    Item::Disjunction(d) => {
+      for fact in &mut d.node.facts {
+        visit_fact_mut(&mut resolver, fact)?;
+      }
+    }
+    Item::Rule(r) => visit_rule_mut(&mut resolver, r)?,
+    Item::Query(q) => visit_query_mut(&mut resolver, q)?,
+    Item::TypeDecl(_) | Item::Decl(_) | Item::Import(_) => {}
+  }
+  Ok(item)
 }
 
-fn parse_items(s: &str) -> Result<Vec<Item>, CompileError> {
+fn parse_items(s: &str, file: usize) -> Result<Vec<Item>, CompileError> {
   let parser = syntax::ItemsParser::new();
   let result = parser.parse(&s).map_err(|e| {
-    print_syntax_error(s, e)
+    print_syntax_error(s, file, e)
   });
   result
 }
 
 struct LocationAssigner {
   id_counter: usize,
+  file: usize,
   newlines: Vec<usize>,
 }
 
 impl LocationAssigner {
-  fn new(source: &str) -> Self {
+  fn new(source: &str, file: usize, first_id: usize) -> Self {
     let newlines: Vec<usize> = source
       .char_indices()
       .filter_map(|(i, c)| if c == '\n' { Some(i) } else { None })
       .collect();
     Self {
-      id_counter: 0,
+      id_counter: first_id,
+      file,
       newlines: newlines,
     }
   }
@@ -119,6 +156,7 @@ impl LocationAssigner {
     l.id = self.id_counter;
     l.row = row;
     l.col = col;
+    l.file = self.file;
     self.id_counter += 1;
   }
 
@@ -144,52 +182,265 @@ impl NodeVisitorMut for LocationAssigner {
   }
 }
 
-fn assign_node_locations(src: &str, ast: &mut Program) {
-  let mut assigner = LocationAssigner::new(src);
+/// Renames the relations defined in a file imported with a namespace, e.g.
+/// `path` into `graph::path`; the other relations it refers to stay global
+struct NamespaceAssigner {
+  namespace: String,
+  relations: HashSet<String>,
+}
 
-  // Use unwrap because this cannot fail
-  visit_program_mut(&mut assigner, ast).unwrap();
+impl NamespaceAssigner {
+  fn new(namespace: &str, prog: &Program) -> Self {
+    let heads = prog
+      .facts
+      .iter()
+      .chain(prog.disjunctions.iter().flat_map(|d| d.node.facts.iter()))
+      .map(|f| &f.node.head)
+      .chain(prog.rules.iter().map(|r| &r.node.head));
+    let relations = prog
+      .decls
+      .iter()
+      .map(|d| d.node.predicate.clone())
+      .chain(heads.map(|a| a.node.predicate.clone()))
+      .collect();
+    Self {
+      namespace: namespace.to_string(),
+      relations,
+    }
+  }
+
+  fn rename(&self, predicate: &mut String) {
+    if self.relations.contains(predicate) {
+      *predicate = format!("{}::{}", self.namespace, predicate);
+    }
+  }
 }
 
-pub fn parse_str(s: &str) -> Result<Program, CompileError> {
-  let items = parse_items(s)?;
-  let mut decls = vec![];
-  let mut facts = vec![];
-  let mut disjunctions = vec![];
-  let mut rules = vec![];
-  let mut queries = vec![];
-  for item in items {
-    match item {
-      Item::Decl(d) => decls.push(d),
-      Item::Fact(f) => facts.push(f),
-      Item::Disjunction(d) => disjunctions.push(d),
-      Item::Rule(r) => rules.push(r),
-      Item::Query(q) => queries.push(q),
-    }
-  }
-  let mut ast = Program {
-    decls,
-    facts,
-    disjunctions,
-    rules,
-    queries,
-  };
-  assign_node_locations(s, &mut ast);
-  Ok(ast)
+impl NodeVisitorMut for NamespaceAssigner {
+  fn visit_decl(&mut self, decl: &mut Decl) -> Result<(), CompileError> {
+    self.rename(&mut decl.node.predicate);
+    Ok(())
+  }
+
+  fn visit_atom(&mut self, atom: &mut Atom) -> Result<(), CompileError> {
+    self.rename(&mut atom.node.predicate);
+    Ok(())
+  }
 }
 
-pub fn parse_file(filename: &str) -> Result<Program, CompileError> {
+/// Turns the arguments given by field name, e.g. `edge(to: Y, from: X)`,
+/// into positional ones; the fields that are not given become wildcards
+struct NamedArgumentResolver {
+  fields: HashMap<String, Vec<Option<String>>>,
+  id_counter: usize,
+}
+
+impl NamedArgumentResolver {
+  fn new(decls: &[Decl], first_id: usize) -> Self {
+    let fields = decls
+      .iter()
+      .map(|d| (d.node.predicate.clone(), d.node.arg_names.clone()))
+      .collect();
+    Self {
+      fields,
+      id_counter: first_id,
+    }
+  }
+}
+
+impl NodeVisitorMut for NamedArgumentResolver {
+  fn visit_atom(&mut self, atom: &mut Atom) -> Result<(), CompileError> {
+    if atom.node.named_args.is_empty() {
+      return Ok(());
+    }
+    let rela_name = atom.node.predicate.clone();
+    let names = self.fields.get(&rela_name).cloned().unwrap_or_default();
+    let mut args = std::mem::take(&mut atom.node.args).into_iter().map(Some).collect::<Vec<_>>();
+    if args.len() < names.len() {
+      args.resize(names.len(), None);
+    }
+    for (field, arg) in std::mem::take(&mut atom.node.named_args) {
+      let loc = *arg.location();
+      match names.iter().position(|name| name.as_ref() == Some(&field)) {
+        Some(i) if args[i].is_none() => args[i] = Some(arg),
+        Some(_) => return Err(CompileError::DuplicatedField { loc, rela_name, field }),
+        None => return Err(CompileError::UnknownField { loc, rela_name, field }),
+      }
+    }
+    atom.node.args = args
+      .into_iter()
+      .map(|arg| {
+        arg.unwrap_or_else(|| {
+          let location = Location {
+            id: self.id_counter,
+            ..atom.location
+          };
+          self.id_counter += 1;
+          Argument::Wildcard(Wildcard { location, node: WildcardNode })
+        })
+      })
+      .collect();
+    Ok(())
+  }
+}
+
+/// Parses a program together with the files it imports
+///
+/// Import paths are relative to the importing file. A file imported several
+/// times into the same namespace is only included once, and the queries of
+/// the imported files are dropped.
+struct Importer {
+  id_counter: usize,
+  /// The files being imported, outermost first, for the cycle detection
+  stack: Vec<PathBuf>,
+  /// The namespaces of the files being imported
+  namespaces: Vec<String>,
+  included: HashSet<(PathBuf, Vec<String>)>,
+}
+
+impl Importer {
+  fn new() -> Self {
+    Self {
+      id_counter: 0,
+      stack: vec![],
+      namespaces: vec![],
+      included: HashSet::new(),
+    }
+  }
+
+  /// Parse the program and resolve its named arguments, once all the
+  /// declarations are imported
+  fn parse_program(&mut self, src: &str, dir: &Path) -> Result<Program, CompileError> {
+    let mut prog = self.parse_source(src, 0, dir)?;
+    self.resolve_named_arguments(&mut prog)?;
+    Ok(prog)
+  }
+
+  fn resolve_named_arguments(&mut self, prog: &mut Program) -> Result<(), CompileError> {
+    let mut resolver = NamedArgumentResolver::new(&prog.decls, self.id_counter);
+    visit_program_mut(&mut resolver, prog)?;
+    self.id_counter = resolver.id_counter;
+    Ok(())
+  }
+
+  fn parse_source(&mut self, src: &str, file: usize, dir: &Path) -> Result<Program, CompileError> {
+    let items = parse_items(src, file)?;
+    let mut ast = Program::default();
+    let mut imports = vec![];
+    for item in items {
+      match item {
+        Item::TypeDecl(t) => ast.type_decls.push(t),
+        Item::Decl(d) => ast.decls.push(d),
+        Item::Fact(f) => ast.facts.push(f),
+        Item::Disjunction(d) => ast.disjunctions.push(d),
+        Item::Rule(r) => ast.rules.push(r),
+        Item::Query(q) => ast.queries.push(q),
+        Item::Import(i) => imports.push(i),
+      }
+    }
+
+    // Use unwrap because this cannot fail
+    let mut assigner = LocationAssigner::new(src, file, self.id_counter);
+    visit_program_mut(&mut assigner, &mut ast).unwrap();
+    for import in &mut imports {
+      assigner.visit_location(&mut import.location).unwrap();
+    }
+    self.id_counter = assigner.id_counter;
+
+    for import in &imports {
+      let imported = self.import(import, dir)?;
+      ast.extend(imported);
+    }
+    Ok(ast)
+  }
+
+  fn parse_file(&mut self, path: &Path) -> Result<Program, CompileError> {
+    let canonical = path.canonicalize().map_err(|_| CompileError::CannotOpenFile)?;
+    let contents = read_file(path)?;
+    let file = register_source_file(&path.display().to_string());
+    self.stack.push(canonical.clone());
+    let result = self.parse_source(&contents, file, canonical.parent().unwrap_or(Path::new(".")));
+    self.stack.pop();
+    result
+  }
+
+  fn import(&mut self, import: &Import, dir: &Path) -> Result<Program, CompileError> {
+    let path = dir.join(&import.node.path);
+    let cannot_import = || CompileError::CannotImportFile {
+      loc: import.location,
+      path: import.node.path.clone(),
+    };
+    let canonical = path.canonicalize().map_err(|_| cannot_import())?;
+    if let Some(i) = self.stack.iter().position(|p| p == &canonical) {
+      let cycle = self.stack[i..].iter().chain(std::iter::once(&canonical));
+      return Err(CompileError::CyclicImport {
+        loc: import.location,
+        cycle: cycle.map(|p| p.display().to_string()).collect(),
+      });
+    }
+
+    let mut namespaces = self.namespaces.clone();
+    namespaces.extend(import.node.namespace.clone());
+    if !self.included.insert((canonical.clone(), namespaces)) {
+      return Ok(Program::default());
+    }
+
+    self.namespaces.extend(import.node.namespace.clone());
+    let result = self.parse_file(&path).map_err(|e| match e {
+      CompileError::CannotOpenFile | CompileError::CannotReadFile => cannot_import(),
+      e => e,
+    });
+    if import.node.namespace.is_some() {
+      self.namespaces.pop();
+    }
+    let mut prog = result?;
+
+    prog.queries.clear();
+    if let Some(namespace) = &import.node.namespace {
+      let mut assigner = NamespaceAssigner::new(namespace, &prog);
+      visit_program_mut(&mut assigner, &mut prog)?;
+    }
+    Ok(prog)
+  }
+}
+
+fn read_file(path: &Path) -> Result<String, CompileError> {
   // Initialize a string containing file content
   let mut contents = String::new();
 
   // Open the file with the given file path
-  let mut file = File::open(filename).map_err(|_| CompileError::CannotOpenFile)?;
+  let mut file = File::open(path).map_err(|_| CompileError::CannotOpenFile)?;
 
   // Read the file content into the string
   file
     .read_to_string(&mut contents)
     .map_err(|_| CompileError::CannotReadFile)?;
+  Ok(contents)
+}
+
+/// Parse a program; its imports are relative to the current directory
+pub fn parse_str(s: &str) -> Result<Program, CompileError> {
+  parse_str_in_dir(s, Path::new("."))
+}
+
+/// Parse a program whose imports are relative to the directory, e.g. the one
+/// of the source file embedding the program
+pub fn parse_str_in_dir(s: &str, dir: &Path) -> Result<Program, CompileError> {
+  Importer::new().parse_program(s, dir)
+}
+
+/// Parse a program file along with the files it imports
+pub fn parse_file(filename: &str) -> Result<Program, CompileError> {
+  let mut importer = Importer::new();
+  let mut prog = importer.parse_file(Path::new(filename))?;
+  importer.resolve_named_arguments(&mut prog)?;
+  Ok(prog)
+}
 
-  // Parse the string
-  parse_str(&contents)
+/// Parse the file of an import item, relative to the current directory
+pub fn parse_import(import: &Import) -> Result<Program, CompileError> {
+  let mut importer = Importer::new();
+  let mut prog = importer.import(import, Path::new("."))?;
+  importer.resolve_named_arguments(&mut prog)?;
+  Ok(prog)
 }
@@ -6,11 +6,14 @@ pub struct Program {
   pub facts: Vec<Fact>,
   pub disjunctions: Vec<Disjunction>,
   pub updates: Vec<Update>,
+
+  /// The ids and names of the symbol constants
+  pub symbols: Vec<(usize, String)>,
 }
 
 #[derive(Clone, Debug)]
 pub struct Fact {
-  pub prob: Option<f32>,
+  pub prob: Option<f64>,
   pub predicate: String,
   pub args: Vec<Constant>,
 }
@@ -33,6 +36,9 @@ pub struct Variable {
   pub is_temporary: bool,
   pub name: String,
   pub arg_types: VarType,
+  /// The names of the fields, if the relation is declared with all of them
+  /// named
+  pub field_names: Option<Vec<String>>,
 }
 
 #[derive(Clone, Debug)]
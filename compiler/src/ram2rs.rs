@@ -1,11 +1,13 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote};
 use syn;
 
+use super::ast2ram::create_project_arg;
 use super::ast_analysis::*;
 use super::common::*;
 use super::ram::*;
 use super::options::CompileOptions;
+use super::symbols;
 
 fn type_to_rs(ty: &Type, _: &CompileOptions) -> TokenStream {
   match ty {
@@ -13,6 +15,7 @@ fn type_to_rs(ty: &Type, _: &CompileOptions) -> TokenStream {
     Type::Integer => quote! { i64 },
     Type::String => quote! { &'static str },
     Type::Symbol => quote! { usize },
+    Type::Named(n) => panic!("Type {} should have been erased", n),
   }
 }
 
@@ -29,13 +32,25 @@ fn var_type_to_rs(ty: &VarType, o: &CompileOptions) -> TokenStream {
 
 fn const_to_rs(constant: &Constant, _: &CompileOptions) -> TokenStream {
   match constant {
-    Constant::Symbol(s) => quote! { #s },
+    Constant::Symbol(s) => match symbols::symbol_name(*s) {
+      Some(name) if *s >= symbols::FIRST_INTERNED_ID => {
+        let symbol = symbol_ident(&name);
+        quote! { #symbol() }
+      }
+      _ => quote! { #s },
+    },
     Constant::Boolean(b) => quote! { #b },
     Constant::Integer(i) => quote! { #i },
     Constant::String(s) => quote! { #s },
   }
 }
 
+/// The tuple of the constants, with the layout of the relations
+fn consts_to_rs(constants: &[Constant], o: &CompileOptions) -> TokenStream {
+  let args = constants.iter().cloned().map(Argument::Constant).collect::<Vec<_>>();
+  arg_to_rs(&create_project_arg(&args), o)
+}
+
 fn bin_op_to_rs(bin_op: &BinaryOp, _: &CompileOptions) -> TokenStream {
   match bin_op {
     BinaryOp::Eq => quote! { == },
@@ -90,6 +105,34 @@ fn arg_to_rs(arg: &Argument, o: &CompileOptions) -> TokenStream {
   }
 }
 
+/// The function giving the runtime id of a symbol of the program
+fn symbol_ident(name: &str) -> Ident {
+  format_ident!("symbol_{}", name)
+}
+
+/// The symbols of the program are interned by name when first used, since
+/// their ids at runtime differ from the ones at compile time
+fn symbol_defs(ram: &Program) -> TokenStream {
+  let mut names = ram.symbols.iter().map(|(_, name)| name).collect::<Vec<_>>();
+  names.sort();
+  let defs = names.into_iter().map(|name| {
+    let symbol = symbol_ident(name);
+    quote! {
+      fn #symbol() -> usize {
+        static ID: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+        *ID.get_or_init(|| scallop_runtime::symbols::intern(#name))
+      }
+    }
+  });
+  quote! { #(#defs)* }
+}
+
+/// The Rust identifier of a relation; the namespaces of the imported
+/// relations, e.g. `graph::path`, are joined with `__`
+pub fn relation_ident(name: &str) -> Ident {
+  format_ident!("{}", name.replace("::", "__"))
+}
+
 fn flow_to_rs_helper(flow: &Flow, is_arg: bool, o: &CompileOptions) -> TokenStream {
   match flow {
     Flow::Product(f1, f2) => {
@@ -124,14 +167,12 @@ fn flow_to_rs_helper(flow: &Flow, is_arg: bool, o: &CompileOptions) -> TokenStre
     }
     Flow::ContainsChain(flow_to_find, key, source) => {
       let f1_rs = flow_to_rs_helper(flow_to_find, true, o);
-      let key_rs = arg_to_rs(&Argument::Tuple(key.iter().map(|c| {
-        Argument::Constant(c.clone())
-      }).collect::<Vec<_>>()), o);
+      let key_rs = consts_to_rs(key, o);
       let f2_rs = flow_to_rs_helper(source, true, o);
       quote! { self.iter.contains_chain(#f1_rs, #key_rs, #f2_rs) }
     }
     Flow::Variable(var) => {
-      let var = format_ident!("{}", var);
+      let var = relation_ident(var);
       if is_arg {
         quote! { &self.#var }
       } else {
@@ -147,7 +188,7 @@ fn flow_to_rs(flow: &Flow, o: &CompileOptions) -> TokenStream {
 
 fn semiring_constraint(analysis: &AnalysisResult, _: &CompileOptions) -> TokenStream {
   if analysis.is_probabilistic {
-    quote! { where Tag: Semiring<Context = ProbProofContext>, ProbProofContext: SemiringContext<Tag, Info = f32> }
+    quote! { where Tag: Semiring<Context = ProbProofContext>, ProbProofContext: SemiringContext<Tag, Info = f64> }
   } else {
     quote! { where Tag: Semiring }
   }
@@ -160,7 +201,7 @@ fn struct_def(name: &str, ram: &Program, analysis: &AnalysisResult, o: &CompileO
     .variables
     .iter()
     .map(|var| {
-      let name = format_ident!("{}", &var.name);
+      let name = relation_ident(&var.name);
       let arg_types = var_type_to_rs(&var.arg_types, o);
       quote! { #name: Variable<#arg_types, Tag> }
     })
@@ -173,30 +214,121 @@ fn struct_def(name: &str, ram: &Program, analysis: &AnalysisResult, o: &CompileO
   }
 }
 
+/// The name of the struct of a relation with named fields, e.g. `ProgEdge`
+/// for the relation `edge` of the program `Prog`
+pub fn record_ident(name: &str, var_name: &str) -> Ident {
+  let camel_case = relation_ident(var_name)
+    .to_string()
+    .split('_')
+    .map(|part| {
+      let mut chars = part.chars();
+      match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect::<String>(),
+        None => String::new(),
+      }
+    })
+    .collect::<String>();
+  format_ident!("{}{}", name, camel_case)
+}
+
+/// The pattern binding the fields of a tuple of the type, e.g.
+/// `(from, (to, weight))`
+fn var_type_pattern(ty: &VarType, fields: &mut impl Iterator<Item = Ident>) -> TokenStream {
+  match ty {
+    VarType::Empty => quote! { () },
+    VarType::Base(_) => {
+      let field = fields.next();
+      quote! { #field }
+    }
+    VarType::Tuple(t) => {
+      let elems = t.iter().map(|vt| var_type_pattern(vt, fields)).collect::<Vec<_>>();
+      quote! { (#(#elems),*) }
+    }
+  }
+}
+
+/// A struct for each relation whose fields are all named, convertible from
+/// and into its tuples
+fn record_defs(name: &str, ram: &Program, o: &CompileOptions) -> TokenStream {
+  let defs = ram.variables.iter().filter(|var| !var.is_temporary).filter_map(|var| {
+    let field_names = var.field_names.as_ref()?;
+    let record = record_ident(name, &var.name);
+    let tuple_type = var_type_to_rs(&var.arg_types, o);
+    let fields = field_names.iter().map(|f| field_ident(f)).collect::<Vec<_>>();
+    let field_types = flatten_var_type(&var.arg_types).into_iter().map(|ty| type_to_rs(&ty, o));
+    let pattern = var_type_pattern(&var.arg_types, &mut fields.iter().cloned());
+    Some(quote! {
+      #[derive(Clone, Debug, PartialEq)]
+      pub struct #record {
+        #(pub #fields: #field_types),*
+      }
+
+      impl From<#tuple_type> for #record {
+        fn from(#pattern: #tuple_type) -> Self {
+          Self { #(#fields),* }
+        }
+      }
+
+      impl From<#record> for #tuple_type {
+        fn from(record: #record) -> Self {
+          let #record { #(#fields),* } = record;
+          #pattern
+        }
+      }
+    })
+  });
+  quote! { #(#defs)* }
+}
+
+/// The Rust identifier of a field; keywords, e.g. `loop`, are raw identifiers
+fn field_ident(field: &str) -> Ident {
+  syn::parse_str::<Ident>(field).unwrap_or_else(|_| Ident::new_raw(field, Span::call_site()))
+}
+
+fn flatten_var_type(ty: &VarType) -> Vec<Type> {
+  match ty {
+    VarType::Empty => vec![],
+    VarType::Base(b) => vec![b.clone()],
+    VarType::Tuple(t) => t.iter().flat_map(flatten_var_type).collect(),
+  }
+}
+
 fn update_to_rs(update: &Update, o: &CompileOptions) -> TokenStream {
-  let into_var = format_ident!("{}", &update.into_var);
+  let into_var = relation_ident(&update.into_var);
   let flow = flow_to_rs(&update.flow, o);
   quote! { self.iter.insert_dataflow(&self.#into_var, #flow); }
 }
 
 fn impl_handles(name: &str, ram: &Program, analysis: &AnalysisResult, o: &CompileOptions) -> TokenStream {
-  let name = format_ident!("{}", &name);
+  let prog_name = format_ident!("{}", &name);
   let constraint = semiring_constraint(analysis, o);
   let handle_accessors = ram.variables.iter().filter_map(|var| {
     if var.is_temporary {
       None
     } else {
-      let var_name = format_ident!("{}", var.name);
+      let var_name = relation_ident(&var.name);
       let arg_types = var_type_to_rs(&var.arg_types, o);
+      let records = var.field_names.as_ref().map(|_| {
+        let records_name = format_ident!("{}_records", var_name);
+        let record = record_ident(name, &var.name);
+        quote! {
+          pub fn #records_name(&mut self) -> Vec<(#record, Tag)> {
+            let relation = self.iter.variable_handle(&self.#var_name).complete();
+            relation.elements.into_iter().map(|elem| (elem.tup.into(), elem.tag)).collect()
+          }
+        }
+      });
       Some(quote! {
         pub fn #var_name<'a>(&'a mut self) -> VariableHandle<'a, #arg_types, Tag> {
           self.iter.variable_handle(&self.#var_name)
         }
+
+        #records
       })
     }
   });
   quote! {
-    impl<Tag> #name<Tag> #constraint {
+    impl<Tag> #prog_name<Tag> #constraint {
       #(#handle_accessors)*
     }
   }
@@ -207,7 +339,7 @@ fn fact_insertion(ram: &Program, o: &CompileOptions) -> Vec<TokenStream> {
     .variables
     .iter()
     .map(|var| {
-      let name = format_ident!("{}", var.name);
+      let name = relation_ident(&var.name);
       let (prob_facts, non_prob_facts): (Vec<&Fact>, Vec<&Fact>) = ram
         .facts
         .iter()
@@ -217,15 +349,14 @@ fn fact_insertion(ram: &Program, o: &CompileOptions) -> Vec<TokenStream> {
         .iter()
         .map(|fact| {
           let prob = fact.prob.unwrap();
-          let tup = fact.args.iter().map(|c| const_to_rs(c, o));
-          quote! { (#prob, (#(#tup),*)) }
+          let tup = consts_to_rs(&fact.args, o);
+          quote! { (#prob, #tup) }
         })
         .collect::<Vec<_>>();
       let insert_non_prob_facts = non_prob_facts
         .iter()
         .map(|fact| {
-          let tup = fact.args.iter().map(|c| const_to_rs(c, o));
-          quote! { (#(#tup),*) }
+          consts_to_rs(&fact.args, o)
         })
         .collect::<Vec<_>>();
       let insert_prob_facts_quote = if insert_prob_facts.len() > 0 {
@@ -255,17 +386,17 @@ fn disjunction_insertion(ram: &Program, analysis: &AnalysisResult, o: &CompileOp
     .disjunctions
     .iter()
     .map(|disjunction| {
-      let name = format_ident!("{}", &analysis.disj_rela_map[&disjunction.id]);
+      let name = relation_ident(&analysis.disj_rela_map[&disjunction.id]);
       let facts = disjunction
         .facts
         .iter()
         .map(|fact| {
           let prob = match &fact.prob {
             Some(p) => quote! { #p },
-            None => quote! { 1.0f32 },
+            None => quote! { 1.0f64 },
           };
-          let tup = fact.args.iter().map(|c| const_to_rs(c, o));
-          quote! { ( #prob, (#(#tup),*) ) }
+          let tup = consts_to_rs(&fact.args, o);
+          quote! { ( #prob, #tup ) }
         })
         .collect::<Vec<_>>();
       quote! {
@@ -286,7 +417,7 @@ fn impl_prog(name: &str, ram: &Program, analysis: &AnalysisResult, o: &CompileOp
     .map(|var| {
       let arg_types = var_type_to_rs(&var.arg_types, o);
       let raw_name = &var.name;
-      let name = format_ident!("{}", var.name);
+      let name = relation_ident(&var.name);
       quote! { let #name = iter.static_variable::<#arg_types>(#raw_name); }
     })
     .collect::<Vec<_>>();
@@ -294,7 +425,7 @@ fn impl_prog(name: &str, ram: &Program, analysis: &AnalysisResult, o: &CompileOp
   let init_struct_fields = ram
     .variables
     .iter()
-    .map(|var| format_ident!("{}", var.name))
+    .map(|var| relation_ident(&var.name))
     .collect::<Vec<_>>();
 
   let var_facts_insertion = fact_insertion(ram, o);
@@ -302,6 +433,7 @@ fn impl_prog(name: &str, ram: &Program, analysis: &AnalysisResult, o: &CompileOp
   let var_disjunction_insertion = disjunction_insertion(ram, analysis, o);
 
   let updates = ram.updates.iter().map(|u| update_to_rs(u, o)).collect::<Vec<_>>();
+
   quote! {
     impl<Tag> Program<Tag> for #name<Tag> #constraint {
       fn new() -> Self {
@@ -335,7 +467,9 @@ pub fn ram2rs(
   let module_name = format_ident!("scallop_{}", name.to_lowercase());
   let sd = struct_def(name, ram, analysis, options);
   let ih = impl_handles(name, ram, analysis, options);
+  let rd = record_defs(name, ram, options);
   let ip = impl_prog(name, ram, analysis, options);
+  let sy = symbol_defs(ram);
   quote! {
     mod #module_name {
       pub use scallop_runtime::*;
@@ -343,7 +477,9 @@ pub fn ram2rs(
       use scallop_runtime::interpreter::*;
       #sd
       #ih
+      #rd
       #ip
+      #sy
     }
     use #module_name::*;
   }
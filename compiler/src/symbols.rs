@@ -0,0 +1,108 @@
+use std::collections::*;
+use std::sync::{OnceLock, RwLock};
+
+/// The first id of the symbols interned in the global table; the ids below
+/// are left to the integers used as symbols, e.g. `3` in `person(3)`
+pub const FIRST_INTERNED_ID: usize = 1 << (usize::BITS - 1);
+
+/// Interner of the symbol constants, e.g. `giraffe`, which are stored as ids
+/// in the relations
+///
+/// Ids are allocated from the first id in interning order; ids that were
+/// registered explicitly are skipped.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+  ids: HashMap<String, usize>,
+  names: BTreeMap<usize, String>,
+  next_id: usize,
+}
+
+impl SymbolTable {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// A table allocating the ids from `first_id`
+  pub fn starting_at(first_id: usize) -> Self {
+    Self {
+      next_id: first_id,
+      ..Self::default()
+    }
+  }
+
+  /// The id of the symbol, allocating a new one if it is not interned yet
+  pub fn intern(&mut self, name: &str) -> usize {
+    if let Some(id) = self.ids.get(name) {
+      return *id;
+    }
+    while self.names.contains_key(&self.next_id) {
+      self.next_id += 1;
+    }
+    let id = self.next_id;
+    self.ids.insert(name.to_string(), id);
+    self.names.insert(id, name.to_string());
+    id
+  }
+
+  /// Give the symbol the id; returns false if either of them is already
+  /// associated with another one
+  pub fn register(&mut self, id: usize, name: &str) -> bool {
+    match (self.ids.get(name), self.names.get(&id)) {
+      (Some(other_id), _) => *other_id == id,
+      (None, Some(_)) => false,
+      (None, None) => {
+        self.ids.insert(name.to_string(), id);
+        self.names.insert(id, name.to_string());
+        true
+      }
+    }
+  }
+
+  pub fn id(&self, name: &str) -> Option<usize> {
+    self.ids.get(name).cloned()
+  }
+
+  pub fn name(&self, id: usize) -> Option<&str> {
+    self.names.get(&id).map(String::as_str)
+  }
+
+  /// All the symbols, ordered by id
+  pub fn symbols(&self) -> impl Iterator<Item = (usize, &str)> {
+    self.names.iter().map(|(id, name)| (*id, name.as_str()))
+  }
+}
+
+/// The symbol table shared by the compiler, the compiled programs and the
+/// dynamic rules of the process
+fn global_table() -> &'static RwLock<SymbolTable> {
+  static TABLE: OnceLock<RwLock<SymbolTable>> = OnceLock::new();
+  TABLE.get_or_init(|| RwLock::new(SymbolTable::starting_at(FIRST_INTERNED_ID)))
+}
+
+/// Intern a symbol in the global table
+pub fn intern(name: &str) -> usize {
+  global_table().write().unwrap().intern(name)
+}
+
+/// Register the symbols with the given ids in the global table
+///
+/// Returns the symbols conflicting with the ones interned before, which are
+/// not registered.
+pub fn register_symbols<'a>(symbols: &[(usize, &'a str)]) -> Vec<(usize, &'a str)> {
+  let mut table = global_table().write().unwrap();
+  symbols.iter().filter(|(id, name)| !table.register(*id, name)).cloned().collect()
+}
+
+pub fn symbol_id(name: &str) -> Option<usize> {
+  global_table().read().unwrap().id(name)
+}
+
+pub fn symbol_name(id: usize) -> Option<String> {
+  global_table().read().unwrap().name(id).map(str::to_string)
+}
+
+/// The name of the symbol, or its id after a `#` if it has none, e.g. for the
+/// integers used as symbols
+pub fn format_symbol(id: usize) -> String {
+  symbol_name(id).unwrap_or_else(|| format!("#{}", id))
+}
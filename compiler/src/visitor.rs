@@ -15,6 +15,7 @@ pub trait NodeVisitorMut {
     Ok(())
   }
 
+  node_visitor_mut_func_def!(visit_type_decl, TypeDecl);
   node_visitor_mut_func_def!(visit_decl, Decl);
   node_visitor_mut_func_def!(visit_type, Type);
   node_visitor_mut_func_def!(visit_fact, Fact);
@@ -50,6 +51,7 @@ macro_rules! impl_node_visitor_mut_tuple {
     where
       $($id: NodeVisitorMut,)*
     {
+      node_visitor_mut_visit_node!(visit_type_decl, TypeDecl, ($($id),*));
       node_visitor_mut_visit_node!(visit_decl, Decl, ($($id),*));
       node_visitor_mut_visit_node!(visit_type, Type, ($($id),*));
       node_visitor_mut_visit_node!(visit_fact, Fact, ($($id),*));
@@ -122,6 +124,9 @@ pub fn visit_atom_mut(
   for arg in &mut atom.node.args {
     visit_arg_mut(visitor, arg)?;
   }
+  for (_, arg) in &mut atom.node.named_args {
+    visit_arg_mut(visitor, arg)?;
+  }
   Ok(())
 }
 
@@ -190,6 +195,12 @@ pub fn visit_program_mut(
   visitor: &mut impl NodeVisitorMut,
   prog: &mut ast::Program,
 ) -> Result<(), CompileError> {
+  for type_decl in &mut prog.type_decls {
+    visitor.visit_type_decl(type_decl)?;
+    visitor.visit_location(&mut type_decl.location)?;
+    visitor.visit_type(&mut type_decl.node.base)?;
+    visitor.visit_location(&mut type_decl.node.base.location)?;
+  }
   for decl in &mut prog.decls {
     visitor.visit_decl(decl)?;
     visitor.visit_location(&mut decl.location)?;
@@ -229,6 +240,7 @@ pub trait NodeVisitor {
     Ok(())
   }
 
+  node_visitor_func_def!(visit_type_decl, TypeDecl);
   node_visitor_func_def!(visit_decl, Decl);
   node_visitor_func_def!(visit_type, Type);
   node_visitor_func_def!(visit_fact, Fact);
@@ -264,6 +276,7 @@ macro_rules! impl_node_visitor_tuple {
     where
       $($id: NodeVisitor,)*
     {
+      node_visitor_visit_node!(visit_type_decl, TypeDecl, ($($id),*));
       node_visitor_visit_node!(visit_decl, Decl, ($($id),*));
       node_visitor_visit_node!(visit_type, Type, ($($id),*));
       node_visitor_visit_node!(visit_fact, Fact, ($($id),*));
@@ -330,6 +343,9 @@ pub fn visit_atom(visitor: &mut impl NodeVisitor, atom: &ast::Atom) -> Result<()
   for arg in &atom.node.args {
     visit_arg(visitor, arg)?;
   }
+  for (_, arg) in &atom.node.named_args {
+    visit_arg(visitor, arg)?;
+  }
   Ok(())
 }
 
@@ -392,6 +408,12 @@ pub fn visit_program(
   visitor: &mut impl NodeVisitor,
   prog: &ast::Program,
 ) -> Result<(), CompileError> {
+  for type_decl in &prog.type_decls {
+    visitor.visit_type_decl(type_decl)?;
+    visitor.visit_location(&type_decl.location)?;
+    visitor.visit_type(&type_decl.node.base)?;
+    visitor.visit_location(&type_decl.node.base.location)?;
+  }
   for decl in &prog.decls {
     visitor.visit_decl(decl)?;
     visitor.visit_location(&decl.location)?;
@@ -0,0 +1,300 @@
+use super::ast;
+use super::ast_eval::*;
+use super::explain::*;
+
+/// The number of closest candidates reported per rule
+pub const MAX_CANDIDATES: usize = 3;
+
+/// Why the body of a rule does not produce the expected tuple
+#[derive(Clone, Debug, PartialEq)]
+pub enum Failure {
+  /// No tuple matches the body atom, shown with its bound variables replaced
+  /// by their values, e.g. `edge(3, B)`
+  MissingTuple { atom: String },
+
+  /// The constraint evaluates to false
+  FalseConstraint { constraint: String },
+
+  /// The constraint cannot be evaluated, because of an unbound variable or an
+  /// operation undefined on the values
+  InvalidConstraint { constraint: String },
+
+  /// The whole body holds but the head evaluates to a tuple not matching the
+  /// pattern, or cannot be evaluated
+  HeadMismatch { head: Option<ValueTuple> },
+}
+
+impl std::fmt::Display for Failure {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::MissingTuple { atom } => write!(f, "no tuple matches {}", atom),
+      Self::FalseConstraint { constraint } => write!(f, "constraint {} is false", constraint),
+      Self::InvalidConstraint { constraint } => write!(f, "constraint {} cannot be evaluated", constraint),
+      Self::HeadMismatch { head: Some(head) } => write!(f, "the head evaluates to ({})", format_values(head)),
+      Self::HeadMismatch { head: None } => write!(f, "the head cannot be evaluated"),
+    }
+  }
+}
+
+/// Partial bindings of a rule body which fail at some body literal
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+  pub bindings: Bindings,
+
+  /// The number of body literals that hold under the bindings
+  pub num_satisfied: usize,
+  pub failure: Failure,
+}
+
+/// The diagnosis of a rule whose head may produce the pattern
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleDiagnosis {
+  pub rule: String,
+  pub row: usize,
+  pub num_literals: usize,
+
+  /// The candidates satisfying the most body literals, at most
+  /// `MAX_CANDIDATES` of them
+  pub candidates: Vec<Candidate>,
+}
+
+/// Why no tuple matches a pattern
+#[derive(Clone, Debug, PartialEq)]
+pub struct WhyNot {
+  pub pattern: String,
+
+  /// The tuples matching the pattern; when there are some, there is nothing
+  /// to explain
+  pub derived: Vec<ValueTuple>,
+
+  /// The rules whose head may produce the pattern
+  pub rules: Vec<RuleDiagnosis>,
+}
+
+impl std::fmt::Display for WhyNot {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if !self.derived.is_empty() {
+      writeln!(f, "{} is derived:", self.pattern)?;
+      for tuple in &self.derived {
+        writeln!(f, "  ({})", format_values(tuple))?;
+      }
+      return Ok(());
+    }
+    writeln!(f, "{} is not derived", self.pattern)?;
+    if self.rules.is_empty() {
+      writeln!(f, "  no input fact or rule head matches it")?;
+    }
+    for rule in &self.rules {
+      writeln!(f, "  rule {} (line {})", rule.rule, rule.row)?;
+      for candidate in &rule.candidates {
+        let bindings = candidate
+          .bindings
+          .iter()
+          .map(|(var, value)| format!("{} = {}", var, value))
+          .collect::<Vec<_>>();
+        writeln!(
+          f,
+          "    with {{{}}}, {} of {} body literals hold: {}",
+          bindings.join(", "),
+          candidate.num_satisfied,
+          rule.num_literals,
+          candidate.failure
+        )?;
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Explainer {
+  /// Explain why no tuple matching the atom is derived
+  ///
+  /// The rules considered are the ones whose head unifies with the atom. For
+  /// each of them, the body is matched literal by literal, evaluating each
+  /// constraint as soon as its variables are bound, and the candidates which
+  /// get the furthest before failing are reported.
+  pub fn why_not(&self, atom: &ast::Atom) -> WhyNot {
+    let predicate = &atom.node.predicate;
+    let derived = self
+      .database()
+      .tuples(predicate)
+      .filter(|(tuple, _)| match_atom(atom, tuple, &Bindings::new()).is_some())
+      .map(|(tuple, _)| tuple.clone())
+      .collect::<Vec<_>>();
+    let rules = if derived.is_empty() {
+      self
+        .program()
+        .rules
+        .iter()
+        .filter(|r| &r.node.head.node.predicate == predicate)
+        .filter_map(|r| self.diagnose(r, atom))
+        .collect()
+    } else {
+      vec![]
+    };
+    WhyNot {
+      pattern: atom.codify(),
+      derived,
+      rules,
+    }
+  }
+
+  fn diagnose(&self, rule: &ast::Rule, pattern: &ast::Atom) -> Option<RuleDiagnosis> {
+    let init = unify_head(rule, pattern)?;
+    let mut search = Search {
+      db: self.database(),
+      rule,
+      pattern,
+      literals: &rule.node.body,
+      candidates: vec![],
+    };
+    let checked = vec![false; rule.node.body.len()];
+    search.run(0, 0, init, checked);
+    Some(RuleDiagnosis {
+      rule: rule.codify(),
+      row: rule.location.row,
+      num_literals: rule.node.body.len(),
+      candidates: search.candidates,
+    })
+  }
+}
+
+/// The bindings of the head variables to the constants of the pattern, if the
+/// head may produce a tuple matching the pattern
+fn unify_head(rule: &ast::Rule, pattern: &ast::Atom) -> Option<Bindings> {
+  let head = &rule.node.head.node;
+  if head.args.len() != pattern.node.args.len() {
+    return None;
+  }
+  let mut bindings = Bindings::new();
+  for (arg, pattern_arg) in head.args.iter().zip(&pattern.node.args) {
+    let value = match pattern_arg {
+      ast::Argument::Constant(c) => Value::from_constant(&c.node),
+      _ => continue,
+    };
+    match arg {
+      ast::Argument::Variable(v) => match bindings.get(&v.node.name) {
+        Some(bound) if bound != &value => return None,
+        Some(_) => {}
+        None => {
+          bindings.insert(v.node.name.clone(), value);
+        }
+      },
+      ast::Argument::Constant(c) if Value::from_constant(&c.node) != value => return None,
+      _ => {}
+    }
+  }
+  Some(bindings)
+}
+
+/// Depth-first matching of the body literals, recording where each branch
+/// fails
+struct Search<'a> {
+  db: &'a Database,
+  rule: &'a ast::Rule,
+  pattern: &'a ast::Atom,
+  literals: &'a [ast::Literal],
+
+  /// The first candidates found satisfying the most body literals so far
+  candidates: Vec<Candidate>,
+}
+
+impl<'a> Search<'a> {
+  /// `next` is the next atom to match; `checked` marks the literals that hold
+  fn run(&mut self, next: usize, num_satisfied: usize, bindings: Bindings, mut checked: Vec<bool>) {
+    // Check the constraints which can be evaluated
+    let mut num_satisfied = num_satisfied;
+    for (i, literal) in self.literals.iter().enumerate() {
+      if let ast::LiteralNode::Constraint(c) = &literal.node {
+        if checked[i] {
+          continue;
+        }
+        match eval_constraint(c, &bindings) {
+          Some(true) => {
+            checked[i] = true;
+            num_satisfied += 1;
+          }
+          Some(false) => {
+            let failure = Failure::FalseConstraint { constraint: c.codify() };
+            return self.fail(bindings, num_satisfied, failure);
+          }
+          None => {}
+        }
+      }
+    }
+
+    // Match the next atom
+    let next_atom = self.literals.iter().enumerate().skip(next).find_map(|(i, l)| match &l.node {
+      ast::LiteralNode::Pos(a) => Some((i, a)),
+      _ => None,
+    });
+    match next_atom {
+      Some((i, atom)) => {
+        let mut matched = false;
+        for (tuple, _) in self.db.tuples(&atom.node.predicate) {
+          if let Some(bindings) = match_atom(atom, tuple, &bindings) {
+            matched = true;
+            let mut checked = checked.clone();
+            checked[i] = true;
+            self.run(i + 1, num_satisfied + 1, bindings, checked);
+          }
+        }
+        if !matched {
+          let failure = Failure::MissingTuple { atom: instantiate(atom, &bindings) };
+          self.fail(bindings, num_satisfied, failure);
+        }
+      }
+      None => {
+        // The constraints left cannot be evaluated
+        let unchecked = self.literals.iter().zip(&checked).find_map(|(l, checked)| match &l.node {
+          ast::LiteralNode::Constraint(c) if !checked => Some(c),
+          _ => None,
+        });
+        if let Some(c) = unchecked {
+          let failure = Failure::InvalidConstraint { constraint: c.codify() };
+          return self.fail(bindings, num_satisfied, failure);
+        }
+        let head = eval_head(self.rule, &bindings);
+        let matches = head
+          .as_ref()
+          .is_some_and(|head| match_atom(self.pattern, head, &Bindings::new()).is_some());
+        if !matches {
+          self.fail(bindings, num_satisfied, Failure::HeadMismatch { head });
+        }
+      }
+    }
+  }
+
+  fn fail(&mut self, bindings: Bindings, num_satisfied: usize, failure: Failure) {
+    match self.candidates.first().map(|c| c.num_satisfied) {
+      Some(best) if num_satisfied < best => return,
+      Some(best) if num_satisfied > best => self.candidates.clear(),
+      _ => {}
+    }
+    if self.candidates.len() < MAX_CANDIDATES {
+      self.candidates.push(Candidate {
+        bindings,
+        num_satisfied,
+        failure,
+      });
+    }
+  }
+}
+
+/// The atom with its bound variables replaced by their values
+fn instantiate(atom: &ast::Atom, bindings: &Bindings) -> String {
+  let args = atom
+    .node
+    .args
+    .iter()
+    .map(|arg| match arg {
+      ast::Argument::Variable(v) => bindings.get(&v.node.name).map_or_else(|| arg.codify(), Value::to_string),
+      _ => arg.codify(),
+    })
+    .collect::<Vec<_>>();
+  format!("{}({})", atom.node.predicate, args.join(", "))
+}
+
+fn format_values(tuple: &[Value]) -> String {
+  tuple.iter().map(Value::to_string).collect::<Vec<_>>().join(", ")
+}
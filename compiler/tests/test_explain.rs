@@ -0,0 +1,105 @@
+use scallop_compiler::ast_eval::Value;
+use scallop_compiler::error::CompileError;
+use scallop_compiler::explain::*;
+use scallop_compiler::parser;
+
+const PATH: &str = "
+  decl edge(Int, Int).
+  decl path(Int, Int).
+  edge(0, 1). 0.5::edge(1, 2). edge(2, 3).
+  path(A, B) :- edge(A, B).
+  path(A, C) :- edge(A, B), path(B, C).
+";
+
+#[test]
+fn test_explain_path() {
+  let prog = parser::parse_str(PATH).unwrap();
+  let explainer = Explainer::new(&prog).unwrap();
+  let atom = parser::parse_query("path(0, 3)").unwrap().node.atom;
+  let derivations = explainer.explain(&atom);
+  assert_eq!(derivations.len(), 1);
+  let derivation = &derivations[0];
+  assert_eq!(derivation.tuple(), &[Value::Integer(0), Value::Integer(3)]);
+  assert_eq!(derivation.depth(), 3);
+  let facts = derivation
+    .input_facts()
+    .into_iter()
+    .map(|(pred, tuple)| (pred.to_string(), tuple.to_vec()))
+    .collect::<Vec<_>>();
+  let edge = |a, b| ("edge".to_string(), vec![Value::Integer(a), Value::Integer(b)]);
+  assert_eq!(facts, vec![edge(0, 1), edge(1, 2), edge(2, 3)]);
+
+  let text = derivation.to_string();
+  assert!(text.starts_with("path(0, 3) <- path(A, C) :- edge(A, B), path(B, C). (line 6)\n"));
+  assert!(text.contains("\n    edge(1, 2) [fact 0.5]\n"));
+}
+
+#[test]
+fn test_explain_pattern() {
+  let prog = parser::parse_str(PATH).unwrap();
+  let explainer = Explainer::new(&prog).unwrap();
+  let atom = parser::parse_query("path(X, 3)").unwrap().node.atom;
+  assert_eq!(explainer.explain(&atom).len(), 3);
+  let atom = parser::parse_query("path(3, X)").unwrap().node.atom;
+  assert!(explainer.explain(&atom).is_empty());
+}
+
+#[test]
+fn test_explain_constraint_and_expression() {
+  let prog = parser::parse_str(
+    "
+    decl num(Int).
+    decl succ(Int, Int).
+    num(1). num(2).
+    succ(A, A + 1) :- num(A), A > 1.
+  ",
+  )
+  .unwrap();
+  let explainer = Explainer::new(&prog).unwrap();
+  let atom = parser::parse_query("succ(X, Y)").unwrap().node.atom;
+  let derivations = explainer.explain(&atom);
+  assert_eq!(derivations.len(), 1);
+  assert_eq!(derivations[0].tuple(), &[Value::Integer(2), Value::Integer(3)]);
+}
+
+#[test]
+fn test_explain_dot() {
+  let prog = parser::parse_str(PATH).unwrap();
+  let explainer = Explainer::new(&prog).unwrap();
+  let atom = parser::parse_query("path(1, 2)").unwrap().node.atom;
+  let dot = to_dot(&explainer.explain(&atom));
+  assert!(dot.starts_with("digraph derivations {\n"));
+  assert!(dot.contains("n0 [label=\"path(1, 2)\"];"));
+  assert!(dot.contains("n2 [label=\"edge(1, 2)\\n0.5\", shape=box];"));
+  assert!(dot.contains("n0 -> n1;"));
+  assert!(dot.contains("n1 -> n2;"));
+}
+
+#[test]
+fn test_explain_rounds() {
+  // Every tuple is derived in the round after its shallowest body tuples
+  let prog = parser::parse_str(PATH).unwrap();
+  let explainer = Explainer::new(&prog).unwrap();
+  let db = explainer.database();
+  let path = |a, b| [Value::Integer(a), Value::Integer(b)];
+  assert_eq!(db.round_of("edge", &path(2, 3)), Some(0));
+  assert_eq!(db.round_of("path", &path(2, 3)), Some(1));
+  assert_eq!(db.round_of("path", &path(1, 3)), Some(2));
+  assert_eq!(db.round_of("path", &path(0, 3)), Some(3));
+  assert_eq!(db.tuples("path").count(), 6);
+}
+
+#[test]
+fn test_explain_analyzed_program() {
+  // The program is checked as by the compiler
+  let prog = parser::parse_str("decl num(Int). num(1). decl p(String). p(A) :- num(A).").unwrap();
+  assert!(matches!(Explainer::new(&prog), Err(CompileError::SubtypeMismatch { .. })));
+
+  // The integers used as symbols are coerced
+  let prog = parser::parse_str("decl person(Symbol). person(3). person(bob). decl p(Symbol). p(3) :- person(3).");
+  let explainer = Explainer::new(&prog.unwrap()).unwrap();
+  let atom = parser::parse_query("p(X)").unwrap().node.atom;
+  let derivations = explainer.explain(&atom);
+  assert_eq!(derivations.len(), 1);
+  assert_eq!(derivations[0].to_string(), "p(#3) <- p(3) :- person(3). (line 1)\n  person(#3) [fact]\n");
+}
@@ -0,0 +1,112 @@
+use scallop_compiler::ast::*;
+use scallop_compiler::error::CompileError;
+use scallop_compiler::options::CompileOptions;
+use scallop_compiler::{ast_analysis, compile_str_to_rs, parser};
+
+fn analyze(prog: &str) -> Result<Program, CompileError> {
+  let prog = parser::parse_str(prog)?;
+  ast_analysis::analyze(&prog, &CompileOptions::default())?;
+  Ok(prog)
+}
+
+#[test]
+fn test_named_fields() {
+  let prog = analyze(
+    "
+    decl edge(from: Int, to: Int).
+    decl path(Int, Int).
+    edge(to: 1, from: 0).
+    path(A, B) :- edge(to: B, from: A).
+    path(A, C) :- path(A, B), edge(B, to: C).
+  ",
+  )
+  .unwrap();
+  assert_eq!(prog.decls[0].codify(), "decl edge(from: Int, to: Int).");
+  assert_eq!(prog.decls[0].node.field_names(), Some(vec!["from".to_string(), "to".to_string()]));
+  assert_eq!(prog.decls[1].node.field_names(), None);
+  assert_eq!(prog.facts[0].node.head.codify(), "edge(0, 1)");
+  assert_eq!(prog.rules[0].codify(), "path(A, B) :- edge(A, B).");
+  assert_eq!(prog.rules[1].codify(), "path(A, C) :- path(A, B), edge(B, C).");
+}
+
+#[test]
+fn test_missing_fields_are_wildcards() {
+  let prog = analyze(
+    "
+    decl cell(row: Int, col: Int, value: Int).
+    decl filled(Int).
+    filled(V) :- cell(value: V).
+  ",
+  )
+  .unwrap();
+  assert_eq!(prog.rules[0].codify(), "filled(V) :- cell(_, _, V).");
+
+  // A missing field in a head is an unbound wildcard
+  let result = analyze("decl cell(row: Int, col: Int). decl row(Int). cell(row: R) :- row(R).");
+  assert!(matches!(result, Err(CompileError::InvalidWildcard { .. })));
+}
+
+#[test]
+fn test_field_errors() {
+  let err = analyze("decl edge(from: Int, to: Int). decl p(Int). p(A) :- edge(src: A).").unwrap_err();
+  assert_eq!(err.to_string(), "[1:62] Unknown field `src` of relation edge");
+
+  let err = analyze("decl edge(from: Int, to: Int). decl p(Int). p(A) :- edge(A, from: A).").unwrap_err();
+  assert_eq!(err.to_string(), "[1:66] Duplicated field `from` of relation edge");
+
+  let err = analyze("decl edge(from: Int, from: Int).").unwrap_err();
+  assert!(matches!(err, CompileError::DuplicatedField { .. }));
+
+  let err = analyze("decl edge(from: Int, to: Int). edge(to: 1, from: \"a\").").unwrap_err();
+  assert!(err.to_string().ends_with("Type mismatch: expected Int type for field `from` of relation edge"));
+
+  // Positional arguments cannot follow named ones
+  assert!(parser::parse_str("decl edge(from: Int, to: Int). edge(from: 1, 2).").is_err());
+}
+
+#[test]
+fn test_named_arguments_in_single_items() {
+  // e.g. in the REPL, with the declarations entered so far
+  let prog = analyze("decl edge(from: Int, to: Int). decl path(Int, Int).").unwrap();
+  match parser::parse_item_with_decls("path(A, B) :- edge(to: B, from: A).", &prog.decls).unwrap() {
+    parser::Item::Rule(rule) => assert_eq!(rule.codify(), "path(A, B) :- edge(A, B)."),
+    _ => panic!("Expected a rule"),
+  }
+  match parser::parse_item_with_decls("edge(to: 1, from: 0).", &prog.decls).unwrap() {
+    parser::Item::Fact(fact) => assert_eq!(fact.node.head.codify(), "edge(0, 1)"),
+    _ => panic!("Expected a fact"),
+  }
+  let rule = parser::parse_rule_with_decls("path(A, B) :- edge(to: B, from: A).", &prog.decls).unwrap();
+  assert_eq!(rule.codify(), "path(A, B) :- edge(A, B).");
+
+  // Without the declarations, the fields are unknown
+  let err = parser::parse_rule("path(A, B) :- edge(to: B, from: A).").unwrap_err();
+  assert!(matches!(err, CompileError::UnknownField { .. }));
+}
+
+#[test]
+fn test_field_records_in_rs() {
+  let rs = compile_str_to_rs(
+    "
+    decl edge(from: Int, to: Int).
+    decl path(Int, Int).
+    edge(0, 1).
+    path(A, B) :- edge(A, B).
+  ",
+  )
+  .unwrap()
+  .to_string();
+  assert!(rs.contains("pub struct ProgEdge { pub from : i64 , pub to : i64 }"));
+  assert!(rs.contains("pub fn edge_records (& mut self) -> Vec < (ProgEdge , Tag) >"));
+  assert!(!rs.contains("ProgPath"));
+}
+
+#[test]
+fn test_keyword_fields_in_rs() {
+  let rs = compile_str_to_rs("decl step(loop: Int, type_: Int, fn: Int). step(0, 1, 2).").unwrap();
+  syn::parse2::<syn::File>(rs.clone()).unwrap();
+  assert!(rs.to_string().contains("pub struct ProgStep { pub r#loop : i64 , pub type_ : i64 , pub r#fn : i64 }"));
+
+  let err = analyze("decl step(self: Int).").unwrap_err();
+  assert_eq!(err.to_string(), "[1:16] Reserved name `self` for a field of relation step");
+}
@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::PathBuf;
+
+use scallop_compiler::error::CompileError;
+use scallop_compiler::options::CompileOptions;
+use scallop_compiler::{ast_analysis, compile_ast_to_rs, parser};
+
+const GRAPH: &str = "
+  decl edge(Int, Int).
+  decl path(Int, Int).
+  path(A, B) :- edge(A, B).
+  path(A, C) :- edge(A, B), path(B, C).
+  query path(A, B).
+";
+
+/// Write the files into a fresh directory and return the path of the first
+fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("scallop_test_import_{}", test));
+  let _ = fs::remove_dir_all(&dir);
+  for (name, contents) in files {
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, contents).unwrap();
+  }
+  dir.join(files[0].0)
+}
+
+#[test]
+fn test_import_relative_to_importing_file() {
+  let main = write_files(
+    "relative",
+    &[
+      ("main.scl", "import \"lib/reach.scl\". edge(0, 1). edge(1, 2). query reach(B)."),
+      ("lib/reach.scl", "import \"../common/graph.scl\". decl reach(Int). reach(B) :- path(0, B)."),
+      ("common/graph.scl", GRAPH),
+    ],
+  );
+  let prog = parser::parse_file(main.to_str().unwrap()).unwrap();
+  let decls = prog.decls.iter().map(|d| d.node.predicate.as_str()).collect::<Vec<_>>();
+  assert_eq!(decls, vec!["reach", "edge", "path"]);
+  assert_eq!(prog.rules.len(), 3);
+
+  // Only the queries of the main file are kept
+  assert_eq!(prog.queries.len(), 1);
+  assert_eq!(prog.queries[0].node.atom.node.predicate, "reach");
+  ast_analysis::analyze(&prog, &CompileOptions::default()).unwrap();
+
+  // The location ids are unique across files
+  let mut ids = prog.rules.iter().map(|r| r.location.id).collect::<Vec<_>>();
+  ids.dedup();
+  assert_eq!(ids.len(), 3);
+}
+
+#[test]
+fn test_import_with_namespace() {
+  let main = write_files(
+    "namespace",
+    &[
+      (
+        "main.scl",
+        "import \"graph.scl\" as graph. decl path(Int). graph::edge(0, 1). path(B) :- graph::path(0, B). query path(B).",
+      ),
+      ("graph.scl", GRAPH),
+    ],
+  );
+  let prog = parser::parse_file(main.to_str().unwrap()).unwrap();
+  let decls = prog.decls.iter().map(|d| d.node.predicate.as_str()).collect::<Vec<_>>();
+  assert_eq!(decls, vec!["path", "graph::edge", "graph::path"]);
+  assert_eq!(prog.rules[2].codify(), "graph::path(A, C) :- graph::edge(A, B), graph::path(B, C).");
+  assert!(compile_ast_to_rs(&prog).unwrap().to_string().contains("graph__path"));
+}
+
+#[test]
+fn test_import_once_per_namespace() {
+  let main = write_files(
+    "diamond",
+    &[
+      ("main.scl", "import \"a.scl\". import \"b.scl\". import \"graph.scl\" as g."),
+      ("a.scl", "import \"graph.scl\"."),
+      ("b.scl", "import \"graph.scl\"."),
+      ("graph.scl", GRAPH),
+    ],
+  );
+  let prog = parser::parse_file(main.to_str().unwrap()).unwrap();
+  let decls = prog.decls.iter().map(|d| d.node.predicate.as_str()).collect::<Vec<_>>();
+  assert_eq!(decls, vec!["edge", "path", "g::edge", "g::path"]);
+}
+
+#[test]
+fn test_import_errors() {
+  let main = write_files(
+    "cycle",
+    &[("a.scl", "decl edge(Int, Int).\nimport \"b.scl\"."), ("b.scl", "import \"a.scl\".")],
+  );
+  match parser::parse_file(main.to_str().unwrap()) {
+    Err(CompileError::CyclicImport { loc, cycle }) => {
+      assert!(cycle[0].ends_with("a.scl") && cycle[1].ends_with("b.scl") && cycle[2].ends_with("a.scl"));
+      assert!(loc.to_string().contains("b.scl:1:"));
+    }
+    r => panic!("Expected a cyclic import, found {:?}", r.map(|p| p.codify())),
+  }
+
+  let main = write_files("missing", &[("main.scl", "decl edge(Int, Int).\n  import \"nowhere.scl\".")]);
+  let err = parser::parse_file(main.to_str().unwrap()).unwrap_err();
+  assert!(err.to_string().ends_with("main.scl:2:3] Cannot import file \"nowhere.scl\""));
+
+  // Errors in the imported files point into them
+  let main = write_files(
+    "location",
+    &[("main.scl", "import \"graph.scl\"."), ("graph.scl", "decl edge(Int, Int).\npath(A) :- edge(A, \"b\").")],
+  );
+  let prog = parser::parse_file(main.to_str().unwrap()).unwrap();
+  let err = ast_analysis::analyze(&prog, &CompileOptions::default()).unwrap_err();
+  assert!(err.to_string().contains("graph.scl:2:"), "{}", err);
+
+  // Syntax errors in the imported files name them
+  let main = write_files("syntax", &[("main.scl", "import \"bad.scl\"."), ("bad.scl", "decl edge(Int Int).")]);
+  let err = parser::parse_file(main.to_str().unwrap()).unwrap_err();
+  assert!(err.to_string().starts_with("Syntax error in file") && err.to_string().ends_with("bad.scl"), "{}", err);
+}
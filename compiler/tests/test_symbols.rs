@@ -0,0 +1,43 @@
+use scallop_compiler::symbols::*;
+use scallop_compiler::{ast2ram, ram, parser};
+
+#[test]
+fn test_symbol_table() {
+  let mut table = SymbolTable::new();
+  assert_eq!(table.intern("giraffe"), 0);
+  assert_eq!(table.intern("zebra"), 1);
+  assert_eq!(table.intern("giraffe"), 0);
+  assert_eq!(table.name(1), Some("zebra"));
+
+  // Registered ids are skipped when interning
+  assert!(table.register(2, "lion"));
+  assert!(table.register(2, "lion"));
+  assert!(!table.register(2, "tiger"));
+  assert!(!table.register(3, "lion"));
+  assert_eq!(table.intern("tiger"), 3);
+  assert_eq!(table.symbols().map(|(id, _)| id).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn test_symbols_in_ram() {
+  let ast = parser::parse_str(
+    "
+    decl kind(Symbol, Symbol).
+    kind(giraffe, animal).
+    kind(A, mammal) :- kind(A, animal).
+  ",
+  )
+  .unwrap();
+  let ram = ast2ram::ast2ram(&ast).unwrap();
+  let names = ram.symbols.iter().map(|(_, name)| name.as_str()).collect::<Vec<_>>();
+  assert_eq!(names.len(), 3);
+  for (id, name) in &ram.symbols {
+    assert_eq!(symbol_id(name), Some(*id));
+  }
+  let giraffe = symbol_id("giraffe").unwrap();
+  let animal = symbol_id("animal").unwrap();
+  assert!(matches!(
+    ram.facts[0].args.as_slice(),
+    [ram::Constant::Symbol(a), ram::Constant::Symbol(b)] if *a == giraffe && *b == animal
+  ));
+}
@@ -0,0 +1,77 @@
+use scallop_compiler::ast_analysis::{self, AnalysisResult};
+use scallop_compiler::common::Type;
+use scallop_compiler::error::CompileError;
+use scallop_compiler::options::CompileOptions;
+use scallop_compiler::{compile_str_to_rs, parser};
+
+fn analyze(prog: &str) -> Result<AnalysisResult, CompileError> {
+  let prog = parser::parse_str(prog)?;
+  ast_analysis::analyze(&prog, &CompileOptions::default())
+}
+
+#[test]
+fn test_infer_through_rules() {
+  let result = analyze(
+    "
+    decl edge(Symbol, Symbol).
+    path(A, B) :- edge(A, B).
+    path(A, C) :- path(A, B), edge(B, C).
+    hops(A, B, 1) :- edge(A, B).
+    far(A, B) :- hops(A, B, N), N > 2.
+  ",
+  )
+  .unwrap();
+  assert_eq!(result.decls["path"], vec![Type::Symbol, Type::Symbol]);
+  assert_eq!(result.decls["hops"], vec![Type::Symbol, Type::Symbol, Type::Integer]);
+  assert_eq!(result.decls["far"], vec![Type::Symbol, Type::Symbol]);
+}
+
+#[test]
+fn test_infer_from_facts_and_bodies() {
+  let result = analyze(
+    "
+    decl person(Symbol).
+    name(\"alice\", true).
+    count(3).
+    age(0, 30).
+    adult(P) :- person(P), age(P, A), A >= 18.
+    reachable(B) :- link(A, B), person(A), count(B).
+  ",
+  )
+  .unwrap();
+  assert_eq!(result.decls["name"], vec![Type::String, Type::Boolean]);
+  assert_eq!(result.decls["count"], vec![Type::Integer]);
+
+  // Integer constants are symbols when the column is joined with symbols
+  assert_eq!(result.decls["age"], vec![Type::Symbol, Type::Integer]);
+  assert_eq!(result.decls["adult"], vec![Type::Symbol]);
+
+  // A relation only used in bodies takes the types of its variables
+  assert_eq!(result.decls["link"], vec![Type::Symbol, Type::Integer]);
+  assert_eq!(result.decls["reachable"], vec![Type::Integer]);
+}
+
+#[test]
+fn test_inference_errors() {
+  let err = analyze("decl edge(Int, Int). p(A) :- q(A), edge(B, B).").unwrap_err();
+  match &err {
+    CompileError::AmbiguousRelationType { rela_name, column, .. } => assert_eq!((rela_name.as_str(), *column), ("p", 0)),
+    e => panic!("Expected an ambiguous relation type, found {}", e),
+  }
+  assert!(err.to_string().ends_with("Cannot infer the type of argument 1 of relation p; consider declaring it"));
+
+  // Conflicting usages of an undeclared relation are reported as usual
+  let err = analyze("num(1). num(\"one\").").unwrap_err();
+  assert!(matches!(err, CompileError::TypeMismatch { ty: Type::String, .. }));
+  let err = analyze("num(1). num(1, 2).").unwrap_err();
+  assert!(matches!(err, CompileError::IncorrectArity { expected: 1, found: 2, .. }));
+}
+
+#[test]
+fn test_inferred_relations_in_rs() {
+  let rs = compile_str_to_rs("edge(0, 1). path(A, B) :- edge(A, B). path(A, C) :- path(A, B), edge(B, C).")
+    .unwrap()
+    .to_string();
+  assert!(rs.contains("edge : Variable < (i64 , i64) , Tag >"));
+  assert!(rs.contains("path : Variable < (i64 , i64) , Tag >"));
+}
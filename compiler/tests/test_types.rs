@@ -0,0 +1,101 @@
+use scallop_compiler::ast_analysis::{self, AnalysisResult};
+use scallop_compiler::common::Type;
+use scallop_compiler::error::CompileError;
+use scallop_compiler::options::CompileOptions;
+use scallop_compiler::{compile_str_to_rs, parser};
+
+const SCHOOL: &str = "
+  type Person <: Symbol.
+  type Student <: Person.
+  type Course <: Symbol.
+  decl person(Person).
+  decl student(Student).
+  decl course(Course).
+  decl enrolled(Student, Course).
+  decl name(Symbol, String).
+  person(alice). person(3). student(bob). course(cs101).
+  enrolled(bob, cs101).
+  name(alice, \"Alice\").
+";
+
+fn analyze(prog: &str) -> Result<AnalysisResult, CompileError> {
+  let prog = parser::parse_str(prog)?;
+  ast_analysis::analyze(&prog, &CompileOptions::default())
+}
+
+#[test]
+fn test_type_declarations() {
+  let prog = parser::parse_str(SCHOOL).unwrap();
+  assert_eq!(prog.type_decls[1].codify(), "type Student <: Person.");
+  assert_eq!(prog.decls[3].codify(), "decl enrolled(Student, Course).");
+
+  let result = analyze(&format!("{} taking(S, C) :- enrolled(S, C).", SCHOOL)).unwrap();
+  assert_eq!(result.type_aliases["Student"], Type::Named("Person".to_string()));
+  assert_eq!(result.decls["taking"], vec![Type::Named("Student".to_string()), Type::Named("Course".to_string())]);
+}
+
+#[test]
+fn test_declared_types_with_base_types() {
+  // The values of declared types are also of their base types
+  analyze(&format!("{} decl greet(String). greet(N) :- person(P), name(P, N).", SCHOOL)).unwrap();
+  analyze(&format!("{} decl alice(Person). alice(P) :- person(P), P == alice.", SCHOOL)).unwrap();
+  analyze(&format!("{} decl attends(Person). attends(S) :- student(S), enrolled(S, _).", SCHOOL)).unwrap();
+}
+
+#[test]
+fn test_declared_types_mismatch() {
+  let err = analyze(&format!("{} decl odd(Course). odd(P) :- person(P), course(P).", SCHOOL)).unwrap_err();
+  match &err {
+    CompileError::TypeUnificationError { loc_1, loc_2, ty_1, ty_2 } => {
+      assert_eq!((ty_1.to_string(), ty_2.to_string()), ("Person".to_string(), "Course".to_string()));
+      assert_ne!(loc_1, loc_2);
+    }
+    e => panic!("Expected a type unification error, found {}", e),
+  }
+  assert!(err.to_string().starts_with("Cannot unify two types: Person at ["));
+
+  // Subtypes of different declared types are not compatible either
+  let err = analyze(&format!("{} odd(S) :- student(S), course(S).", SCHOOL)).unwrap_err();
+  assert!(matches!(err, CompileError::TypeUnificationError { .. }));
+  let err = analyze(&format!("{} odd(S, C) :- student(S), course(C), S == C.", SCHOOL)).unwrap_err();
+  assert!(matches!(err, CompileError::TypeUnificationError { .. }));
+}
+
+#[test]
+fn test_declared_types_in_head() {
+  // The head only takes the values of the body as their supertypes
+  let err = analyze(&format!("{} student(P) :- person(P).", SCHOOL)).unwrap_err();
+  match &err {
+    CompileError::SubtypeMismatch { ty, expected, .. } => {
+      assert_eq!((ty.to_string(), expected.to_string()), ("Person".to_string(), "Student".to_string()));
+    }
+    e => panic!("Expected a subtype mismatch, found {}", e),
+  }
+  assert!(err.to_string().contains("Type mismatch: expected Student type, found Person from ["));
+  let err = analyze(&format!("{} decl tag(Symbol). student(T) :- tag(T).", SCHOOL)).unwrap_err();
+  assert!(matches!(err, CompileError::SubtypeMismatch { .. }));
+
+  // Negated atoms do not narrow the values
+  analyze(&format!("{} decl teacher(Person). teacher(P) :- person(P), ~student(P).", SCHOOL)).unwrap();
+  let err = analyze(&format!("{} decl other(Person). other(P) :- person(P), ~course(P).", SCHOOL)).unwrap_err();
+  assert!(matches!(err, CompileError::TypeUnificationError { .. }));
+}
+
+#[test]
+fn test_type_declaration_errors() {
+  let err = analyze("type Person <: Symbol. decl person(Persn).").unwrap_err();
+  assert!(err.to_string().ends_with("Unknown type Persn"));
+  let err = analyze("type Person <: Persn.").unwrap_err();
+  assert!(matches!(err, CompileError::UnknownType { .. }));
+  let err = analyze("type Person <: Symbol. type Person <: Int.").unwrap_err();
+  assert!(err.to_string().ends_with("Duplicated declaration of type Person"));
+  analyze("type Person <: Symbol. type Person <: Symbol.").unwrap();
+}
+
+#[test]
+fn test_declared_types_erased_in_rs() {
+  let rs = compile_str_to_rs(SCHOOL).unwrap().to_string();
+  assert!(rs.contains("person : Variable < usize , Tag >"));
+  assert!(rs.contains("enrolled : Variable < (usize , usize) , Tag >"));
+  assert!(!rs.contains("Person"));
+}
@@ -0,0 +1,87 @@
+use scallop_compiler::ast_eval::Value;
+use scallop_compiler::explain::*;
+use scallop_compiler::parser;
+use scallop_compiler::why_not::*;
+
+const PATH: &str = "
+  decl edge(Int, Int).
+  decl path(Int, Int).
+  decl short(Int, Int).
+  edge(0, 1). edge(1, 2). edge(5, 6).
+  path(A, B) :- edge(A, B).
+  path(A, C) :- edge(A, B), path(B, C).
+  short(A, B) :- path(A, B), B - A < 2.
+";
+
+fn why_not(prog: &str, atom: &str) -> WhyNot {
+  let prog = parser::parse_str(prog).unwrap();
+  let explainer = Explainer::new(&prog).unwrap();
+  explainer.why_not(&parser::parse_query(atom).unwrap().node.atom)
+}
+
+#[test]
+fn test_why_not_missing_tuple() {
+  let result = why_not(PATH, "path(0, 6)");
+  assert!(result.derived.is_empty());
+  assert_eq!(result.rules.len(), 2);
+
+  // The base case fails on the first atom
+  let base = &result.rules[0];
+  assert_eq!(base.row, 6);
+  assert_eq!(base.candidates.len(), 1);
+  assert_eq!(base.candidates[0].num_satisfied, 0);
+  assert_eq!(base.candidates[0].failure, Failure::MissingTuple { atom: "edge(0, 6)".to_string() });
+
+  // The recursive case gets to the second atom
+  let rec = &result.rules[1];
+  assert_eq!(rec.candidates[0].num_satisfied, 1);
+  assert_eq!(rec.candidates[0].bindings["B"], Value::Integer(1));
+  assert_eq!(rec.candidates[0].failure, Failure::MissingTuple { atom: "path(1, 6)".to_string() });
+}
+
+#[test]
+fn test_why_not_constraint() {
+  let result = why_not(PATH, "short(0, 2)");
+  assert_eq!(result.rules.len(), 1);
+  // The constants of the pattern are enough to evaluate the constraint
+  let candidate = &result.rules[0].candidates[0];
+  assert_eq!(candidate.num_satisfied, 0);
+  assert_eq!(candidate.failure, Failure::FalseConstraint { constraint: "B - A < 2".to_string() });
+  assert!(result.to_string().contains("0 of 2 body literals hold: constraint B - A < 2 is false"));
+
+  let result = why_not(PATH, "short(A, 5)");
+  let candidate = &result.rules[0].candidates[0];
+  assert_eq!(candidate.num_satisfied, 0);
+  assert_eq!(candidate.failure, Failure::MissingTuple { atom: "path(A, 5)".to_string() });
+}
+
+#[test]
+fn test_why_not_derived_and_unmatched() {
+  let result = why_not(PATH, "path(0, X)");
+  assert_eq!(result.derived.len(), 2);
+  assert!(result.rules.is_empty());
+
+  let result = why_not(PATH, "edge(3, 4)");
+  assert!(result.derived.is_empty());
+  assert!(result.rules.is_empty());
+  assert!(result.to_string().contains("no input fact or rule head matches it"));
+}
+
+#[test]
+fn test_why_not_bounded_candidates() {
+  let result = why_not(
+    "
+    decl num(Int).
+    decl odd(Int).
+    decl pair(Int, Int).
+    num(0). num(1). num(2). num(3). num(4). num(5). num(6). num(7).
+    pair(A, B) :- num(A), num(B), odd(B).
+    ",
+    "pair(0, X)",
+  );
+  // Every `B` fails on `odd(B)`, but only the first few are kept
+  let candidates = &result.rules[0].candidates;
+  assert_eq!(candidates.len(), MAX_CANDIDATES);
+  assert!(candidates.iter().all(|c| c.num_satisfied == 2));
+  assert_eq!(candidates[0].failure, Failure::MissingTuple { atom: "odd(0)".to_string() });
+}
@@ -0,0 +1,42 @@
+use scallop_compiler::ast2ram::{column_indices, nest_columns};
+use scallop_compiler::compile_str_to_rs;
+
+fn wide_program(arity: usize) -> String {
+  let types = vec!["Int"; arity].join(", ");
+  let vars = (0..arity).map(|i| format!("C{}", i)).collect::<Vec<_>>().join(", ");
+  let consts = (0..arity).map(|i| i.to_string()).collect::<Vec<_>>().join(", ");
+  format!(
+    "decl wide({types}). decl ends(Int, Int). decl copy({types}).
+    wide({consts}).
+    ends(C0, C{last}) :- wide({vars}).
+    copy({vars}) :- ends(C0, C{last}), wide({vars}).",
+    types = types,
+    vars = vars,
+    consts = consts,
+    last = arity - 1,
+  )
+}
+
+#[test]
+fn test_column_layout() {
+  let columns = nest_columns((0..32).map(|i| vec![i]).collect(), &|cs| cs.concat());
+  assert_eq!(columns, (0..32).collect::<Vec<_>>());
+  assert_eq!(column_indices(1, 0), Vec::<usize>::new());
+  assert_eq!(column_indices(10, 9), vec![9]);
+  assert_eq!(column_indices(32, 0), vec![0, 0, 0]);
+  assert_eq!(column_indices(32, 19), vec![1, 0, 3]);
+  assert_eq!(column_indices(100, 99), vec![1, 1, 1, 1, 6]);
+}
+
+#[test]
+fn test_wide_relations_in_rs() {
+  let rs = compile_str_to_rs(&wide_program(32)).unwrap().to_string();
+  let i8 = vec!["i64"; 8].join(" , ");
+  let half = format!("(({}) , ({}))", i8, i8);
+  assert!(rs.contains(&format!("wide : Variable < ({} , {}) , Tag >", half, half)));
+  assert!(rs.contains("(((0i64 , 1i64 , 2i64 , 3i64 , 4i64 , 5i64 , 6i64 , 7i64) ,"));
+  assert!(rs.contains("(arg . 0 . 0 . 0 , arg . 1 . 1 . 7)"));
+
+  // Arbitrarily wide relations are nested deeper
+  assert!(compile_str_to_rs(&wide_program(100)).is_ok());
+}
@@ -0,0 +1,80 @@
+use std::time::Instant;
+
+use scallop_runtime::dataflows::*;
+use scallop_runtime::tags::*;
+use scallop_runtime::*;
+
+/// ``` datalog
+/// decl digit_a(PairID, Int).
+/// decl digit_b(PairID, Int).
+/// decl sum(PairID, Int).
+///
+/// sum(P, D1 + D2) :- digit_a(P, D1), digit_b(P, D2).
+/// ```
+///
+/// Every digit is a categorical input, so there is one disjunction per image.
+/// With thousands of images, this stresses the disjunction conflict checks
+/// performed in the multiplication of proofs.
+struct ManySum2<Tag: Semiring<Context = ProbProofContext>> {
+  iter: Iteration<Tag>,
+  digit_a: Variable<(usize, u8), Tag>,
+  digit_b: Variable<(usize, u8), Tag>,
+  sum: Variable<(usize, u8), Tag>,
+}
+
+impl<Tag: Semiring<Context = ProbProofContext>> Program<Tag> for ManySum2<Tag> {
+  fn new() -> Self {
+    let mut iter = Iteration::new();
+    let digit_a = iter.variable::<(usize, u8)>();
+    let digit_b = iter.variable::<(usize, u8)>();
+    let sum = iter.variable::<(usize, u8)>();
+    Self { iter, digit_a, digit_b, sum }
+  }
+
+  fn iteration(&self) -> &Iteration<Tag> {
+    &self.iter
+  }
+
+  fn iteration_mut(&mut self) -> &mut Iteration<Tag> {
+    &mut self.iter
+  }
+
+  fn update(&self) {
+    self.iter.insert_dataflow(
+      &self.sum,
+      self
+        .iter
+        .join(&self.digit_a, &self.digit_b)
+        .project(|(p, d1, d2)| (p, d1 + d2)),
+    );
+  }
+}
+
+fn digit_distr(pair_id: usize) -> Vec<(f64, (usize, u8))> {
+  (0..10).map(|d| (if d == pair_id % 10 { 0.91 } else { 0.01 }, (pair_id, d as u8))).collect()
+}
+
+fn main() {
+  let num_pairs = std::env::args().nth(1).and_then(|n| n.parse().ok()).unwrap_or(2000);
+  let mut prog = ManySum2::<TopKProbProofs<3>>::new();
+
+  // One disjunction per image
+  for pair_id in 0..num_pairs {
+    prog.iter.insert_disjunction(&prog.digit_a, digit_distr(pair_id));
+    prog.iter.insert_disjunction(&prog.digit_b, digit_distr(pair_id + 1));
+  }
+
+  // Execute the program
+  let start = Instant::now();
+  prog.run();
+  let elapsed = start.elapsed();
+
+  let result = prog.iter.complete(&prog.sum);
+  println!(
+    "#pairs: {}, #disjunctions: {}, #sums: {}, time: {:?}",
+    num_pairs,
+    prog.iter.semiring_ctx.disjunctions.len(),
+    result.len(),
+    elapsed
+  );
+}
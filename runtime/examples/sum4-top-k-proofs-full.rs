@@ -64,10 +64,10 @@ impl<Tag: Semiring<Context = ProbProofContext>> Program<Tag> for Sum4<Tag> {
   }
 }
 
-fn ten_normalized_numbers(seed: u64) -> Vec<f32> {
+fn ten_normalized_numbers(seed: u64) -> Vec<f64> {
   use rand::prelude::*;
   let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
-  let mut numbers = (0..9).map(|_| rng.gen::<f32>()).collect::<Vec<_>>();
+  let mut numbers = (0..9).map(|_| rng.gen::<f64>()).collect::<Vec<_>>();
   let sum = numbers.iter().fold(0.0, |a, p| a + p);
   for i in 0..9 {
     numbers[i] /= sum;
@@ -54,21 +54,16 @@ impl CompilerContext {
 
     // Generate declarations
     let decls = self.variables.iter().filter_map(|(name, (_, tup_type))| {
-      match tup_type {
-        TupleType::Tuple(tys) => {
-          let tys = tys.iter().map(|ty| {
-            match ty {
-              TupleType::Integer => Some(common::Type::Integer),
-              TupleType::Boolean => Some(common::Type::Boolean),
-              TupleType::String => Some(common::Type::String),
-              TupleType::Symbol => Some(common::Type::Symbol),
-              _ => None
-            }
-          }).collect::<Option<Vec<_>>>()?;
-          Some((name.clone(), tys))
+      let tys = tup_type.columns()?.iter().map(|ty| {
+        match ty {
+          TupleType::Integer => Some(common::Type::Integer),
+          TupleType::Boolean => Some(common::Type::Boolean),
+          TupleType::String => Some(common::Type::String),
+          TupleType::Symbol => Some(common::Type::Symbol),
+          _ => None
         }
-        _ => None
-      }
+      }).collect::<Option<Vec<_>>>()?;
+      Some((name.clone(), tys))
     }).collect::<HashMap<_, _>>();
 
     // Get type assign context
@@ -88,7 +83,7 @@ impl CompilerContext {
     let type_assign = first_pass.0;
     let mut node_types = type_assign.node_types;
     let to_unify_args = type_assign.to_unify_args;
-    let mut second_pass = TypeUnification::new(&mut node_types, to_unify_args);
+    let mut second_pass = TypeUnification::new(&mut node_types, to_unify_args, &type_assign.type_aliases);
     visit_rule(&mut second_pass, ast).map_err(|e| DynCompileError::CompileError(e))?;
 
     // Success
@@ -104,6 +99,7 @@ impl CompilerContext {
           common::Type::Integer => TupleType::Integer,
           common::Type::String => TupleType::String,
           common::Type::Symbol => TupleType::Symbol,
+          common::Type::Named(n) => panic!("Type {} should have been erased", n),
         }
       },
       ram::VarType::Tuple(fields) => {
@@ -118,7 +114,7 @@ impl CompilerContext {
     (tmp_var.name, self.ram_type_to_tuple_type(tmp_var.arg_types))
   }
 
-  fn ram_const_to_dyn_tuple(&self, c: &ram::Constant) -> DynTuple {
+  pub(crate) fn ram_const_to_dyn_tuple(&self, c: &ram::Constant) -> DynTuple {
     match c {
       ram::Constant::Boolean(b) => DynTuple::Boolean(b.clone()),
       ram::Constant::Integer(i) => DynTuple::Integer(i.clone()),
@@ -128,7 +124,7 @@ impl CompilerContext {
   }
 
   fn ram_consts_to_dyn_tuple(&self, args: &Vec<ram::Constant>) -> DynTuple {
-    DynTuple::Tuple(args.iter().map(|c| {
+    DynTuple::from_columns(args.iter().map(|c| {
       self.ram_const_to_dyn_tuple(c)
     }).collect::<Vec<_>>())
   }
@@ -180,10 +176,7 @@ impl CompilerContext {
           op0: Box::new(self.ram_arg_to_dyn_exp(a)),
         })
       },
-      ram::Argument::Element(acc) => {
-        let indices = acc.iter().map(|i| *i as u8).collect::<Vec<_>>();
-        interpreter::Expression::Access(TupleAccessor::from_indices(&indices))
-      },
+      ram::Argument::Element(acc) => interpreter::Expression::Access(TupleAccessor::from_indices(acc)),
       ram::Argument::Constant(c) => {
         let c = self.ram_const_to_dyn_const(c);
         interpreter::Expression::Constant(c)
@@ -264,6 +257,60 @@ impl CompilerContext {
     }
   }
 
+  /// Compile a whole program, analyzed and transformed like a compiled one,
+  /// into the relations, facts and updates to add to the iteration
+  pub fn compile_program_from_ast(&mut self, mut ast: ast::Program) -> Result<ProgramToAdd, DynCompileError> {
+    let ram = self.ast_program_to_ram(&mut ast).map_err(DynCompileError::CompileError)?;
+
+    // Then we turn ram items to dyn items
+    let to_dyn_facts = |facts: Vec<ram::Fact>| {
+      facts
+        .into_iter()
+        .map(|fact| (fact.prob, fact.predicate, self.ram_consts_to_dyn_tuple(&fact.args)))
+        .collect::<Vec<_>>()
+    };
+    Ok(ProgramToAdd {
+      vars_to_add: ram
+        .variables
+        .into_iter()
+        .map(|var| self.ram_variable_to_dyn_variable(var))
+        .collect(),
+      facts_to_add: to_dyn_facts(ram.facts),
+      disjunctions_to_add: ram.disjunctions.into_iter().map(|disj| to_dyn_facts(disj.facts)).collect(),
+      updates_to_add: ram
+        .updates
+        .iter()
+        .map(|ram_update| self.ram_update_to_dyn_update(ram_update))
+        .collect(),
+    })
+  }
+
+  /// Like `ast2ram`, but the temporary variables do not collide with the ones
+  /// of the dynamic rules
+  fn ast_program_to_ram(&mut self, ast: &mut ast::Program) -> Result<ram::Program, CompileError> {
+    use scallop_compiler::{ast_analysis, ast_transform, options::CompileOptions};
+    let options = CompileOptions::default();
+    let mut analysis = ast_analysis::analyze(ast, &options)?;
+    ast_transform::transform(ast, &mut analysis, &options)?;
+
+    let id_map = ast2ram::identifier_map(ast);
+    let mut variables = ast2ram::ast_to_ram_variables(ast);
+    let mut facts = ast2ram::ast_to_ram_facts(ast, &id_map)?;
+    let disjunctions = ast2ram::ast_to_ram_disjunctions(ast, &id_map)?;
+    let mut updates = vec![];
+    for rule in &ast.rules {
+      let tmp_var_id = &mut self.tmp_var_id_allocator.curr_id;
+      updates.extend(ast2ram::ast_rule_to_ram_updates(rule, &mut variables, &mut facts, &id_map, tmp_var_id)?);
+    }
+    Ok(ram::Program {
+      variables,
+      facts,
+      disjunctions,
+      updates,
+      symbols: vec![],
+    })
+  }
+
   pub fn compile_rule_from_ast(&mut self, ast: ast::Rule) -> Result<RuleToAdd, DynCompileError> {
     // First do analysis on the ast to make sure it is well formed
     self.analyze_rule_ast(&ast)?;
@@ -274,6 +321,7 @@ impl CompilerContext {
         is_temporary: false,
         arg_types: self.tuple_type_to_var_type(tup_type),
         name: name.clone(),
+        field_names: None,
       }
     }).collect::<Vec<_>>();
     let num_existing_vars = vars.len();
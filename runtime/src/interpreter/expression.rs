@@ -16,7 +16,7 @@ impl Expression {
   pub fn eval(&self, comp: &DynTuple) -> DynTuple {
     match self {
       Self::Tuple(exprs) => DynTuple::Tuple(exprs.iter().map(|expr| expr.eval(comp)).collect()),
-      Self::Access(acc) => comp[acc.clone()].clone(),
+      Self::Access(acc) => comp[acc].clone(),
       Self::Constant(cst) => cst.eval(),
       Self::Binary(bin) => bin.eval(comp),
       Self::Unary(una) => una.eval(comp),
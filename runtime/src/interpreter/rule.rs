@@ -13,6 +13,24 @@ pub struct RuleToAdd {
   pub updates_to_add: Vec<Update>,
 }
 
+/// The facts of a program, with their probabilities
+pub type FactsToAdd = Vec<(Option<f64>, String, DynTuple)>;
+
+#[derive(Clone, Debug)]
+pub struct ProgramToAdd {
+  /// The declared and temporary dynamic variables to add
+  pub vars_to_add: Vec<(String, TupleType)>,
+
+  /// The facts to add
+  pub facts_to_add: FactsToAdd,
+
+  /// The disjunctions of mutually exclusive facts to add
+  pub disjunctions_to_add: Vec<FactsToAdd>,
+
+  /// The compiled updates of all the rules to add
+  pub updates_to_add: Vec<Update>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Rule {
   /// The updates corresopnding to this rule
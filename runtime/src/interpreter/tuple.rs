@@ -1,7 +1,7 @@
 use super::utils::*;
 use crate::*;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum DynTuple {
   Integer(i64),
   Boolean(bool),
@@ -11,6 +11,28 @@ pub enum DynTuple {
 }
 
 impl DynTuple {
+  /// The tuple of a relation with the columns, laid out like the compiled relations
+  pub fn from_columns(columns: Vec<DynTuple>) -> Self {
+    if columns.is_empty() {
+      Self::Tuple(vec![])
+    } else {
+      scallop_compiler::ast2ram::nest_columns(columns, &Self::Tuple)
+    }
+  }
+
+  /// The leaf elements of the tuple in order
+  pub fn columns(&self) -> Vec<DynTuple> {
+    fn flatten(t: &DynTuple, columns: &mut Vec<DynTuple>) {
+      match t {
+        DynTuple::Tuple(ts) => ts.iter().for_each(|t| flatten(t, columns)),
+        t => columns.push(t.clone()),
+      }
+    }
+    let mut columns = vec![];
+    flatten(self, &mut columns);
+    columns
+  }
+
   pub fn is_true(&self) -> bool {
     match self {
       Self::Boolean(b) => b.clone(),
@@ -25,7 +47,7 @@ impl std::fmt::Debug for DynTuple {
       Self::Integer(i) => write!(f, "{}", i),
       Self::Boolean(b) => write!(f, "{}", b),
       Self::String(s) => write!(f, "\"{}\"", s),
-      Self::Symbol(s) => write!(f, "{}", s),
+      Self::Symbol(s) => write!(f, "{}", crate::symbols::format_symbol(*s)),
       Self::Tuple(cs) => {
         write!(f, "(")?;
         if cs.len() == 1 {
@@ -138,6 +160,22 @@ impl From<i64> for DynTuple {
   }
 }
 
+/// Narrower integers are stored as integers, e.g. the `u8` digits of the
+/// hand-written programs
+macro_rules! impl_from_small_int_for_dyn_tuple {
+  ( $($ty:ty),* ) => {
+    $(
+      impl From<$ty> for DynTuple {
+        fn from(i: $ty) -> Self {
+          Self::Integer(i.into())
+        }
+      }
+    )*
+  }
+}
+
+impl_from_small_int_for_dyn_tuple!(i8, i16, i32, u8, u16, u32);
+
 impl From<bool> for DynTuple {
   fn from(b: bool) -> Self {
     Self::Boolean(b)
@@ -180,87 +218,49 @@ impl From<()> for DynTuple {
   }
 }
 
-impl<A: Into<DynTuple>> From<(A,)> for DynTuple {
-  fn from((a,): (A,)) -> Self {
-    Self::Tuple(vec![a.into()])
-  }
-}
-
-impl<A, B> From<(A, B)> for DynTuple
-where
-  A: Into<DynTuple>,
-  B: Into<DynTuple>
-{
-  fn from((a, b): (A, B)) -> Self {
-    Self::Tuple(vec![a.into(), b.into()])
-  }
-}
-
-impl<A, B, C> From<(A, B, C)> for DynTuple
-where
-  A: Into<DynTuple>,
-  B: Into<DynTuple>,
-  C: Into<DynTuple>,
-{
-  fn from((a, b, c): (A, B, C)) -> Self {
-    Self::Tuple(vec![a.into(), b.into(), c.into()])
+macro_rules! impl_from_tuple_for_dyn_tuple {
+  ( $($id:ident,)* ) => {
+    impl<$($id,)*> From<($($id,)*)> for DynTuple
+    where
+      $($id: Into<DynTuple>,)*
+    {
+      #[allow(non_snake_case)]
+      fn from(($($id,)*): ($($id,)*)) -> Self {
+        Self::Tuple(vec![$($id.into()),*])
+      }
+    }
   }
 }
 
-impl<A, B, C, D> From<(A, B, C, D)> for DynTuple
-where
-  A: Into<DynTuple>,
-  B: Into<DynTuple>,
-  C: Into<DynTuple>,
-  D: Into<DynTuple>,
-{
-  fn from((a, b, c, d): (A, B, C, D)) -> Self {
-    Self::Tuple(vec![a.into(), b.into(), c.into(), d.into()])
-  }
-}
+impl_from_tuple_for_dyn_tuple!(A,);
+impl_from_tuple_for_dyn_tuple!(A, B,);
+impl_from_tuple_for_dyn_tuple!(A, B, C,);
+impl_from_tuple_for_dyn_tuple!(A, B, C, D,);
+impl_from_tuple_for_dyn_tuple!(A, B, C, D, E,);
+impl_from_tuple_for_dyn_tuple!(A, B, C, D, E, F,);
+impl_from_tuple_for_dyn_tuple!(A, B, C, D, E, F, G,);
+impl_from_tuple_for_dyn_tuple!(A, B, C, D, E, F, G, H,);
+impl_from_tuple_for_dyn_tuple!(A, B, C, D, E, F, G, H, I,);
+impl_from_tuple_for_dyn_tuple!(A, B, C, D, E, F, G, H, I, J,);
+impl_from_tuple_for_dyn_tuple!(A, B, C, D, E, F, G, H, I, J, K,);
+impl_from_tuple_for_dyn_tuple!(A, B, C, D, E, F, G, H, I, J, K, L,);
 
-impl<A, B, C, D, E> From<(A, B, C, D, E)> for DynTuple
-where
-  A: Into<DynTuple>,
-  B: Into<DynTuple>,
-  C: Into<DynTuple>,
-  D: Into<DynTuple>,
-  E: Into<DynTuple>,
-{
-  fn from((a, b, c, d, e): (A, B, C, D, E)) -> Self {
-    Self::Tuple(vec![a.into(), b.into(), c.into(), d.into(), e.into()])
-  }
-}
+impl std::ops::Index<TupleAccessor> for DynTuple {
+  type Output = DynTuple;
 
-impl<A, B, C, D, E, F> From<(A, B, C, D, E, F)> for DynTuple
-where
-  A: Into<DynTuple>,
-  B: Into<DynTuple>,
-  C: Into<DynTuple>,
-  D: Into<DynTuple>,
-  E: Into<DynTuple>,
-  F: Into<DynTuple>,
-{
-  fn from((a, b, c, d, e, f): (A, B, C, D, E, F)) -> Self {
-    Self::Tuple(vec![a.into(), b.into(), c.into(), d.into(), e.into(), f.into()])
+  fn index(&self, acc: TupleAccessor) -> &Self::Output {
+    &self[&acc]
   }
 }
 
-impl std::ops::Index<TupleAccessor> for DynTuple {
+impl std::ops::Index<&TupleAccessor> for DynTuple {
   type Output = DynTuple;
 
-  fn index(&self, acc: TupleAccessor) -> &Self::Output {
-    fn index_helper(source: &DynTuple, acc: TupleAccessor) -> &DynTuple {
-      if acc.len() == 0 {
-        source
-      } else {
-        match source {
-          DynTuple::Tuple(comps) => index_helper(&comps[acc.first_level()], acc.indent()),
-          _ => panic!("Should not happen"),
-        }
-      }
-    }
-    index_helper(self, acc)
+  fn index(&self, acc: &TupleAccessor) -> &Self::Output {
+    acc.indices().iter().fold(self, |source, i| match source {
+      DynTuple::Tuple(comps) => &comps[*i],
+      _ => panic!("Should not happen"),
+    })
   }
 }
 
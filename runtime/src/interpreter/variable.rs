@@ -9,6 +9,7 @@ pub struct DynVariable<Tag: Semiring> {
   pub stable: Rc<RefCell<Vec<DynRelation<Tag>>>>,
   pub recent: Rc<RefCell<DynRelation<Tag>>>,
   to_add: Rc<RefCell<Vec<DynRelation<Tag>>>>,
+  name: Option<String>,
 }
 
 impl<Tag: Semiring> DynVariable<Tag> {
@@ -17,9 +18,22 @@ impl<Tag: Semiring> DynVariable<Tag> {
       stable: Rc::new(RefCell::new(Vec::new())),
       recent: Rc::new(RefCell::new(DynRelation::empty())),
       to_add: Rc::new(RefCell::new(Vec::new())),
+      name: None,
     }
   }
 
+  /// Create a new variable for the relation `name`
+  pub fn with_name(name: &str) -> Self {
+    Self {
+      name: Some(name.into()),
+      ..Self::new()
+    }
+  }
+
+  pub fn name(&self) -> Option<&str> {
+    self.name.as_deref()
+  }
+
   pub fn insert_with_context(
     &self,
     ctx: &mut Tag::Context,
@@ -31,8 +45,8 @@ impl<Tag: Semiring> DynVariable<Tag> {
     let elements = data
       .into_iter()
       .map(|(info, tup)| DynElement {
+        tag: ctx.base_tag_of_tuple(info, self.name(), &tup),
         tup,
-        tag: ctx.base_tag(info),
       })
       .collect::<Vec<_>>();
     let dataflow = DynDataflow::Vec(&elements);
@@ -75,23 +89,31 @@ impl<Tag: Semiring> DynVariable<Tag> {
         to_add = to_add.merge(to_add_more, ctx);
       }
 
-      // Make sure that there is no duplicates
+      // Make sure that there is no duplicates; the late derivations of stable
+      // tuples are kept if the semiring propagates them
       for batch in self.stable.borrow().iter() {
         let mut slice = &batch[..];
         // Only gallop if the slice is relatively large.
-        if slice.len() > 4 * to_add.elements.len() {
-          to_add.elements.retain(|x| {
+        let gallop = slice.len() > 4 * to_add.elements.len();
+        to_add.elements.retain_mut(|x| {
+          if gallop {
             slice = super::utils::gallop(slice, |y| y < x);
-            slice.is_empty() || &slice[0] != x
-          });
-        } else {
-          to_add.elements.retain(|x| {
+          } else {
             while !slice.is_empty() && &slice[0] < x {
               slice = &slice[1..];
             }
-            slice.is_empty() || &slice[0] != x
-          });
-        }
+          }
+          if slice.is_empty() || &slice[0] != x {
+            return true;
+          }
+          match Tag::late_derivation(ctx, &slice[0].tag, &x.tag) {
+            Some(tag) => {
+              x.tag = tag;
+              true
+            }
+            None => false,
+          }
+        });
       }
 
       *self.recent.borrow_mut() = to_add;
@@ -126,4 +148,11 @@ impl<Tag: Semiring> DynVariable<Tag> {
     }
     result
   }
+
+  /// Like `complete`, but the variable keeps its elements
+  pub fn complete_and_keep(&self, ctx: &Tag::Context) -> DynRelation<Tag> {
+    let result = self.complete(ctx);
+    self.stable.borrow_mut().push(result.clone());
+    result
+  }
 }
@@ -1,6 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
+use scallop_compiler::{ast, ast2ram, error::CompileError};
+
 use super::dataflows::*;
+use super::error::RuntimeError;
 use super::interpreter::*;
 use super::tags::*;
 use super::utils::IdAllocator;
@@ -128,7 +131,7 @@ impl<Tag: Semiring> Iteration<Tag> {
     TupleType: FromType<Tup>,
     Tup: Into<DynTuple>,
   {
-    let variable = Variable::new();
+    let variable = Variable::with_name(name);
     let var_type = <TupleType as FromType<Tup>>::from_type();
     self.static_variables.insert(
       name.to_string(),
@@ -160,7 +163,7 @@ impl<Tag: Semiring> Iteration<Tag> {
     }
 
     // Then generate the variable
-    let variable = DynVariable::new();
+    let variable = DynVariable::with_name(name);
 
     // First add dynamic variables
     self
@@ -289,6 +292,38 @@ impl<Tag: Semiring> Iteration<Tag> {
     }
   }
 
+  /// The elements of a dynamic variable whose tuples match the atom; its
+  /// arguments are constants, variables or wildcards
+  pub fn query(&self, atom: &ast::Atom) -> Result<Vec<DynElement<Tag>>, RuntimeError> {
+    let var = self.get_dynamic_variable(&atom.node.predicate).ok_or(RuntimeError::UndefinedVariable)?;
+    let mut pattern = vec![];
+    for arg in &atom.node.args {
+      pattern.push(match arg {
+        ast::Argument::Constant(c) => {
+          let c = ast2ram::ast_const_to_ram_const(&c.node, &HashMap::new());
+          Some(Ok(self.compiler_context.ram_const_to_dyn_tuple(&c)))
+        }
+        ast::Argument::Variable(v) => Some(Err(&v.node.name)),
+        ast::Argument::Wildcard(_) => None,
+        arg => {
+          let e = CompileError::ExpressionInQuery { loc: *arg.location() };
+          return Err(RuntimeError::CompileError(e));
+        }
+      });
+    }
+    let matches = |columns: Vec<DynTuple>| {
+      let mut bindings = HashMap::new();
+      columns.len() == pattern.len()
+        && columns.into_iter().zip(&pattern).all(|(column, arg)| match arg {
+          Some(Ok(c)) => &column == c,
+          Some(Err(name)) => bindings.entry(*name).or_insert_with(|| column.clone()) == &column,
+          None => true,
+        })
+    };
+    let relation = var.complete_and_keep(&self.semiring_ctx);
+    Ok(relation.elements.into_iter().filter(|elem| matches(elem.tup.columns())).collect())
+  }
+
   fn process_rule_to_add(&mut self, rule: RuleToAdd) -> usize {
     // Create a rule id
     let rule_id = self.dynamic_rule_id_allocator.allocate();
@@ -335,13 +370,13 @@ impl<Tag: Semiring> Iteration<Tag> {
   pub fn insert<Tup>(&mut self, var: &Variable<Tup, Tag>, data: Vec<Tup>)
   where
     Tup: Tuple,
-    <Tag as Semiring>::Context: SemiringContext<Tag, Info = ()>,
+    <<Tag as Semiring>::Context as SemiringContext<Tag>>::Info: UnitInfo,
   {
     let data = data
       .into_iter()
       .map(|tup| Element {
         tup,
-        tag: self.semiring_ctx.base_tag(()),
+        tag: self.semiring_ctx.base_tag(UnitInfo::unit()),
       })
       .collect::<Vec<_>>();
     var.insert(&self.semiring_ctx, data)
@@ -366,7 +401,7 @@ impl<Tag: Semiring> Iteration<Tag> {
     var: &Variable<Tup, Tag>,
     data: Vec<(<Tag::Context as SemiringContext<Tag>>::Info, Tup)>,
   ) where
-    Tup: Tuple,
+    Tup: Tuple + Into<DynTuple>,
   {
     var.insert_with_context(&mut self.semiring_ctx, data)
   }
@@ -473,7 +508,7 @@ where
     var: &Variable<Tup, Tag>,
     data: Vec<(<ProbProofContext as SemiringContext<Tag>>::Info, Tup)>,
   ) where
-    Tup: Tuple,
+    Tup: Tuple + Into<DynTuple>,
   {
     let id = self.semiring_ctx.id_counter;
     self
@@ -483,3 +518,40 @@ where
     var.insert_with_context(&mut self.semiring_ctx, data);
   }
 }
+
+impl<Tag> Iteration<Tag>
+where
+  ProbProofContext: SemiringContext<Tag, Info = f64>,
+  Tag: Semiring<Context = ProbProofContext>,
+{
+  /// Load a whole program into dynamic variables and rules; facts without
+  /// probability hold for sure, like in compiled programs
+  pub fn add_program(&mut self, ast: ast::Program) -> Result<(), DynCompileError> {
+    let program = self.compiler_context.compile_program_from_ast(ast)?;
+    for (name, var_type) in program.vars_to_add {
+      self.dynamic_variable(&name, var_type);
+    }
+    for update in program.updates_to_add {
+      self.add_dynamic_update(update);
+    }
+    for (prob, name, tup) in program.facts_to_add {
+      let var = self.dynamic_variables[&name].1.clone();
+      match prob {
+        Some(prob) => var.insert_with_context(&mut self.semiring_ctx, vec![(prob, tup)]),
+        None => {
+          let elements = vec![DynElement { tup, tag: Tag::one(&self.semiring_ctx) }];
+          var.insert(&self.semiring_ctx, &DynDataflow::Vec(&elements));
+        }
+      }
+    }
+    for facts in program.disjunctions_to_add {
+      let id = self.semiring_ctx.id_counter;
+      self.semiring_ctx.disjunctions.push((id..id + facts.len()).collect());
+      for (prob, name, tup) in facts {
+        let var = self.dynamic_variables[&name].1.clone();
+        var.insert_with_context(&mut self.semiring_ctx, vec![(prob.unwrap_or(1.0), tup)]);
+      }
+    }
+    Ok(())
+  }
+}
@@ -9,6 +9,7 @@ mod iteration;
 mod program;
 mod relation;
 mod semiring;
+pub mod symbols;
 pub mod tags;
 mod tuple;
 mod utils;
@@ -22,8 +22,13 @@ where
     &self.iteration().semiring_ctx
   }
 
-  /// Add a dynamic variable with a given tuple type
+  /// Add a dynamic variable with a given tuple type; a tuple of columns is
+  /// laid out like the relations of compiled programs
   fn add_variable(&mut self, name: &str, tup_type: TupleType) -> Result<(), RuntimeError> {
+    let tup_type = match tup_type {
+      TupleType::Tuple(columns) => TupleType::from_columns(columns),
+      tup_type => tup_type,
+    };
     match self.iteration_mut().dynamic_variable(name, tup_type) {
       Some(_) => Ok(()),
       None => Err(RuntimeError::VariableAlreadyExisted),
@@ -110,7 +110,8 @@ where
 
     elements.push(elements1.next().unwrap());
     if elements.first() == elements2.peek() {
-      elements2.next();
+      let e2 = elements2.next().unwrap();
+      elements[0].tag = Tag::add(semiring_ctx, &elements[0].tag, &e2.tag);
     }
 
     for mut elem in elements1 {
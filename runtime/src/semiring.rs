@@ -1,5 +1,7 @@
 use std::fmt::Debug;
 
+use super::interpreter::DynTuple;
+
 pub trait Semiring: Clone + Sized + Debug + Send + Sync + 'static {
   type Context: SemiringContext<Self>;
 
@@ -12,6 +14,13 @@ pub trait Semiring: Clone + Sized + Debug + Send + Sync + 'static {
   fn mult(ctx: &Self::Context, t1: &Self, t2: &Self) -> Self;
 
   fn is_valid(&self, ctx: &Self::Context) -> bool;
+
+  /// The tag to propagate again for a derivation `late` of a tuple found
+  /// after the tuple became `stable`, if any; it is then added into the
+  /// stable tag. By default such derivations are dropped
+  fn late_derivation(_ctx: &Self::Context, _stable: &Self, _late: &Self) -> Option<Self> {
+    None
+  }
 }
 
 pub trait SemiringWithDifference: Semiring {
@@ -22,4 +31,30 @@ pub trait SemiringContext<Tag>: Default + Sync {
   type Info;
 
   fn base_tag(&mut self, info: Self::Info) -> Tag;
+
+  /// Create the base tag of the input tuple `tup` of `relation`; contexts
+  /// keeping track of the input facts override this to record the fact
+  fn base_tag_of_tuple<Tup>(&mut self, info: Self::Info, _relation: Option<&str>, _tup: &Tup) -> Tag
+  where
+    Tup: Clone + Into<DynTuple>,
+  {
+    self.base_tag(info)
+  }
+}
+
+/// The info of contexts whose base tags carry no information, such as `()`
+/// and pairs of such infos; tuples of these semirings can be inserted without
+/// any info
+pub trait UnitInfo {
+  fn unit() -> Self;
+}
+
+impl UnitInfo for () {
+  fn unit() -> Self {}
+}
+
+impl<A: UnitInfo, B: UnitInfo> UnitInfo for (A, B) {
+  fn unit() -> Self {
+    (A::unit(), B::unit())
+  }
 }
@@ -0,0 +1,8 @@
+pub use scallop_compiler::symbols::*;
+
+use crate::interpreter::DynTuple;
+
+/// The tuple as printed by `Debug`, but with its symbols printed by name
+pub fn format_tuple<Tup: Clone + Into<DynTuple>>(tup: &Tup) -> String {
+  format!("{:?}", tup.clone().into())
+}
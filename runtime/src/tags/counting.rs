@@ -0,0 +1,79 @@
+use crate::semiring::*;
+
+#[derive(Default, Clone)]
+pub struct CountingSemiringContext;
+
+/// The number of distinct derivations of a tuple
+///
+/// Derivations found after a tuple became stable are added into its count
+/// and propagated again. The count saturates at `MAX`, so that recursive
+/// programs with infinitely many derivations still reach a fix-point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DerivationCount<const MAX: usize> {
+  pub count: usize,
+}
+
+impl<const MAX: usize> DerivationCount<MAX> {
+  pub fn new(count: usize) -> Self {
+    Self { count: count.min(MAX) }
+  }
+
+  /// Whether the count has reached the saturation bound
+  pub fn is_saturated(&self) -> bool {
+    self.count >= MAX
+  }
+}
+
+impl<const MAX: usize> Semiring for DerivationCount<MAX> {
+  type Context = CountingSemiringContext;
+
+  #[inline(always)]
+  fn zero(_: &Self::Context) -> Self {
+    Self { count: 0 }
+  }
+
+  #[inline(always)]
+  fn one(_: &Self::Context) -> Self {
+    Self::new(1)
+  }
+
+  #[inline(always)]
+  fn add(_: &Self::Context, c1: &Self, c2: &Self) -> Self {
+    Self::new(c1.count.saturating_add(c2.count))
+  }
+
+  #[inline(always)]
+  fn mult(_: &Self::Context, c1: &Self, c2: &Self) -> Self {
+    Self::new(c1.count.saturating_mul(c2.count))
+  }
+
+  #[inline(always)]
+  fn is_valid(&self, _: &Self::Context) -> bool {
+    self.count > 0
+  }
+
+  fn late_derivation(_: &Self::Context, stable: &Self, late: &Self) -> Option<Self> {
+    let added = Self::new(stable.count.saturating_add(late.count)).count - stable.count;
+    if added > 0 {
+      Some(Self { count: added })
+    } else {
+      None
+    }
+  }
+}
+
+impl<const MAX: usize> SemiringWithDifference for DerivationCount<MAX> {
+  /// Monus on natural numbers: c1 - c2 = max(c1 - c2, 0)
+  fn minus(_: &Self::Context, c1: &Self, c2: &Self) -> Self {
+    Self::new(c1.count.saturating_sub(c2.count))
+  }
+}
+
+impl<const MAX: usize> SemiringContext<DerivationCount<MAX>> for CountingSemiringContext {
+  type Info = ();
+
+  #[inline(always)]
+  fn base_tag(&mut self, (): Self::Info) -> DerivationCount<MAX> {
+    DerivationCount::new(1)
+  }
+}
@@ -0,0 +1,115 @@
+use std::collections::*;
+
+use crate::interpreter::DynTuple;
+use crate::semiring::*;
+use super::*;
+
+/// Top-K proofs where K is chosen at runtime
+///
+/// This has the same semantics as `TopKProbProofs<K>`, but K is read from
+/// `ProbProofContext::top_k` instead of being fixed at compile time.
+#[derive(Clone, Debug, Default)]
+pub struct DynTopKProbProofs {
+  pub proofs: BinaryHeap<TopKProbProof>,
+}
+
+impl DynTopKProbProofs {
+  pub fn new() -> Self {
+    Self {
+      proofs: BinaryHeap::new(),
+    }
+  }
+
+  pub fn singleton(fact_id: usize, prob: f64) -> Self {
+    let mut facts = BTreeSet::new();
+    facts.insert(fact_id);
+    let mut proofs = BinaryHeap::new();
+    proofs.push(TopKProbProof { prob, facts, neg_facts: BTreeSet::new() });
+    Self { proofs }
+  }
+
+  pub fn insert(&mut self, proof: TopKProbProof, k: usize) {
+    insert_top_k(&mut self.proofs, proof, k)
+  }
+
+  /// The proofs with their probabilities, the most probable first
+  pub fn format(&self, ctx: &ProbProofContext) -> String {
+    format_top_k(&self.proofs, ctx)
+  }
+}
+
+impl Semiring for DynTopKProbProofs {
+  type Context = ProbProofContext;
+
+  fn zero(_: &Self::Context) -> Self {
+    Self::new()
+  }
+
+  fn one(ctx: &Self::Context) -> Self {
+    let mut proofs = BinaryHeap::new();
+    proofs.push(TopKProbProof {
+      prob: ctx.one_score(),
+      facts: BTreeSet::new(),
+      neg_facts: BTreeSet::new(),
+    });
+    Self { proofs }
+  }
+
+  fn add(ctx: &Self::Context, s1: &Self, s2: &Self) -> Self {
+    let mut proofs = s1.clone();
+    for p2 in &s2.proofs {
+      proofs.insert(p2.clone(), ctx.top_k);
+    }
+    proofs
+  }
+
+  fn mult(ctx: &Self::Context, s1: &Self, s2: &Self) -> Self {
+    let mut proofs = Self::new();
+    for p1 in &s1.proofs {
+      for p2 in &s2.proofs {
+        if let Some(proof) = p1.conjoin(p2, ctx) {
+          proofs.insert(proof, ctx.top_k);
+        }
+      }
+    }
+    proofs
+  }
+
+  fn is_valid(&self, _: &Self::Context) -> bool {
+    !self.proofs.is_empty()
+  }
+}
+
+impl SemiringWithDifference for DynTopKProbProofs {
+  /// s1 - s2 = s1 /\ ~s2, where ~s2 is kept to its top-k proofs while being
+  /// converted back into a disjunction of proofs
+  fn minus(ctx: &Self::Context, s1: &Self, s2: &Self) -> Self {
+    let neg = s2.proofs.iter().fold(Self::one(ctx), |acc, proof| {
+      let mut neg_proof = Self::new();
+      for lit in proof.negate(ctx) {
+        neg_proof.insert(lit, ctx.top_k);
+      }
+      Self::mult(ctx, &acc, &neg_proof)
+    });
+    Self::mult(ctx, s1, &neg)
+  }
+}
+
+impl SemiringContext<DynTopKProbProofs> for ProbProofContext {
+  type Info = f64;
+
+  fn base_tag(&mut self, prob: Self::Info) -> DynTopKProbProofs {
+    let id = self.id_counter;
+    self.id_counter += 1;
+    self.prob_table.insert(id, prob);
+    DynTopKProbProofs::singleton(id, self.score(prob))
+  }
+
+  fn base_tag_of_tuple<Tup>(&mut self, prob: Self::Info, relation: Option<&str>, tup: &Tup) -> DynTopKProbProofs
+  where
+    Tup: Clone + Into<DynTuple>,
+  {
+    self.record_input_fact(relation, tup);
+    <Self as SemiringContext<DynTopKProbProofs>>::base_tag(self, prob)
+  }
+}
@@ -1,14 +1,19 @@
 mod boolean;
+mod counting;
 #[cfg(feature = "torch")]
 mod diff_top_k_prob_proofs;
+mod dyn_top_k_prob_proofs;
 mod prob_proofs;
+mod product;
 mod top_k_prob_proofs;
 mod unit;
 mod utils;
 
 pub use boolean::*;
+pub use counting::*;
 #[cfg(feature = "torch")]
 pub use diff_top_k_prob_proofs::*;
+pub use dyn_top_k_prob_proofs::*;
 pub use prob_proofs::*;
 pub use top_k_prob_proofs::*;
 pub use unit::*;
@@ -1,18 +1,60 @@
 use std::collections::*;
 
+use crate::interpreter::DynTuple;
 use crate::semiring::*;
 use super::utils::*;
 
+/// A proof is a conjunction of input facts that must be true (`facts`) and
+/// input facts that must be false (`neg_facts`)
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ProbProof {
   pub facts: BTreeSet<usize>,
+  pub neg_facts: BTreeSet<usize>,
 }
 
 impl ProbProof {
+  pub fn empty() -> Self {
+    Self {
+      facts: BTreeSet::new(),
+      neg_facts: BTreeSet::new(),
+    }
+  }
+
   pub fn singleton(id: usize) -> Self {
     let mut facts = BTreeSet::new();
     facts.insert(id);
-    Self { facts }
+    Self { facts, neg_facts: BTreeSet::new() }
+  }
+
+  pub fn neg_singleton(id: usize) -> Self {
+    let mut neg_facts = BTreeSet::new();
+    neg_facts.insert(id);
+    Self { facts: BTreeSet::new(), neg_facts }
+  }
+
+  /// Conjoin two proofs; returns `None` if the result can never hold, i.e.
+  /// a fact is required to be both true and false, or two facts of the same
+  /// disjunction are required to be true
+  pub fn conjoin(&self, other: &Self, disjunctions: &Disjunctions) -> Option<Self> {
+    let facts = self.facts.union(&other.facts).cloned().collect::<BTreeSet<_>>();
+    let neg_facts = self.neg_facts.union(&other.neg_facts).cloned().collect::<BTreeSet<_>>();
+    if !facts.is_disjoint(&neg_facts) || has_conflict_in_disjunctions(disjunctions, &facts) {
+      None
+    } else {
+      Some(Self { facts, neg_facts })
+    }
+  }
+
+  /// The negation of a proof as a disjunction of single-literal proofs
+  ///
+  /// ~(a /\ ~b) = ~a \/ b
+  pub fn negate(&self) -> Vec<Self> {
+    self
+      .facts
+      .iter()
+      .map(|id| Self::neg_singleton(*id))
+      .chain(self.neg_facts.iter().map(|id| Self::singleton(*id)))
+      .collect()
   }
 }
 
@@ -28,6 +70,26 @@ impl ProbProofs {
     proofs.insert(proof);
     Self { proofs }
   }
+
+  /// The proofs with their facts as registered in the context, e.g.
+  /// `{edge(0, 1)} | {edge(0, 2), ~edge(2, 1)}`
+  pub fn format(&self, ctx: &ProbProofContext) -> String {
+    let proofs = self.proofs.iter().map(|proof| ctx.format_proof(&proof.facts, &proof.neg_facts));
+    proofs.collect::<Vec<_>>().join(" | ")
+  }
+
+  /// The negation of a set of proofs, turned back into a set of proofs
+  ///
+  /// ~(p1 \/ ... \/ pn) = ~p1 /\ ... /\ ~pn, where each ~pi is a disjunction
+  /// of single-literal proofs
+  pub fn negate(ctx: &ProbProofContext, t: &Self) -> Self {
+    t.proofs.iter().fold(Self::one(ctx), |acc, proof| {
+      let neg = Self {
+        proofs: proof.negate().into_iter().collect(),
+      };
+      Self::mult(ctx, &acc, &neg)
+    })
+  }
 }
 
 impl Semiring for ProbProofs {
@@ -41,9 +103,7 @@ impl Semiring for ProbProofs {
 
   fn one(_: &Self::Context) -> Self {
     let mut proofs = BTreeSet::new();
-    proofs.insert(ProbProof {
-      facts: BTreeSet::new(),
-    });
+    proofs.insert(ProbProof::empty());
     Self { proofs }
   }
 
@@ -57,9 +117,7 @@ impl Semiring for ProbProofs {
     let mut result = BTreeSet::new();
     for p1 in &t1.proofs {
       for p2 in &t2.proofs {
-        let facts = p1.facts.union(&p2.facts).cloned().collect();
-        if !has_conflict_in_disjunctions(&ctx.disjunctions, &facts) {
-          let proof = ProbProof { facts };
+        if let Some(proof) = p1.conjoin(p2, &ctx.disjunctions) {
           result.insert(proof);
         }
       }
@@ -72,8 +130,15 @@ impl Semiring for ProbProofs {
   }
 }
 
+impl SemiringWithDifference for ProbProofs {
+  /// t1 - t2 = t1 /\ ~t2
+  fn minus(ctx: &Self::Context, t1: &Self, t2: &Self) -> Self {
+    Self::mult(ctx, t1, &Self::negate(ctx, t2))
+  }
+}
+
 impl SemiringContext<ProbProofs> for ProbProofContext {
-  type Info = f32;
+  type Info = f64;
 
   fn base_tag(&mut self, prob: Self::Info) -> ProbProofs {
     let id = self.id_counter;
@@ -81,4 +146,12 @@ impl SemiringContext<ProbProofs> for ProbProofContext {
     self.prob_table.insert(id, prob);
     ProbProofs::singleton(id)
   }
+
+  fn base_tag_of_tuple<Tup>(&mut self, prob: Self::Info, relation: Option<&str>, tup: &Tup) -> ProbProofs
+  where
+    Tup: Clone + Into<DynTuple>,
+  {
+    self.record_input_fact(relation, tup);
+    <Self as SemiringContext<ProbProofs>>::base_tag(self, prob)
+  }
 }
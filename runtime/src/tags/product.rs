@@ -0,0 +1,84 @@
+use crate::interpreter::DynTuple;
+use crate::semiring::*;
+
+/// The product of two semirings, tracking both provenances at once
+///
+/// The context of `(A, B)` is the pair of the two contexts, and all the
+/// operations are performed component-wise. A tuple is kept only when it is
+/// valid in both semirings.
+impl<A, B> Semiring for (A, B)
+where
+  A: Semiring,
+  B: Semiring,
+{
+  type Context = (A::Context, B::Context);
+
+  fn zero((c1, c2): &Self::Context) -> Self {
+    (A::zero(c1), B::zero(c2))
+  }
+
+  fn one((c1, c2): &Self::Context) -> Self {
+    (A::one(c1), B::one(c2))
+  }
+
+  fn add((c1, c2): &Self::Context, (a1, b1): &Self, (a2, b2): &Self) -> Self {
+    (A::add(c1, a1, a2), B::add(c2, b1, b2))
+  }
+
+  fn mult((c1, c2): &Self::Context, (a1, b1): &Self, (a2, b2): &Self) -> Self {
+    (A::mult(c1, a1, a2), B::mult(c2, b1, b2))
+  }
+
+  fn is_valid(&self, (c1, c2): &Self::Context) -> bool {
+    self.0.is_valid(c1) && self.1.is_valid(c2)
+  }
+
+  /// A late derivation is propagated if either component propagates it; the
+  /// component dropping it propagates the sum of its stable and late tags
+  fn late_derivation((c1, c2): &Self::Context, (a1, b1): &Self, (a2, b2): &Self) -> Option<Self> {
+    match (A::late_derivation(c1, a1, a2), B::late_derivation(c2, b1, b2)) {
+      (None, None) => None,
+      (a, b) => Some((
+        a.unwrap_or_else(|| A::add(c1, a1, a2)),
+        b.unwrap_or_else(|| B::add(c2, b1, b2)),
+      )),
+    }
+  }
+}
+
+impl<A, B> SemiringWithDifference for (A, B)
+where
+  A: SemiringWithDifference,
+  B: SemiringWithDifference,
+{
+  fn minus((c1, c2): &Self::Context, (a1, b1): &Self, (a2, b2): &Self) -> Self {
+    (A::minus(c1, a1, a2), B::minus(c2, b1, b2))
+  }
+}
+
+/// The info of a product is the pair of the infos of both contexts; the base
+/// tag is created by fanning out each component to its own context
+impl<A, B> SemiringContext<(A, B)> for (A::Context, B::Context)
+where
+  A: Semiring,
+  B: Semiring,
+{
+  type Info = (
+    <A::Context as SemiringContext<A>>::Info,
+    <B::Context as SemiringContext<B>>::Info,
+  );
+
+  fn base_tag(&mut self, (i1, i2): Self::Info) -> (A, B) {
+    (self.0.base_tag(i1), self.1.base_tag(i2))
+  }
+
+  fn base_tag_of_tuple<Tup>(&mut self, (i1, i2): Self::Info, relation: Option<&str>, tup: &Tup) -> (A, B)
+  where
+    Tup: Clone + Into<DynTuple>,
+  {
+    (
+      self.0.base_tag_of_tuple(i1, relation, tup),
+      self.1.base_tag_of_tuple(i2, relation, tup),
+    )
+  }
+}
@@ -1,18 +1,54 @@
 use std::cmp::Ordering;
 use std::collections::*;
 
+use crate::interpreter::DynTuple;
 use crate::semiring::*;
 use super::*;
 
+/// A proof with its probability; `facts` must be true and `neg_facts` must
+/// be false
+///
+/// When the context is in log-space, `prob` stores the log-probability of
+/// the proof; in either case a higher value means a more probable proof.
 #[derive(Clone, Debug)]
 pub struct TopKProbProof {
-  pub prob: f32,
+  pub prob: f64,
   pub facts: BTreeSet<usize>,
+  pub neg_facts: BTreeSet<usize>,
+}
+
+impl TopKProbProof {
+  /// Conjoin two proofs; returns `None` if the conjunction is inconsistent
+  pub fn conjoin(&self, other: &Self, ctx: &ProbProofContext) -> Option<Self> {
+    let facts = self.facts.union(&other.facts).cloned().collect::<BTreeSet<_>>();
+    let neg_facts = self.neg_facts.union(&other.neg_facts).cloned().collect::<BTreeSet<_>>();
+    if !facts.is_disjoint(&neg_facts) || has_conflict_in_disjunctions(&ctx.disjunctions, &facts) {
+      None
+    } else {
+      let prob = ctx.proof_score(&facts, &neg_facts);
+      Some(Self { prob, facts, neg_facts })
+    }
+  }
+
+  /// The negation of a proof as a disjunction of single-literal proofs
+  pub fn negate(&self, ctx: &ProbProofContext) -> Vec<Self> {
+    let neg_lits = self.facts.iter().map(|id| {
+      let neg_facts = std::iter::once(*id).collect::<BTreeSet<_>>();
+      let prob = ctx.score(1.0 - ctx.prob_table[id]);
+      Self { prob, facts: BTreeSet::new(), neg_facts }
+    });
+    let pos_lits = self.neg_facts.iter().map(|id| {
+      let facts = std::iter::once(*id).collect::<BTreeSet<_>>();
+      let prob = ctx.score(ctx.prob_table[id]);
+      Self { prob, facts, neg_facts: BTreeSet::new() }
+    });
+    neg_lits.chain(pos_lits).collect()
+  }
 }
 
 impl PartialEq for TopKProbProof {
   fn eq(&self, other: &Self) -> bool {
-    self.facts == other.facts
+    self.facts == other.facts && self.neg_facts == other.neg_facts
   }
 }
 
@@ -30,7 +66,7 @@ impl Ord for TopKProbProof {
     // Reversed ordering for MIN-HEAP
     match other.prob.partial_cmp(&self.prob) {
       Some(ord) => ord,
-      _ => other.facts.cmp(&self.facts),
+      _ => (&other.facts, &other.neg_facts).cmp(&(&self.facts, &self.neg_facts)),
     }
   }
 }
@@ -40,6 +76,13 @@ pub struct TopKProbProofs<const K: usize> {
   pub proofs: BinaryHeap<TopKProbProof>,
 }
 
+/// Differentiable top-k proofs that do not depend on libtorch
+///
+/// The proofs are managed exactly as `TopKProbProofs`; gradients with respect
+/// to the input fact probabilities are obtained from `GradTopKProbProofsWMC`
+/// as plain `f64` values.
+pub type GradTopKProbProofs<const K: usize> = TopKProbProofs<K>;
+
 impl<const K: usize> TopKProbProofs<K> {
   pub fn new() -> Self {
     Self {
@@ -47,23 +90,42 @@ impl<const K: usize> TopKProbProofs<K> {
     }
   }
 
-  pub fn singleton(fact_id: usize, prob: f32) -> Self {
+  pub fn singleton(fact_id: usize, prob: f64) -> Self {
     let mut facts = BTreeSet::new();
     facts.insert(fact_id);
     let mut proofs = BinaryHeap::new();
-    proofs.push(TopKProbProof { prob, facts });
+    proofs.push(TopKProbProof { prob, facts, neg_facts: BTreeSet::new() });
     TopKProbProofs { proofs }
   }
 
   pub fn insert(&mut self, proof: TopKProbProof) {
-    if self.proofs.len() < K {
-      self.proofs.push(proof);
-    } else {
-      let p = self.proofs.peek().unwrap(); // len >= K, K > 0
-      if p.prob < proof.prob {
-        self.proofs.pop();
-        self.proofs.push(proof);
-      }
+    insert_top_k(&mut self.proofs, proof, K)
+  }
+
+  /// The proofs with their probabilities, the most probable first, e.g.
+  /// `0.5::{edge(0, 1)} | 0.25::{edge(0, 2), edge(2, 1)}`
+  pub fn format(&self, ctx: &ProbProofContext) -> String {
+    format_top_k(&self.proofs, ctx)
+  }
+}
+
+pub(crate) fn format_top_k(proofs: &BinaryHeap<TopKProbProof>, ctx: &ProbProofContext) -> String {
+  // The ordering is reversed, so sorting puts the most probable proof first
+  let proofs = proofs.clone().into_sorted_vec().into_iter().map(|proof| {
+    format!("{}::{}", ctx.score_to_prob(proof.prob), ctx.format_proof(&proof.facts, &proof.neg_facts))
+  });
+  proofs.collect::<Vec<_>>().join(" | ")
+}
+
+/// Insert a proof into a min-heap of at most `k` proofs; if the heap is full,
+/// the proof with the lowest probability is dropped
+pub(crate) fn insert_top_k(proofs: &mut BinaryHeap<TopKProbProof>, proof: TopKProbProof, k: usize) {
+  if proofs.len() < k {
+    proofs.push(proof);
+  } else if let Some(p) = proofs.peek() {
+    if p.prob < proof.prob {
+      proofs.pop();
+      proofs.push(proof);
     }
   }
 }
@@ -75,11 +137,12 @@ impl<const K: usize> Semiring for TopKProbProofs<K> {
     Self::new()
   }
 
-  fn one(_: &Self::Context) -> Self {
+  fn one(ctx: &Self::Context) -> Self {
     let mut proofs = BinaryHeap::new();
     proofs.push(TopKProbProof {
-      prob: 1.0,
+      prob: ctx.one_score(),
       facts: BTreeSet::new(),
+      neg_facts: BTreeSet::new(),
     });
     Self { proofs }
   }
@@ -96,15 +159,8 @@ impl<const K: usize> Semiring for TopKProbProofs<K> {
     let mut proofs = Self::new();
     for p1 in &s1.proofs {
       for p2 in &s2.proofs {
-        let facts = p1.facts.union(&p2.facts).cloned().collect::<BTreeSet<_>>();
-        if !has_conflict_in_disjunctions(&ctx.disjunctions, &facts) {
-          let prob = facts
-            .iter()
-            .fold(1.0, |p, fact_id| p * ctx.prob_table[fact_id]);
-          proofs.insert(TopKProbProof {
-            prob: prob,
-            facts: facts,
-          });
+        if let Some(proof) = p1.conjoin(p2, ctx) {
+          proofs.insert(proof);
         }
       }
     }
@@ -116,13 +172,36 @@ impl<const K: usize> Semiring for TopKProbProofs<K> {
   }
 }
 
+impl<const K: usize> SemiringWithDifference for TopKProbProofs<K> {
+  /// s1 - s2 = s1 /\ ~s2, where ~s2 is kept to its top-k proofs while being
+  /// converted back into a disjunction of proofs
+  fn minus(ctx: &Self::Context, s1: &Self, s2: &Self) -> Self {
+    let neg = s2.proofs.iter().fold(Self::one(ctx), |acc, proof| {
+      let mut neg_proof = Self::new();
+      for lit in proof.negate(ctx) {
+        neg_proof.insert(lit);
+      }
+      Self::mult(ctx, &acc, &neg_proof)
+    });
+    Self::mult(ctx, s1, &neg)
+  }
+}
+
 impl<const K: usize> SemiringContext<TopKProbProofs<K>> for ProbProofContext {
-  type Info = f32;
+  type Info = f64;
 
   fn base_tag(&mut self, prob: Self::Info) -> TopKProbProofs<K> {
     let id = self.id_counter;
     self.id_counter += 1;
     self.prob_table.insert(id, prob);
-    TopKProbProofs::singleton(id, prob)
+    TopKProbProofs::singleton(id, self.score(prob))
+  }
+
+  fn base_tag_of_tuple<Tup>(&mut self, prob: Self::Info, relation: Option<&str>, tup: &Tup) -> TopKProbProofs<K>
+  where
+    Tup: Clone + Into<DynTuple>,
+  {
+    self.record_input_fact(relation, tup);
+    <Self as SemiringContext<TopKProbProofs<K>>>::base_tag(self, prob)
   }
 }
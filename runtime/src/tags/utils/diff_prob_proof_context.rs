@@ -13,7 +13,7 @@ impl Default for DiffProbProofContext {
   fn default() -> Self {
     Self {
       id_counter: 0,
-      disjunctions: Vec::new(),
+      disjunctions: Disjunctions::new(),
       diff_prob_table: HashMap::new(),
     }
   }
@@ -3,66 +3,78 @@ pub use std::iter::FromIterator;
 
 pub type Disjunction = BTreeSet<usize>;
 
-/// Note: Assumes that the #facts > 2
-pub(crate) fn has_conflict_in_disjunction(disj: &Disjunction, facts: &BTreeSet<usize>) -> bool {
-  // Short cut 1
-  if disj.len() < 2 {
-    return false;
+/// The set of disjunctions in a context
+///
+/// Alongside the disjunctions, an index from fact id to the ids of the
+/// disjunctions containing the fact is maintained, so that checking a set of
+/// facts for conflicts costs time proportional to the number of facts only.
+#[derive(Clone, Debug, Default)]
+pub struct Disjunctions {
+  disjunctions: Vec<Disjunction>,
+  fact_to_disjunctions: HashMap<usize, Vec<usize>>,
+}
+
+impl Disjunctions {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Add a new disjunction and index all of its facts
+  pub fn push(&mut self, disj: Disjunction) {
+    let disj_id = self.disjunctions.len();
+    for fact_id in &disj {
+      self.fact_to_disjunctions.entry(*fact_id).or_default().push(disj_id);
+    }
+    self.disjunctions.push(disj);
+  }
+
+  pub fn len(&self) -> usize {
+    self.disjunctions.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.disjunctions.is_empty()
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &Disjunction> {
+    self.disjunctions.iter()
   }
 
-  // Short cut 2
-  let j_last = disj.last().unwrap(); // Note: disj.len >= 2
-  let j_first = disj.first().unwrap(); // Note: disj.len >= 2
-  let f_last = facts.last().unwrap(); // Note: facts.len >= 2
-  let f_first = facts.first().unwrap(); // Note: facts.len >= 2
-  if j_last < f_first || f_last < j_first {
-    return false;
+  /// Get the ids of the disjunctions containing the fact
+  pub fn disjunctions_of(&self, fact_id: &usize) -> &[usize] {
+    self.fact_to_disjunctions.get(fact_id).map_or(&[], |ids| &ids[..])
   }
 
-  // Start iteration
-  let mut j_iter = disj.iter();
-  let mut f_iter = facts.iter();
-  let mut has_same = false;
-  let mut j_curr = j_iter.next();
-  let mut f_curr = f_iter.next();
-  loop {
-    match (j_curr, f_curr) {
-      (Some(j_elem), Some(f_elem)) => {
-        if j_elem == f_elem {
-          if has_same {
-            return true;
-          } else {
-            has_same = true;
-          }
-          j_curr = j_iter.next();
-          f_curr = f_iter.next();
-        } else if j_elem < f_elem {
-          j_curr = j_iter.next();
-        } else {
-          f_curr = f_iter.next();
+  /// Check if two of the facts belong to the same disjunction
+  pub fn has_conflict(&self, facts: &BTreeSet<usize>) -> bool {
+    // Short hand
+    if facts.len() < 2 || self.disjunctions.is_empty() {
+      return false;
+    }
+
+    // Check whether any disjunction is visited twice
+    let mut visited = HashSet::new();
+    for fact_id in facts {
+      for disj_id in self.disjunctions_of(fact_id) {
+        if !visited.insert(*disj_id) {
+          return true;
         }
       }
-      _ => break,
     }
+    false
   }
-  false
 }
 
-pub type Disjunctions = Vec<Disjunction>;
+impl std::ops::Index<usize> for Disjunctions {
+  type Output = Disjunction;
 
-pub(crate) fn has_conflict_in_disjunctions(disjs: &Disjunctions, facts: &BTreeSet<usize>) -> bool {
-  // Short hand
-  if facts.len() < 2 {
-    return false;
+  fn index(&self, disj_id: usize) -> &Self::Output {
+    &self.disjunctions[disj_id]
   }
+}
 
-  // Check conflict for each disjunction
-  for disj in disjs {
-    if has_conflict_in_disjunction(disj, facts) {
-      return true;
-    }
-  }
-  false
+pub(crate) fn has_conflict_in_disjunctions(disjs: &Disjunctions, facts: &BTreeSet<usize>) -> bool {
+  disjs.has_conflict(facts)
 }
 
 #[cfg(test)]
@@ -73,16 +85,13 @@ mod test {
   use super::*;
 
   #[test]
-  fn test_disjunction_conflict_1() {
-    let disj = BTreeSet::from_iter(vec![1, 2, 3]);
-    let facts = BTreeSet::from_iter(vec![2, 3, 4]);
-    assert!(has_conflict_in_disjunction(&disj, &facts))
-  }
-
-  #[test]
-  fn test_disjunction_conflict_2() {
-    let disj = BTreeSet::from_iter(vec![1, 2, 3]);
-    let facts = BTreeSet::from_iter(vec![3, 4, 5]);
-    assert!(!has_conflict_in_disjunction(&disj, &facts))
+  fn test_disjunctions_conflict_indexed() {
+    let mut disjs = Disjunctions::new();
+    disjs.push(BTreeSet::from_iter(vec![0, 1, 2]));
+    disjs.push(BTreeSet::from_iter(vec![3, 4, 5]));
+    assert!(disjs.has_conflict(&BTreeSet::from_iter(vec![1, 2])));
+    assert!(disjs.has_conflict(&BTreeSet::from_iter(vec![0, 4, 5])));
+    assert!(!disjs.has_conflict(&BTreeSet::from_iter(vec![0, 3, 6])));
+    assert!(!disjs.has_conflict(&BTreeSet::from_iter(vec![2])));
   }
 }
@@ -1,9 +1,15 @@
 pub trait Probability {
-  fn prob(&self) -> f32;
+  fn prob(&self) -> f64;
 }
 
-impl Probability for f32 {
-  fn prob(&self) -> f32 {
+impl Probability for f64 {
+  fn prob(&self) -> f64 {
     self.clone()
   }
 }
+
+impl Probability for f32 {
+  fn prob(&self) -> f64 {
+    *self as f64
+  }
+}
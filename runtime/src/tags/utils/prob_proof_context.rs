@@ -1,20 +1,206 @@
 use std::collections::*;
 
 use super::disjunction::*;
+use crate::interpreter::DynTuple;
+
+/// An input fact registered in the context
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputFactInfo {
+  /// The relation of the fact; `None` if it was inserted into an unnamed
+  /// variable
+  pub relation: Option<String>,
+
+  /// The tuple of the fact
+  pub tuple: DynTuple,
+
+  /// A label given by the user
+  pub label: Option<String>,
+}
+
+impl std::fmt::Display for InputFactInfo {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match &self.relation {
+      Some(relation) => write!(f, "{}{}", relation, as_arguments(&self.tuple))?,
+      None => write!(f, "{:?}", self.tuple)?,
+    }
+    match &self.label {
+      Some(label) => write!(f, " [{}]", label),
+      None => Ok(()),
+    }
+  }
+}
+
+/// Format a tuple as an argument list, e.g. `(0,)` as `(0)` and `0` as `(0)`
+fn as_arguments(tuple: &DynTuple) -> String {
+  match tuple {
+    DynTuple::Tuple(elems) => {
+      let args = elems.iter().map(|elem| format!("{:?}", elem)).collect::<Vec<_>>();
+      format!("({})", args.join(", "))
+    }
+    elem => format!("({:?})", elem),
+  }
+}
 
 #[derive(Clone, Debug)]
 pub struct ProbProofContext {
   pub id_counter: usize,
   pub disjunctions: Disjunctions,
-  pub prob_table: HashMap<usize, f32>,
+  pub prob_table: HashMap<usize, f64>,
+
+  /// The registry of the input facts inserted along with their tuple
+  ///
+  /// Fact ids are allocated in insertion order from 0, so running the same
+  /// program on the same inputs gives every fact the same id.
+  pub input_facts: BTreeMap<usize, InputFactInfo>,
+
+  /// The ids of the registered facts by relation and tuple, and by label
+  fact_ids: HashMap<(Option<String>, DynTuple), usize>,
+  label_ids: HashMap<String, usize>,
+
+  /// The number of proofs kept by `DynTopKProbProofs`
+  pub top_k: usize,
+
+  /// Whether the proofs are scored with log-probabilities instead of
+  /// probabilities, so that long proofs do not underflow to 0
+  pub log_space: bool,
 }
 
 impl Default for ProbProofContext {
   fn default() -> Self {
     Self {
       id_counter: 0,
-      disjunctions: Vec::new(),
+      disjunctions: Disjunctions::new(),
       prob_table: HashMap::new(),
+      input_facts: BTreeMap::new(),
+      fact_ids: HashMap::new(),
+      label_ids: HashMap::new(),
+      top_k: 3,
+      log_space: false,
+    }
+  }
+}
+
+impl ProbProofContext {
+  /// Create a new context where `DynTopKProbProofs` keeps `top_k` proofs
+  pub fn with_top_k(top_k: usize) -> Self {
+    Self {
+      top_k,
+      ..Self::default()
+    }
+  }
+
+  /// Create a new context scoring proofs with log-probabilities
+  pub fn with_log_space() -> Self {
+    Self {
+      log_space: true,
+      ..Self::default()
+    }
+  }
+
+  /// Record the relation and the tuple of the next fact id
+  pub(crate) fn record_input_fact<Tup>(&mut self, relation: Option<&str>, tup: &Tup)
+  where
+    Tup: Clone + Into<DynTuple>,
+  {
+    let info = InputFactInfo {
+      relation: relation.map(str::to_string),
+      tuple: tup.clone().into(),
+      label: None,
+    };
+    let key = (info.relation.clone(), info.tuple.clone());
+    self.fact_ids.entry(key).or_insert(self.id_counter);
+    self.input_facts.insert(self.id_counter, info);
+  }
+
+  pub fn input_fact(&self, id: usize) -> Option<&InputFactInfo> {
+    self.input_facts.get(&id)
+  }
+
+  /// The id of the input fact of the relation with the tuple, if any
+  pub fn find_input_fact<Tup: Into<DynTuple>>(&self, relation: &str, tup: Tup) -> Option<usize> {
+    let key = (Some(relation.to_string()), tup.into());
+    self.fact_ids.get(&key).cloned()
+  }
+
+  /// The ids and the facts of a relation, ordered by id
+  pub fn input_facts_of<'a>(&'a self, relation: &'a str) -> impl Iterator<Item = (usize, &'a InputFactInfo)> {
+    self
+      .input_facts
+      .iter()
+      .filter(move |(_, info)| info.relation.as_deref() == Some(relation))
+      .map(|(id, info)| (*id, info))
+  }
+
+  /// Label a registered input fact; returns false if there is no such fact
+  pub fn set_label(&mut self, id: usize, label: &str) -> bool {
+    match self.input_facts.get_mut(&id) {
+      Some(info) => {
+        if let Some(old_label) = info.label.replace(label.to_string()) {
+          if self.label_ids.get(&old_label) == Some(&id) {
+            self.label_ids.remove(&old_label);
+          }
+        }
+        self.label_ids.entry(label.to_string()).or_insert(id);
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// The id of the input fact with the label, if any
+  pub fn find_label(&self, label: &str) -> Option<usize> {
+    self.label_ids.get(label).cloned()
+  }
+
+  /// The fact with the id, e.g. `edge(0, 1)`; facts that are not registered
+  /// are printed as their id, e.g. `#3`
+  pub fn format_fact(&self, id: usize) -> String {
+    match self.input_facts.get(&id) {
+      Some(info) => info.to_string(),
+      None => format!("#{}", id),
+    }
+  }
+
+  /// A proof as the set of its facts, the negated ones prefixed by `~`, e.g.
+  /// `{edge(0, 1), ~edge(1, 2)}`
+  pub fn format_proof(&self, facts: &BTreeSet<usize>, neg_facts: &BTreeSet<usize>) -> String {
+    let pos = facts.iter().map(|id| self.format_fact(*id));
+    let neg = neg_facts.iter().map(|id| format!("~{}", self.format_fact(*id)));
+    format!("{{{}}}", pos.chain(neg).collect::<Vec<_>>().join(", "))
+  }
+
+  /// Turn a probability into a score; the score is the log-probability when
+  /// `log_space` is enabled and the probability itself otherwise
+  pub fn score(&self, prob: f64) -> f64 {
+    if self.log_space {
+      prob.ln()
+    } else {
+      prob
+    }
+  }
+
+  /// Turn a score back into a probability
+  pub fn score_to_prob(&self, score: f64) -> f64 {
+    if self.log_space {
+      score.exp()
+    } else {
+      score
+    }
+  }
+
+  /// The score of the empty proof, i.e. of probability 1
+  pub fn one_score(&self) -> f64 {
+    self.score(1.0)
+  }
+
+  /// The score of a proof where `facts` are true and `neg_facts` are false
+  pub fn proof_score(&self, facts: &BTreeSet<usize>, neg_facts: &BTreeSet<usize>) -> f64 {
+    let pos = facts.iter().map(|id| self.prob_table[id]);
+    let neg = neg_facts.iter().map(|id| 1.0 - self.prob_table[id]);
+    if self.log_space {
+      pos.chain(neg).map(f64::ln).sum()
+    } else {
+      pos.chain(neg).product()
     }
   }
 }
@@ -2,62 +2,52 @@ use std::fmt::Debug;
 use std::rc::Rc;
 use std::sync::Arc;
 
+use scallop_compiler::ast2ram;
+
 pub trait Tuple: Sized + Ord + Clone + Debug + Send + Sync {}
 
 impl<Tup> Tuple for Tup where Tup: Sized + Ord + Clone + Debug + Send + Sync {}
 
-#[derive(Debug, Clone, Copy)]
+/// The path of indices to an element nested in a tuple, of any depth
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TupleAccessor {
-  size: u8,
-  indices: [u8; 3],
+  indices: Vec<usize>,
 }
 
 impl TupleAccessor {
   pub fn len(&self) -> usize {
-    self.size as usize
+    self.indices.len()
   }
 
   pub fn is_root(&self) -> bool {
-    self.size == 0
+    self.indices.is_empty()
   }
 
   pub fn first_level(&self) -> usize {
-    self.indices[0] as usize
+    self.indices[0]
+  }
+
+  pub fn indices(&self) -> &[usize] {
+    &self.indices
   }
 
   pub fn root() -> Self {
-    Self {
-      size: 0,
-      indices: [0; 3],
-    }
+    Self { indices: vec![] }
   }
 
   pub fn top(index: usize) -> Self {
-    Self {
-      size: 1,
-      indices: [index as u8, 0, 0],
-    }
+    Self { indices: vec![index] }
   }
 
-  pub fn from_indices(indices: &[u8]) -> Self {
-    let mut new_indices = [0; 3];
-    for index in 0..indices.len().min(3) {
-      new_indices[index] = indices[index];
-    }
+  pub fn from_indices(indices: &[usize]) -> Self {
     Self {
-      size: indices.len() as u8,
-      indices: new_indices,
+      indices: indices.to_vec(),
     }
   }
 
   pub fn indent(&self) -> Self {
-    let mut new_indices = [0; 3];
-    for index in 1..self.size {
-      new_indices[index as usize - 1] = self.indices[index as usize];
-    }
     Self {
-      size: self.size - 1,
-      indices: new_indices,
+      indices: self.indices[1..].to_vec(),
     }
   }
 }
@@ -73,14 +63,37 @@ pub enum TupleType {
 
 impl TupleType {
   pub fn type_of(&self, acc: &TupleAccessor) -> &TupleType {
-    if acc.is_root() {
-      self
+    acc.indices().iter().fold(self, |ty, i| match ty {
+      Self::Tuple(types) => &types[*i],
+      _ => panic!("Not possible"),
+    })
+  }
+
+  /// The type of the tuples of a relation with the column types, laid out
+  /// like the compiled relations
+  pub fn from_columns(columns: Vec<TupleType>) -> Self {
+    if columns.is_empty() {
+      Self::Tuple(vec![])
     } else {
-      match self {
-        Self::Tuple(types) => types[acc.first_level()].type_of(&acc.indent()),
-        _ => panic!("Not possible"),
+      ast2ram::nest_columns(columns, &Self::Tuple)
+    }
+  }
+
+  /// The column types, if the type has the layout of the relations
+  pub fn columns(&self) -> Option<Vec<TupleType>> {
+    fn flatten(ty: &TupleType, columns: &mut Vec<TupleType>) {
+      match ty {
+        TupleType::Tuple(types) => types.iter().for_each(|ty| flatten(ty, columns)),
+        ty => columns.push(ty.clone()),
       }
     }
+    let mut columns = vec![];
+    flatten(self, &mut columns);
+    if Self::from_columns(columns.clone()) == *self {
+      Some(columns)
+    } else {
+      None
+    }
   }
 }
 
@@ -136,98 +149,28 @@ impl FromType<()> for TupleType {
   }
 }
 
-impl<A> FromType<(A,)> for TupleType
-where
-  Self: FromType<A>,
-{
-  fn from_type() -> Self {
-    Self::Tuple(vec![
-      <Self as FromType<A>>::from_type()
-    ])
-  }
-}
-
-impl<A, B> FromType<(A, B)> for TupleType
-where
-  Self: FromType<A>,
-  Self: FromType<B>,
-{
-  fn from_type() -> Self {
-    Self::Tuple(vec![
-      <Self as FromType<A>>::from_type(),
-      <Self as FromType<B>>::from_type(),
-    ])
-  }
-}
-
-impl<A, B, C> FromType<(A, B, C)> for TupleType
-where
-  Self: FromType<A>,
-  Self: FromType<B>,
-  Self: FromType<C>,
-{
-  fn from_type() -> Self {
-    Self::Tuple(vec![
-      <Self as FromType<A>>::from_type(),
-      <Self as FromType<B>>::from_type(),
-      <Self as FromType<C>>::from_type(),
-    ])
-  }
-}
-
-impl<A, B, C, D> FromType<(A, B, C, D)> for TupleType
-where
-  Self: FromType<A>,
-  Self: FromType<B>,
-  Self: FromType<C>,
-  Self: FromType<D>,
-{
-  fn from_type() -> Self {
-    Self::Tuple(vec![
-      <Self as FromType<A>>::from_type(),
-      <Self as FromType<B>>::from_type(),
-      <Self as FromType<C>>::from_type(),
-      <Self as FromType<D>>::from_type(),
-    ])
-  }
-}
-
-impl<A, B, C, D, E> FromType<(A, B, C, D, E)> for TupleType
-where
-  Self: FromType<A>,
-  Self: FromType<B>,
-  Self: FromType<C>,
-  Self: FromType<D>,
-  Self: FromType<E>,
-{
-  fn from_type() -> Self {
-    Self::Tuple(vec![
-      <Self as FromType<A>>::from_type(),
-      <Self as FromType<B>>::from_type(),
-      <Self as FromType<C>>::from_type(),
-      <Self as FromType<D>>::from_type(),
-      <Self as FromType<E>>::from_type(),
-    ])
+macro_rules! impl_from_type_for_tuple {
+  ( $($id:ident,)* ) => {
+    impl<$($id,)*> FromType<($($id,)*)> for TupleType
+    where
+      $(Self: FromType<$id>,)*
+    {
+      fn from_type() -> Self {
+        Self::Tuple(vec![$(<Self as FromType<$id>>::from_type()),*])
+      }
+    }
   }
 }
 
-impl<A, B, C, D, E, F> FromType<(A, B, C, D, E, F)> for TupleType
-where
-  Self: FromType<A>,
-  Self: FromType<B>,
-  Self: FromType<C>,
-  Self: FromType<D>,
-  Self: FromType<E>,
-  Self: FromType<F>,
-{
-  fn from_type() -> Self {
-    Self::Tuple(vec![
-      <Self as FromType<A>>::from_type(),
-      <Self as FromType<B>>::from_type(),
-      <Self as FromType<C>>::from_type(),
-      <Self as FromType<D>>::from_type(),
-      <Self as FromType<E>>::from_type(),
-      <Self as FromType<F>>::from_type(),
-    ])
-  }
-}
+impl_from_type_for_tuple!(A,);
+impl_from_type_for_tuple!(A, B,);
+impl_from_type_for_tuple!(A, B, C,);
+impl_from_type_for_tuple!(A, B, C, D,);
+impl_from_type_for_tuple!(A, B, C, D, E,);
+impl_from_type_for_tuple!(A, B, C, D, E, F,);
+impl_from_type_for_tuple!(A, B, C, D, E, F, G,);
+impl_from_type_for_tuple!(A, B, C, D, E, F, G, H,);
+impl_from_type_for_tuple!(A, B, C, D, E, F, G, H, I,);
+impl_from_type_for_tuple!(A, B, C, D, E, F, G, H, I, J,);
+impl_from_type_for_tuple!(A, B, C, D, E, F, G, H, I, J, K,);
+impl_from_type_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L,);
@@ -1,6 +1,7 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use super::interpreter::DynTuple;
 use super::*;
 
 pub trait VariableTrait<Tag>
@@ -15,6 +16,9 @@ pub struct Variable<Tup: Tuple, Tag: Semiring = ()> {
   pub stable: Rc<RefCell<Vec<Relation<Tup, Tag>>>>,
   pub recent: Rc<RefCell<Relation<Tup, Tag>>>,
   to_add: Rc<RefCell<Vec<Relation<Tup, Tag>>>>,
+
+  /// The relation name, recorded along with the input facts
+  name: Option<Rc<str>>,
 }
 
 impl<Tup, Tag> Variable<Tup, Tag>
@@ -27,9 +31,22 @@ where
       stable: Rc::new(RefCell::new(Vec::new())),
       recent: Rc::new(RefCell::new(Relation::empty())),
       to_add: Rc::new(RefCell::new(Vec::new())),
+      name: None,
+    }
+  }
+
+  /// Create a new variable for the relation `name`
+  pub fn with_name(name: &str) -> Self {
+    Self {
+      name: Some(name.into()),
+      ..Self::new()
     }
   }
 
+  pub fn name(&self) -> Option<&str> {
+    self.name.as_deref()
+  }
+
   pub fn insert_with_context(
     &self,
     semiring_ctx: &mut Tag::Context,
@@ -37,12 +54,14 @@ where
       <<Tag as Semiring>::Context as SemiringContext<Tag>>::Info,
       Tup,
     )>,
-  ) {
+  ) where
+    Tup: Into<DynTuple>,
+  {
     let elements = data
       .into_iter()
       .map(|(info, tup)| Element {
+        tag: semiring_ctx.base_tag_of_tuple(info, self.name(), &tup),
         tup,
-        tag: semiring_ctx.base_tag(info),
       })
       .collect::<Vec<_>>();
     self.insert(semiring_ctx, elements);
@@ -122,23 +141,31 @@ where
         to_add = to_add.merge(to_add_more, semiring_ctx);
       }
 
-      // Make sure that there is no duplicates
+      // Make sure that there is no duplicates; the late derivations of stable
+      // tuples are kept if the semiring propagates them
       for batch in self.stable.borrow().iter() {
         let mut slice = &batch[..];
         // Only gallop if the slice is relatively large.
-        if slice.len() > 4 * to_add.elements.len() {
-          to_add.elements.retain(|x| {
+        let gallop = slice.len() > 4 * to_add.elements.len();
+        to_add.elements.retain_mut(|x| {
+          if gallop {
             slice = utils::gallop::gallop(slice, |y| y < x);
-            slice.is_empty() || &slice[0] != x
-          });
-        } else {
-          to_add.elements.retain(|x| {
+          } else {
             while !slice.is_empty() && &slice[0] < x {
               slice = &slice[1..];
             }
-            slice.is_empty() || &slice[0] != x
-          });
-        }
+          }
+          if slice.is_empty() || &slice[0] != x {
+            return true;
+          }
+          match Tag::late_derivation(semiring_ctx, &slice[0].tag, &x.tag) {
+            Some(tag) => {
+              x.tag = tag;
+              true
+            }
+            None => false,
+          }
+        });
       }
 
       *self.recent.borrow_mut() = to_add;
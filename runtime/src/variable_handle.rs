@@ -1,5 +1,6 @@
 use rayon::prelude::*;
 
+use super::interpreter::DynTuple;
 use super::*;
 use super::tags::*;
 use super::wmc::*;
@@ -30,7 +31,7 @@ where
 
   pub fn insert(&self, data: Vec<Tup>)
   where
-    <Tag as Semiring>::Context: SemiringContext<Tag, Info = ()>,
+    <<Tag as Semiring>::Context as SemiringContext<Tag>>::Info: UnitInfo,
   {
     let data = data
       .into_iter()
@@ -65,10 +66,12 @@ where
     &mut self,
     tag_info: <Tag::Context as SemiringContext<Tag>>::Info,
     tup: Tup,
-  ) {
+  ) where
+    Tup: Into<DynTuple>,
+  {
     let elem = Element {
+      tag: self.semiring_ctx.base_tag_of_tuple(tag_info, self.var.name(), &tup),
       tup,
-      tag: self.semiring_ctx.base_tag(tag_info),
     };
     self.temp_storage.push(elem)
   }
@@ -76,7 +79,9 @@ where
   pub fn insert_with_tag_info(
     &mut self,
     data: Vec<(<Tag::Context as SemiringContext<Tag>>::Info, Tup)>,
-  ) {
+  ) where
+    Tup: Into<DynTuple>,
+  {
     self.var.insert_with_context(&mut self.semiring_ctx, data)
   }
 
@@ -113,6 +118,23 @@ where
     let elements = self.var.complete(self.semiring_ctx).elements;
     par_process_elements(elements, &*self.semiring_ctx, wmc)
   }
+
+  /// Like `complete_with_wmc`, but the tags of all the tuples are counted in
+  /// one batch; SDD-based counters compile them into a single shared arena
+  pub fn complete_with_batched_wmc<Wmc>(
+    self,
+    wmc: &Wmc,
+  ) -> Vec<(Tup, Tag, <Wmc as WeightedModelCounter>::Output)>
+  where
+    Wmc: WeightedModelCounter<Tag = Tag>,
+  {
+    let elements = self.var.complete(self.semiring_ctx).elements;
+    let tags = elements.iter().map(|elem| &elem.tag).collect::<Vec<_>>();
+    let wmc_results = wmc.wmc_batch(self.semiring_ctx, &tags);
+    elements.into_iter().zip(wmc_results).map(|(elem, wmc_result)| {
+      (elem.tup, elem.tag, wmc_result)
+    }).collect::<Vec<_>>()
+  }
 }
 
 fn par_process_elements<Tup, Tag, Wmc>(
@@ -133,7 +155,7 @@ where
 
 impl<'a, Tup, Tag> VariableHandle<'a, Tup, Tag>
 where
-  Tup: Tuple,
+  Tup: Tuple + Into<DynTuple>,
   Tag: Semiring,
   ProbProofContext: SemiringContext<Tag>,
   Tag: Semiring<Context = ProbProofContext>,
@@ -147,14 +169,32 @@ where
       .semiring_ctx
       .disjunctions
       .push((id..id + data.len()).collect());
-    self.var.insert_with_context(&mut self.semiring_ctx, data);
+    self.var.insert_with_context(self.semiring_ctx, data);
   }
 }
 
-#[cfg(feature = "torch")]
 impl<'a, Tup, Tag> VariableHandle<'a, Tup, Tag>
 where
   Tup: Tuple,
+  Tag: Semiring<Context = ProbProofContext> + ToBooleanFormula,
+{
+  /// Complete the variable along with the conditional probability of each
+  /// tuple given `evidence`; the probability is `None` if the evidence is
+  /// impossible
+  pub fn complete_with_evidence(self, evidence: &Evidence) -> Vec<(Tup, Tag, Option<f64>)> {
+    let elements = self.var.complete(self.semiring_ctx).elements;
+    let tags = elements.iter().map(|elem| &elem.tag).collect::<Vec<_>>();
+    let probs = conditional_prob_batch(self.semiring_ctx, &tags, evidence);
+    elements.into_iter().zip(probs).map(|(elem, prob)| {
+      (elem.tup, elem.tag, prob)
+    }).collect::<Vec<_>>()
+  }
+}
+
+#[cfg(feature = "torch")]
+impl<'a, Tup, Tag> VariableHandle<'a, Tup, Tag>
+where
+  Tup: Tuple + Into<DynTuple>,
   Tag: Semiring,
   DiffProbProofContext: SemiringContext<Tag>,
   Tag: Semiring<Context = DiffProbProofContext>,
@@ -0,0 +1,6 @@
+use sdd::BooleanFormula;
+
+/// Tags that can be turned into a boolean formula over input fact ids
+pub trait ToBooleanFormula {
+  fn to_boolean_formula(&self) -> BooleanFormula;
+}
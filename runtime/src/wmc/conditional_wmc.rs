@@ -0,0 +1,90 @@
+use sdd::BooleanFormula;
+
+use super::boolean_formula::*;
+use super::proofs_wmc::*;
+use crate::tags::ProbProofContext;
+
+/// Evidence on tuples, given by the tags of tuples observed to be true or
+/// false
+///
+/// The tags may come from several relations, as long as they share the same
+/// context.
+#[derive(Clone, Debug)]
+pub struct Evidence {
+  formula: BooleanFormula,
+  num_observations: usize,
+}
+
+impl Default for Evidence {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Evidence {
+  pub fn new() -> Self {
+    Self {
+      formula: BooleanFormula::True,
+      num_observations: 0,
+    }
+  }
+
+  /// Observe that the tuple tagged with `tag` holds (`holds = true`) or does
+  /// not hold (`holds = false`)
+  pub fn observe<Tag: ToBooleanFormula>(&mut self, tag: &Tag, holds: bool) {
+    let form = tag.to_boolean_formula();
+    let literal = if holds { form } else { !form };
+    let formula = std::mem::replace(&mut self.formula, BooleanFormula::True);
+    self.formula = formula & literal;
+    self.num_observations += 1;
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.num_observations == 0
+  }
+
+  pub fn formula(&self) -> &BooleanFormula {
+    &self.formula
+  }
+}
+
+/// Conditional probability of the tuple tagged with `query` given `evidence`
+///
+/// The query is conjoined with the evidence in the same SDD as the evidence
+/// itself; the result is `P(query /\ evidence) / P(evidence)`, or the
+/// difference of the two in log-space. Returns `None` when the evidence has
+/// probability 0.
+pub fn conditional_prob<Tag: ToBooleanFormula>(
+  ctx: &ProbProofContext,
+  query: &Tag,
+  evidence: &Evidence,
+) -> Option<f64> {
+  conditional_prob_batch(ctx, &[query], evidence).pop().unwrap()
+}
+
+/// Conditional probabilities of many queries given the same evidence, all
+/// compiled into one SDD arena
+pub fn conditional_prob_batch<Tag: ToBooleanFormula>(
+  ctx: &ProbProofContext,
+  queries: &[&Tag],
+  evidence: &Evidence,
+) -> Vec<Option<f64>> {
+  let forms = std::iter::once(evidence.formula.clone())
+    .chain(queries.iter().map(|query| query.to_boolean_formula() & evidence.formula.clone()))
+    .collect::<Vec<_>>();
+  let scores = batched_prob_wmc(ctx, &forms);
+  let evidence_score = scores[0];
+  if evidence_score == ctx.score(0.0) {
+    return vec![None; queries.len()];
+  }
+  scores[1..]
+    .iter()
+    .map(|score| {
+      if ctx.log_space {
+        Some(score - evidence_score)
+      } else {
+        Some(score / evidence_score)
+      }
+    })
+    .collect()
+}
@@ -0,0 +1,29 @@
+use super::top_k_proofs_wmc::*;
+use super::WeightedModelCounter;
+use crate::semiring::*;
+use crate::tags::DynTopKProbProofs;
+
+#[derive(Debug, Clone)]
+pub struct DynTopKProbProofsWMC;
+
+impl WeightedModelCounter for DynTopKProbProofsWMC {
+  type Tag = DynTopKProbProofs;
+
+  type Output = f64;
+
+  fn wmc(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tag: &Self::Tag,
+  ) -> Self::Output {
+    top_k_prob_proofs_wmc(ctx, &tag.proofs)
+  }
+
+  fn wmc_batch(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tags: &[&Self::Tag],
+  ) -> Vec<Self::Output> {
+    batched_top_k_prob_proofs_wmc(ctx, tags.iter().map(|tag| &tag.proofs))
+  }
+}
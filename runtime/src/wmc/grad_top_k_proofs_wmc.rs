@@ -0,0 +1,62 @@
+use std::collections::*;
+use sdd::{SDDBuilder, SDDBuilderConfig};
+
+use crate::semiring::*;
+use crate::tags::*;
+use super::top_k_proofs_wmc::top_k_prob_proofs_to_boolean_formula;
+use super::{WeightedModelCounter, DiffProbabilitySemiring2};
+
+/// Weighted model counter returning the probability of a tag along with its
+/// sparse gradient, i.e. the derivative with respect to the probability of
+/// each input fact that occurs in the tag, as `(fact_id, derivative)` pairs
+#[derive(Debug, Clone)]
+pub struct GradTopKProbProofsWMC<const K: usize>;
+
+impl<const K: usize> WeightedModelCounter for GradTopKProbProofsWMC<K> {
+  type Tag = GradTopKProbProofs<K>;
+
+  type Output = (f64, Vec<(usize, f64)>);
+
+  fn wmc(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tag: &Self::Tag,
+  ) -> Self::Output {
+    // Shortcut for false tag
+    if tag.proofs.is_empty() {
+      return (0.0, vec![]);
+    }
+
+    // Shortcut for always true tag; the gradient is zero
+    if tag.proofs.iter().any(|p| p.facts.is_empty() && p.neg_facts.is_empty()) {
+      return (1.0, vec![]);
+    }
+
+    let form = top_k_prob_proofs_to_boolean_formula(&tag.proofs);
+    let vars = form.collect_vars();
+
+    // Construct an SDD from the boolean formula
+    let config = SDDBuilderConfig::with_formula(&form);
+    let sdd = SDDBuilder::with_config(config).build(&form);
+
+    // Each variable is assigned a one-hot dual number; the sparse index of a
+    // variable is its position in `vars`
+    let var_assign = vars
+      .iter()
+      .enumerate()
+      .map(|(sparse_id, var_id)| {
+        let prob = ctx.prob_table[var_id];
+        (*var_id, DualNumber2::new(prob, sparse_id, vars.len()))
+      })
+      .collect::<HashMap<usize, DualNumber2>>();
+    let result = sdd.eval_t(&var_assign, &DiffProbabilitySemiring2);
+
+    // Map the sparse gradient back to fact ids
+    let grad = vars
+      .iter()
+      .enumerate()
+      .map(|(sparse_id, var_id)| (*var_id, result.ith_grad(&sparse_id)))
+      .collect::<Vec<_>>();
+    (result.prob(), grad)
+  }
+}
@@ -0,0 +1,61 @@
+use std::collections::*;
+
+use sdd::{SDDBuilder, SDDBuilderConfig};
+pub use sdd::SDDBudget;
+
+use super::boolean_formula::*;
+use super::prob_semiring::*;
+use super::sampling_wmc::*;
+use super::WeightedModelCounter;
+use crate::semiring::*;
+use crate::tags::ProbProofContext;
+
+/// Weighted model counter compiling the tag into an SDD within a budget, and
+/// falling back to sampling when the budget is exceeded
+#[derive(Clone, Debug)]
+pub struct HybridWMC<Tag> {
+  pub budget: SDDBudget,
+  pub sampling: SamplingWMC<Tag>,
+}
+
+impl<Tag> HybridWMC<Tag> {
+  pub fn new(budget: SDDBudget, sampling: SamplingWMC<Tag>) -> Self {
+    Self { budget, sampling }
+  }
+}
+
+impl<Tag> WeightedModelCounter for HybridWMC<Tag>
+where
+  Tag: Semiring<Context = ProbProofContext> + ToBooleanFormula,
+{
+  type Tag = Tag;
+
+  type Output = ProbEstimate;
+
+  fn wmc(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tag: &Self::Tag,
+  ) -> Self::Output {
+    let form = tag.to_boolean_formula();
+    let vars = form.collect_vars();
+    if vars.is_empty() {
+      return self.sampling.estimate(ctx, &form).to_score(ctx);
+    }
+
+    // Try the exact compilation first; both paths estimate probabilities,
+    // turned into scores in the end
+    let config = SDDBuilderConfig::with_formula(&form).with_budget(self.budget);
+    let estimate = match SDDBuilder::with_config(config).try_build(&form) {
+      Some(sdd) => {
+        let var_assign = vars
+          .iter()
+          .map(|var_id| (*var_id, ctx.prob_table[var_id]))
+          .collect::<HashMap<usize, f64>>();
+        ProbEstimate::exact(sdd.eval_t(&var_assign, &ProbabilitySemiring))
+      }
+      None => self.sampling.estimate(ctx, &form),
+    };
+    estimate.to_score(ctx)
+  }
+}
@@ -0,0 +1,155 @@
+use std::collections::*;
+use std::marker::PhantomData;
+
+use sdd::{BooleanFormula, SDDBuilder, SDDBuilderConfig, Semiring as SDDSemiring, VTreeHeuristic, SDD};
+
+use super::boolean_formula::*;
+use super::prob_semiring::*;
+use super::proofs_wmc::*;
+use super::WeightedModelCounter;
+use crate::semiring::*;
+use crate::tags::ProbProofContext;
+
+/// A circuit compiled from one or more boolean formulas, with one root per
+/// formula, supporting weighted model counting
+pub trait CompiledCircuit {
+  /// The number of roots, i.e. the number of compiled formulas
+  fn num_roots(&self) -> usize;
+
+  /// The number of nodes of the circuit, to compare the backends
+  fn size(&self) -> usize;
+
+  /// Evaluate all the roots under the semiring, in order
+  fn eval_roots<S: SDDSemiring>(
+    &self,
+    var_assign: &HashMap<usize, S::Element>,
+    semiring: &S,
+  ) -> Vec<S::Element>;
+}
+
+/// A knowledge compilation backend, turning boolean formulas over input fact
+/// ids into a circuit
+pub trait KnowledgeCompiler: Send + Sync {
+  type Circuit: CompiledCircuit;
+
+  /// Compile all the formulas into a single circuit, sharing sub-circuits
+  /// among them; there is at least one variable among the formulas
+  fn compile(&self, forms: &[BooleanFormula]) -> Self::Circuit;
+}
+
+/// The SDD backend, with the vtree constructed by a heuristic
+#[derive(Clone, Debug, Default)]
+pub struct SDDCompiler {
+  pub heuristic: VTreeHeuristic,
+}
+
+impl SDDCompiler {
+  pub fn new(heuristic: VTreeHeuristic) -> Self {
+    Self { heuristic }
+  }
+}
+
+impl KnowledgeCompiler for SDDCompiler {
+  type Circuit = SDD;
+
+  fn compile(&self, forms: &[BooleanFormula]) -> Self::Circuit {
+    let config = SDDBuilderConfig::with_formulas_and_heuristic(forms, &self.heuristic);
+    let mut builder = SDDBuilder::with_config(config);
+    for form in forms {
+      builder.add_formula(form);
+    }
+    builder.build_arena()
+  }
+}
+
+impl CompiledCircuit for SDD {
+  fn num_roots(&self) -> usize {
+    SDD::num_roots(self)
+  }
+
+  fn size(&self) -> usize {
+    self.num_nodes()
+  }
+
+  fn eval_roots<S: SDDSemiring>(
+    &self,
+    var_assign: &HashMap<usize, S::Element>,
+    semiring: &S,
+  ) -> Vec<S::Element> {
+    self.eval_roots_t(var_assign, semiring)
+  }
+}
+
+/// Weighted model counter compiling the tags with a pluggable knowledge
+/// compilation backend
+///
+/// With `SDDCompiler::default()` this computes the same as the dedicated
+/// counters such as `ProbProofsWMC`; other backends such as `OBDDCompiler`
+/// can be swapped in to compare the size and speed of the circuits.
+#[derive(Clone, Debug)]
+pub struct CompiledWMC<Tag, C> {
+  pub compiler: C,
+  phantom: PhantomData<fn() -> Tag>,
+}
+
+impl<Tag, C> CompiledWMC<Tag, C> {
+  pub fn new(compiler: C) -> Self {
+    Self {
+      compiler,
+      phantom: PhantomData,
+    }
+  }
+}
+
+impl<Tag, C> WeightedModelCounter for CompiledWMC<Tag, C>
+where
+  Tag: Semiring<Context = ProbProofContext> + ToBooleanFormula,
+  C: KnowledgeCompiler,
+{
+  type Tag = Tag;
+
+  type Output = f64;
+
+  fn wmc(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tag: &Self::Tag,
+  ) -> Self::Output {
+    self.wmc_batch(ctx, &[tag])[0]
+  }
+
+  fn wmc_batch(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tags: &[&Self::Tag],
+  ) -> Vec<Self::Output> {
+    let forms = tags.iter().map(|tag| tag.to_boolean_formula()).collect::<Vec<_>>();
+    compiled_prob_wmc(ctx, &forms, &self.compiler)
+  }
+}
+
+/// Weighted model counts of many formulas, all compiled into one circuit by
+/// the backend; these are log-probabilities when the context is in log-space
+pub(crate) fn compiled_prob_wmc<C: KnowledgeCompiler>(
+  ctx: &ProbProofContext,
+  forms: &[BooleanFormula],
+  compiler: &C,
+) -> Vec<f64> {
+  // Shortcut when there is nothing to compile, as circuits need variables
+  let vars = forms.iter().flat_map(|form| form.collect_vars()).collect::<BTreeSet<_>>();
+  if vars.is_empty() {
+    let var_assign = HashMap::new();
+    return forms
+      .iter()
+      .map(|form| if form.eval(&var_assign) { ctx.one_score() } else { ctx.score(0.0) })
+      .collect();
+  }
+
+  let circuit = compiler.compile(forms);
+  let var_assign = prob_var_assign(ctx, &vars.into_iter().collect::<Vec<_>>());
+  if ctx.log_space {
+    circuit.eval_roots(&var_assign, &LogProbabilitySemiring)
+  } else {
+    circuit.eval_roots(&var_assign, &ProbabilitySemiring)
+  }
+}
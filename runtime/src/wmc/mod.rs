@@ -1,27 +1,43 @@
 #[cfg(feature = "torch")]
 mod diff_prob_semiring;
 #[cfg(feature = "torch")]
-mod diff_prob_semiring_2;
-#[cfg(feature = "torch")]
 mod diff_top_k_proofs_wmc;
 #[cfg(feature = "torch")]
 mod diff_top_k_proofs_wmc_2;
 
+mod boolean_formula;
+mod conditional_wmc;
+mod diff_prob_semiring_2;
+mod dyn_top_k_proofs_wmc;
+mod grad_top_k_proofs_wmc;
+mod hybrid_wmc;
+mod knowledge_compiler;
+mod mpe;
+mod obdd;
 mod prob_semiring;
 mod proofs_wmc;
+mod sampling_wmc;
 mod top_k_proofs_wmc;
 mod wmc;
 
 #[cfg(feature = "torch")]
 pub use diff_prob_semiring::*;
 #[cfg(feature = "torch")]
-pub use diff_prob_semiring_2::*;
-#[cfg(feature = "torch")]
 pub use diff_top_k_proofs_wmc::*;
 #[cfg(feature = "torch")]
 pub use diff_top_k_proofs_wmc_2::*;
 
+pub use boolean_formula::*;
+pub use conditional_wmc::*;
+pub use diff_prob_semiring_2::*;
+pub use dyn_top_k_proofs_wmc::*;
+pub use grad_top_k_proofs_wmc::*;
+pub use hybrid_wmc::*;
+pub use knowledge_compiler::*;
+pub use mpe::*;
+pub use obdd::*;
 pub use prob_semiring::*;
 pub use proofs_wmc::*;
+pub use sampling_wmc::*;
 pub use top_k_proofs_wmc::*;
 pub use wmc::*;
@@ -0,0 +1,87 @@
+use std::collections::*;
+
+use sdd::{bf, BooleanFormula, SDDBuilder, SDDBuilderConfig};
+
+use super::boolean_formula::*;
+use super::conditional_wmc::*;
+use crate::tags::ProbProofContext;
+
+/// The most probable world in which a tuple holds
+#[derive(Clone, Debug, PartialEq)]
+pub struct MostProbableExplanation {
+  /// The probability of the world, restricted to the facts it mentions
+  pub prob: f64,
+
+  /// The value of each input fact relevant to the tuple, ordered by fact id
+  pub facts: Vec<(usize, bool)>,
+}
+
+impl MostProbableExplanation {
+  /// The ids of the facts that are true in the world
+  pub fn true_facts(&self) -> Vec<usize> {
+    self.facts.iter().filter(|(_, value)| *value).map(|(id, _)| *id).collect()
+  }
+
+  /// The input facts along with their values, as printed by
+  /// `ProbProofContext::format_fact`
+  pub fn tuples(&self, ctx: &ProbProofContext) -> Vec<(String, bool)> {
+    self
+      .facts
+      .iter()
+      .map(|(id, value)| (ctx.format_fact(*id), *value))
+      .collect()
+  }
+}
+
+/// The most probable assignment of the input facts under which the tuple
+/// tagged with `tag` holds; `None` if the tag can never hold
+///
+/// Facts of the same disjunction are never assigned true together.
+pub fn most_probable_explanation<Tag: ToBooleanFormula>(
+  ctx: &ProbProofContext,
+  tag: &Tag,
+) -> Option<MostProbableExplanation> {
+  mpe_of_formula(ctx, tag.to_boolean_formula())
+}
+
+/// The most probable explanation of a tuple that is also consistent with the
+/// evidence
+pub fn most_probable_explanation_given<Tag: ToBooleanFormula>(
+  ctx: &ProbProofContext,
+  tag: &Tag,
+  evidence: &Evidence,
+) -> Option<MostProbableExplanation> {
+  mpe_of_formula(ctx, tag.to_boolean_formula() & evidence.formula().clone())
+}
+
+fn mpe_of_formula(ctx: &ProbProofContext, form: BooleanFormula) -> Option<MostProbableExplanation> {
+  let vars = form.collect_vars();
+  if vars.is_empty() {
+    return if form.eval(&HashMap::new()) {
+      Some(MostProbableExplanation { prob: 1.0, facts: vec![] })
+    } else {
+      None
+    };
+  }
+
+  // At most one fact of each disjunction can be true
+  let form = vars.iter().fold(form, |acc, var_id| {
+    ctx.disjunctions.disjunctions_of(var_id).iter().fold(acc, |acc, disj_id| {
+      ctx.disjunctions[*disj_id]
+        .range(var_id + 1..)
+        .filter(|other| vars.binary_search(other).is_ok())
+        .fold(acc, |acc, other| acc & (!bf(*var_id) | !bf(*other)))
+    })
+  });
+
+  let config = SDDBuilderConfig::with_formula(&form);
+  let sdd = SDDBuilder::with_config(config).build(&form);
+  let var_probs = vars
+    .iter()
+    .map(|var_id| (*var_id, ctx.prob_table[var_id]))
+    .collect::<HashMap<_, _>>();
+  sdd.mpe(&var_probs).map(|(prob, assign)| MostProbableExplanation {
+    prob,
+    facts: assign.into_iter().collect(),
+  })
+}
@@ -0,0 +1,322 @@
+use std::collections::*;
+
+use sdd::{BooleanFormula, Semiring as SDDSemiring};
+
+use super::knowledge_compiler::*;
+
+/// The order of the variables in an OBDD, from the root downwards
+#[derive(Clone, Debug, Default)]
+pub enum OBDDVarOrder {
+  /// Variables sorted by id
+  Sorted,
+
+  /// Variables in the order of their first occurrence in the formulas, which
+  /// keeps the facts of a proof close to each other
+  #[default]
+  FirstOccurrence,
+
+  /// A given order; variables missing from it come last, sorted by id
+  Given(Vec<usize>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OBDDNode {
+  False,
+  True,
+  Node { var_id: usize, low: usize, high: usize },
+}
+
+/// A reduced ordered binary decision diagram with multiple roots
+///
+/// Nodes are stored in creation order, so that the children of a node always
+/// come before it.
+#[derive(Clone, Debug)]
+pub struct OBDD {
+  nodes: Vec<OBDDNode>,
+  roots: Vec<usize>,
+}
+
+impl OBDD {
+  pub fn nodes(&self) -> &[OBDDNode] {
+    &self.nodes
+  }
+
+  pub fn roots(&self) -> &[usize] {
+    &self.roots
+  }
+}
+
+impl CompiledCircuit for OBDD {
+  fn num_roots(&self) -> usize {
+    self.roots.len()
+  }
+
+  fn size(&self) -> usize {
+    self.nodes.len()
+  }
+
+  fn eval_roots<S: SDDSemiring>(
+    &self,
+    var_assign: &HashMap<usize, S::Element>,
+    semiring: &S,
+  ) -> Vec<S::Element> {
+    let mut values: Vec<S::Element> = Vec::with_capacity(self.nodes.len());
+    for node in &self.nodes {
+      let value = match node {
+        OBDDNode::False => semiring.zero(),
+        OBDDNode::True => semiring.one(),
+        OBDDNode::Node { var_id, low, high } => {
+          let weight = var_assign[var_id].clone();
+          let pos = semiring.mult(weight.clone(), values[*high].clone());
+          let neg = semiring.mult(semiring.negate(weight), values[*low].clone());
+          semiring.add(pos, neg)
+        }
+      };
+      values.push(value);
+    }
+    self.roots.iter().map(|root| values[*root].clone()).collect()
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum OBDDOp {
+  And,
+  Or,
+}
+
+/// Builder of an OBDD under a variable order, with a unique table so that
+/// the diagram is reduced, and a cache of the applies
+pub struct OBDDBuilder {
+  levels: HashMap<usize, usize>,
+  nodes: Vec<OBDDNode>,
+  unique_table: HashMap<OBDDNode, usize>,
+  apply_cache: HashMap<(OBDDOp, usize, usize), usize>,
+  negate_cache: HashMap<usize, usize>,
+  roots: Vec<usize>,
+}
+
+const FALSE: usize = 0;
+const TRUE: usize = 1;
+
+impl OBDDBuilder {
+  /// A builder where variable `order[i]` is tested at depth `i`
+  pub fn new(order: Vec<usize>) -> Self {
+    let levels = order.into_iter().enumerate().map(|(level, var_id)| (var_id, level)).collect();
+    Self {
+      levels,
+      nodes: vec![OBDDNode::False, OBDDNode::True],
+      unique_table: HashMap::new(),
+      apply_cache: HashMap::new(),
+      negate_cache: HashMap::new(),
+      roots: vec![],
+    }
+  }
+
+  /// The number of nodes created so far
+  pub fn num_nodes(&self) -> usize {
+    self.nodes.len()
+  }
+
+  /// Compile a formula and add it as a new root; returns the index of the root
+  pub fn add_formula(&mut self, form: &BooleanFormula) -> usize {
+    let root = self.build(form);
+    self.roots.push(root);
+    self.roots.len() - 1
+  }
+
+  /// Build the OBDD, keeping only the constants and the nodes reachable from
+  /// the roots
+  pub fn build_obdd(self) -> OBDD {
+    let mut reachable = vec![false; self.nodes.len()];
+    reachable[FALSE] = true;
+    reachable[TRUE] = true;
+    let mut stack = self.roots.clone();
+    while let Some(node) = stack.pop() {
+      if !reachable[node] {
+        reachable[node] = true;
+        if let OBDDNode::Node { low, high, .. } = self.nodes[node] {
+          stack.push(low);
+          stack.push(high);
+        }
+      }
+    }
+
+    // Children come before their parents, so they are remapped first
+    let mut new_ids = vec![0; self.nodes.len()];
+    let mut nodes = vec![];
+    for (id, node) in self.nodes.into_iter().enumerate() {
+      if reachable[id] {
+        new_ids[id] = nodes.len();
+        nodes.push(match node {
+          OBDDNode::Node { var_id, low, high } => OBDDNode::Node {
+            var_id,
+            low: new_ids[low],
+            high: new_ids[high],
+          },
+          constant => constant,
+        });
+      }
+    }
+    let roots = self.roots.into_iter().map(|root| new_ids[root]).collect();
+    OBDD { nodes, roots }
+  }
+
+  fn build(&mut self, form: &BooleanFormula) -> usize {
+    match form {
+      BooleanFormula::False => FALSE,
+      BooleanFormula::True => TRUE,
+      BooleanFormula::Pos { var_id } => self.make_node(*var_id, FALSE, TRUE),
+      BooleanFormula::Neg { var_id } => self.make_node(*var_id, TRUE, FALSE),
+      BooleanFormula::Not { form } => {
+        let node = self.build(form);
+        self.negate(node)
+      }
+      BooleanFormula::And { left, right } => {
+        let (l, r) = (self.build(left), self.build(right));
+        self.apply(OBDDOp::And, l, r)
+      }
+      BooleanFormula::Or { left, right } => {
+        let (l, r) = (self.build(left), self.build(right));
+        self.apply(OBDDOp::Or, l, r)
+      }
+    }
+  }
+
+  fn make_node(&mut self, var_id: usize, low: usize, high: usize) -> usize {
+    // Redundant tests are skipped
+    if low == high {
+      return low;
+    }
+    let node = OBDDNode::Node { var_id, low, high };
+    if let Some(id) = self.unique_table.get(&node) {
+      return *id;
+    }
+    let id = self.nodes.len();
+    self.nodes.push(node);
+    self.unique_table.insert(node, id);
+    id
+  }
+
+  fn level(&self, node: usize) -> usize {
+    match &self.nodes[node] {
+      OBDDNode::Node { var_id, .. } => self.levels[var_id],
+      _ => usize::MAX,
+    }
+  }
+
+  /// The children of a node when testing the variable at `level`
+  fn cofactors(&self, node: usize, level: usize) -> (usize, usize) {
+    match &self.nodes[node] {
+      OBDDNode::Node { var_id, low, high } if self.levels[var_id] == level => (*low, *high),
+      _ => (node, node),
+    }
+  }
+
+  fn apply(&mut self, op: OBDDOp, lhs: usize, rhs: usize) -> usize {
+    // Terminal cases
+    match (op, lhs, rhs) {
+      (OBDDOp::And, FALSE, _) | (OBDDOp::And, _, FALSE) => return FALSE,
+      (OBDDOp::And, TRUE, other) | (OBDDOp::And, other, TRUE) => return other,
+      (OBDDOp::Or, TRUE, _) | (OBDDOp::Or, _, TRUE) => return TRUE,
+      (OBDDOp::Or, FALSE, other) | (OBDDOp::Or, other, FALSE) => return other,
+      _ if lhs == rhs => return lhs,
+      _ => {}
+    }
+
+    // Both operations are commutative
+    let key = (op, lhs.min(rhs), lhs.max(rhs));
+    if let Some(result) = self.apply_cache.get(&key) {
+      return *result;
+    }
+
+    // Shannon expansion on the top-most variable
+    let level = self.level(lhs).min(self.level(rhs));
+    let (l_low, l_high) = self.cofactors(lhs, level);
+    let (r_low, r_high) = self.cofactors(rhs, level);
+    let low = self.apply(op, l_low, r_low);
+    let high = self.apply(op, l_high, r_high);
+    let var_id = match (&self.nodes[lhs], &self.nodes[rhs]) {
+      (OBDDNode::Node { var_id, .. }, _) if self.levels[var_id] == level => *var_id,
+      (_, OBDDNode::Node { var_id, .. }) => *var_id,
+      _ => panic!("Should not happen"),
+    };
+    let result = self.make_node(var_id, low, high);
+    self.apply_cache.insert(key, result);
+    result
+  }
+
+  fn negate(&mut self, node: usize) -> usize {
+    if let Some(result) = self.negate_cache.get(&node) {
+      return *result;
+    }
+    let result = match self.nodes[node] {
+      OBDDNode::False => TRUE,
+      OBDDNode::True => FALSE,
+      OBDDNode::Node { var_id, low, high } => {
+        let (low, high) = (self.negate(low), self.negate(high));
+        self.make_node(var_id, low, high)
+      }
+    };
+    self.negate_cache.insert(node, result);
+    result
+  }
+}
+
+/// The OBDD backend, under a variable order
+#[derive(Clone, Debug, Default)]
+pub struct OBDDCompiler {
+  pub order: OBDDVarOrder,
+}
+
+impl OBDDCompiler {
+  pub fn new(order: OBDDVarOrder) -> Self {
+    Self { order }
+  }
+
+  /// The variable order of the formulas, from the root downwards
+  pub fn var_order(&self, forms: &[BooleanFormula]) -> Vec<usize> {
+    let vars = forms.iter().flat_map(|form| form.collect_vars()).collect::<BTreeSet<_>>();
+    let given = match &self.order {
+      OBDDVarOrder::Sorted => vec![],
+      OBDDVarOrder::FirstOccurrence => {
+        let mut order = vec![];
+        for form in forms {
+          collect_vars_in_occurrence_order(form, &mut order);
+        }
+        order
+      }
+      OBDDVarOrder::Given(order) => order.clone(),
+    };
+    let mut visited = HashSet::new();
+    let mut order = given
+      .into_iter()
+      .filter(|var_id| vars.contains(var_id) && visited.insert(*var_id))
+      .collect::<Vec<_>>();
+    order.extend(vars.into_iter().filter(|var_id| !visited.contains(var_id)));
+    order
+  }
+}
+
+impl KnowledgeCompiler for OBDDCompiler {
+  type Circuit = OBDD;
+
+  fn compile(&self, forms: &[BooleanFormula]) -> Self::Circuit {
+    let mut builder = OBDDBuilder::new(self.var_order(forms));
+    for form in forms {
+      builder.add_formula(form);
+    }
+    builder.build_obdd()
+  }
+}
+
+fn collect_vars_in_occurrence_order(form: &BooleanFormula, order: &mut Vec<usize>) {
+  match form {
+    BooleanFormula::True | BooleanFormula::False => {}
+    BooleanFormula::Pos { var_id } | BooleanFormula::Neg { var_id } => order.push(*var_id),
+    BooleanFormula::Not { form } => collect_vars_in_occurrence_order(form, order),
+    BooleanFormula::And { left, right } | BooleanFormula::Or { left, right } => {
+      collect_vars_in_occurrence_order(left, order);
+      collect_vars_in_occurrence_order(right, order);
+    }
+  }
+}
@@ -3,7 +3,7 @@ use sdd::Semiring as SDDSemiring;
 pub struct ProbabilitySemiring;
 
 impl SDDSemiring for ProbabilitySemiring {
-  type Element = f32;
+  type Element = f64;
 
   fn zero(&self) -> Self::Element {
     0.0
@@ -25,3 +25,38 @@ impl SDDSemiring for ProbabilitySemiring {
     1.0 - a
   }
 }
+
+/// Probabilities represented by their logarithms, so that products of many
+/// small probabilities do not underflow
+pub struct LogProbabilitySemiring;
+
+impl SDDSemiring for LogProbabilitySemiring {
+  type Element = f64;
+
+  fn zero(&self) -> Self::Element {
+    f64::NEG_INFINITY
+  }
+
+  fn one(&self) -> Self::Element {
+    0.0
+  }
+
+  /// log(exp(a) + exp(b)), computed without leaving log-space
+  fn add(&self, a: Self::Element, b: Self::Element) -> Self::Element {
+    let (max, min) = if a > b { (a, b) } else { (b, a) };
+    if max == f64::NEG_INFINITY {
+      max
+    } else {
+      max + (min - max).exp().ln_1p()
+    }
+  }
+
+  fn mult(&self, a: Self::Element, b: Self::Element) -> Self::Element {
+    a + b
+  }
+
+  /// log(1 - exp(a))
+  fn negate(&self, a: Self::Element) -> Self::Element {
+    (-a.exp()).ln_1p()
+  }
+}
@@ -1,11 +1,13 @@
 use std::collections::*;
 
-use sdd::{BooleanFormula, SDDBuilder, SDDBuilderConfig};
+use sdd::{BooleanFormula, SDDBuilder, SDDBuilderConfig, VTreeHeuristic};
 
+use super::boolean_formula::*;
+use super::knowledge_compiler::*;
 use super::prob_semiring::*;
 use super::WeightedModelCounter;
 use crate::semiring::*;
-use crate::tags::{ProbProof, ProbProofs};
+use crate::tags::{ProbProof, ProbProofContext, ProbProofs};
 
 #[derive(Debug, Clone)]
 pub struct ProbProofsWMC;
@@ -13,45 +15,98 @@ pub struct ProbProofsWMC;
 impl WeightedModelCounter for ProbProofsWMC {
   type Tag = ProbProofs;
 
-  type Output = f32;
+  type Output = f64;
 
   fn wmc(
     &self,
     ctx: &<Self::Tag as Semiring>::Context,
     tag: &Self::Tag,
   ) -> Self::Output {
+    // Shortcut for false tag and always true tag
+    if tag.proofs.is_empty() {
+      return ctx.score(0.0);
+    } else if tag.proofs.iter().any(|p| p.facts.is_empty() && p.neg_facts.is_empty()) {
+      return ctx.one_score();
+    }
+
     let form = prob_proofs_to_boolean_formula(tag);
     let vars = form.collect_vars();
     let config = SDDBuilderConfig::with_formula(&form);
     let sdd = SDDBuilder::with_config(config).build(&form);
-    let prob_semiring = ProbabilitySemiring;
-    let var_assign = vars
+    let var_assign = prob_var_assign(ctx, &vars);
+    if ctx.log_space {
+      sdd.eval_t(&var_assign, &LogProbabilitySemiring)
+    } else {
+      sdd.eval_t(&var_assign, &ProbabilitySemiring)
+    }
+  }
+
+  fn wmc_batch(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tags: &[&Self::Tag],
+  ) -> Vec<Self::Output> {
+    let forms = tags
       .iter()
-      .map(|var_id| (var_id.clone(), ctx.prob_table[var_id]))
-      .collect::<HashMap<usize, f32>>();
-    sdd.eval_t(&var_assign, &prob_semiring)
+      .map(|tag| {
+        if tag.proofs.iter().any(|p| p.facts.is_empty() && p.neg_facts.is_empty()) {
+          BooleanFormula::True
+        } else {
+          prob_proofs_to_boolean_formula(tag)
+        }
+      })
+      .collect::<Vec<_>>();
+    batched_prob_wmc(ctx, &forms)
   }
 }
 
-fn prob_proof_to_boolean_formula(proof: &ProbProof) -> BooleanFormula {
-  let mut iter = proof.facts.iter();
-  let mut acc = BooleanFormula::Pos {
-    var_id: iter.next().unwrap().clone(),
+/// Assign to each variable its score under the context
+pub(crate) fn prob_var_assign(ctx: &ProbProofContext, vars: &[usize]) -> HashMap<usize, f64> {
+  vars
+    .iter()
+    .map(|var_id| (*var_id, ctx.score(ctx.prob_table[var_id])))
+    .collect()
+}
+
+/// Weighted model counts of many formulas, all compiled into one SDD arena
+/// sharing a common vtree, so that sub-formulas common to several formulas
+/// are compiled and evaluated only once
+///
+/// The facts of each disjunction of the context are kept in their own
+/// subtree of the vtree; without disjunctions, co-occurring facts are
+/// clustered.
+pub(crate) fn batched_prob_wmc(ctx: &ProbProofContext, forms: &[BooleanFormula]) -> Vec<f64> {
+  let heuristic = if ctx.disjunctions.is_empty() {
+    VTreeHeuristic::CoOccurrence
+  } else {
+    let groups = ctx.disjunctions.iter().map(|disj| disj.iter().cloned().collect()).collect();
+    VTreeHeuristic::Groups(groups)
   };
-  while let Some(var_id) = iter.next() {
-    acc = acc
-      & BooleanFormula::Pos {
-        var_id: var_id.clone(),
-      };
+  compiled_prob_wmc(ctx, forms, &SDDCompiler::new(heuristic))
+}
+
+impl ToBooleanFormula for ProbProofs {
+  fn to_boolean_formula(&self) -> BooleanFormula {
+    prob_proofs_to_boolean_formula(self)
+  }
+}
+
+fn prob_proof_to_boolean_formula(proof: &ProbProof) -> BooleanFormula {
+  let pos = proof.facts.iter().map(|var_id| BooleanFormula::Pos { var_id: *var_id });
+  let neg = proof.neg_facts.iter().map(|var_id| BooleanFormula::Neg { var_id: *var_id });
+  let mut iter = pos.chain(neg);
+  match iter.next() {
+    Some(first) => iter.fold(first, |acc, lit| acc & lit),
+    None => BooleanFormula::True,
   }
-  acc
 }
 
 fn prob_proofs_to_boolean_formula(proofs: &ProbProofs) -> BooleanFormula {
   let mut iter = proofs.proofs.iter();
-  let mut acc = prob_proof_to_boolean_formula(iter.next().unwrap());
-  while let Some(proof) = iter.next() {
-    acc = acc | prob_proof_to_boolean_formula(proof);
+  match iter.next() {
+    Some(first) => iter.fold(prob_proof_to_boolean_formula(first), |acc, proof| {
+      acc | prob_proof_to_boolean_formula(proof)
+    }),
+    None => BooleanFormula::False,
   }
-  acc
 }
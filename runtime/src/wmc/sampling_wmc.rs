@@ -0,0 +1,138 @@
+use std::collections::*;
+use std::marker::PhantomData;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use sdd::BooleanFormula;
+
+use super::boolean_formula::*;
+use super::WeightedModelCounter;
+use crate::semiring::*;
+use crate::tags::ProbProofContext;
+
+/// An estimated probability along with its confidence interval
+///
+/// The counters return the estimates as scores of the context, i.e. as
+/// log-probabilities when the context is in log-space, like the other counters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProbEstimate {
+  pub prob: f64,
+  pub lower: f64,
+  pub upper: f64,
+
+  /// Whether `prob` is exact, in which case the interval is reduced to it
+  pub exact: bool,
+}
+
+impl ProbEstimate {
+  pub fn exact(prob: f64) -> Self {
+    Self {
+      prob,
+      lower: prob,
+      upper: prob,
+      exact: true,
+    }
+  }
+
+  /// The estimate with all its bounds turned into scores of the context
+  pub fn to_score(self, ctx: &ProbProofContext) -> Self {
+    Self {
+      prob: ctx.score(self.prob),
+      lower: ctx.score(self.lower),
+      upper: ctx.score(self.upper),
+      exact: self.exact,
+    }
+  }
+
+  /// Estimate from `num_true` satisfying samples out of `num_samples`, using
+  /// the Wilson score interval for the z-score `z`
+  pub fn from_samples(num_true: usize, num_samples: usize, z: f64) -> Self {
+    let n = num_samples as f64;
+    let p = num_true as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let radius = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    Self {
+      prob: p,
+      lower: (center - radius).max(0.0),
+      upper: (center + radius).min(1.0),
+      exact: false,
+    }
+  }
+}
+
+/// Weighted model counter estimating the probability of a tag by Monte Carlo
+/// sampling of the input facts
+///
+/// Every sample draws each fact occurring in the tag independently with its
+/// probability, and checks whether the boolean formula of the tag holds. The
+/// samples are drawn from a generator seeded with `seed`, so that the
+/// estimates are reproducible.
+#[derive(Clone, Debug)]
+pub struct SamplingWMC<Tag> {
+  pub num_samples: usize,
+  pub seed: u64,
+
+  /// The z-score of the confidence interval; 1.96 gives a 95% interval
+  pub z: f64,
+
+  phantom: PhantomData<fn() -> Tag>,
+}
+
+impl<Tag> SamplingWMC<Tag> {
+  pub fn new(num_samples: usize) -> Self {
+    Self {
+      num_samples,
+      seed: 0,
+      z: 1.96,
+      phantom: PhantomData,
+    }
+  }
+
+  pub fn with_seed(mut self, seed: u64) -> Self {
+    self.seed = seed;
+    self
+  }
+
+  pub fn with_z(mut self, z: f64) -> Self {
+    self.z = z;
+    self
+  }
+
+  pub(crate) fn estimate(&self, ctx: &ProbProofContext, form: &BooleanFormula) -> ProbEstimate {
+    let vars = form.collect_vars();
+    let mut var_assign = HashMap::new();
+    if vars.is_empty() || self.num_samples == 0 {
+      return ProbEstimate::exact(if form.eval(&var_assign) { 1.0 } else { 0.0 });
+    }
+
+    let mut rng = StdRng::seed_from_u64(self.seed);
+    let mut num_true = 0;
+    for _ in 0..self.num_samples {
+      for var_id in &vars {
+        var_assign.insert(*var_id, rng.gen::<f64>() < ctx.prob_table[var_id]);
+      }
+      if form.eval(&var_assign) {
+        num_true += 1;
+      }
+    }
+    ProbEstimate::from_samples(num_true, self.num_samples, self.z)
+  }
+}
+
+impl<Tag> WeightedModelCounter for SamplingWMC<Tag>
+where
+  Tag: Semiring<Context = ProbProofContext> + ToBooleanFormula,
+{
+  type Tag = Tag;
+
+  type Output = ProbEstimate;
+
+  fn wmc(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tag: &Self::Tag,
+  ) -> Self::Output {
+    self.estimate(ctx, &tag.to_boolean_formula()).to_score(ctx)
+  }
+}
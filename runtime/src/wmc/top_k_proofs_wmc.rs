@@ -2,10 +2,12 @@ use std::collections::*;
 
 use sdd::{BooleanFormula, SDDBuilder, SDDBuilderConfig};
 
+use super::boolean_formula::*;
 use super::prob_semiring::*;
+use super::proofs_wmc::*;
 use super::WeightedModelCounter;
 use crate::semiring::*;
-use crate::tags::{TopKProbProof, TopKProbProofs};
+use crate::tags::{DynTopKProbProofs, ProbProofContext, TopKProbProof, TopKProbProofs};
 
 #[derive(Debug, Clone)]
 pub struct TopKProbProofsWMC<const K: usize>;
@@ -13,47 +15,92 @@ pub struct TopKProbProofsWMC<const K: usize>;
 impl<const K: usize> WeightedModelCounter for TopKProbProofsWMC<K> {
   type Tag = TopKProbProofs<K>;
 
-  type Output = f32;
+  type Output = f64;
 
   fn wmc(
     &self,
     ctx: &<Self::Tag as Semiring>::Context,
     tag: &Self::Tag,
   ) -> Self::Output {
-    let form = top_k_prob_proofs_to_boolean_formula(tag);
-    let vars = form.collect_vars();
-    let config = SDDBuilderConfig::with_formula(&form);
-    let sdd = SDDBuilder::with_config(config).build(&form);
-    let prob_semiring = ProbabilitySemiring;
-    let var_assign = vars
-      .iter()
-      .map(|var_id| (var_id.clone(), ctx.prob_table[var_id]))
-      .collect::<HashMap<usize, f32>>();
-    sdd.eval_t(&var_assign, &prob_semiring)
+    top_k_prob_proofs_wmc(ctx, &tag.proofs)
+  }
+
+  fn wmc_batch(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tags: &[&Self::Tag],
+  ) -> Vec<Self::Output> {
+    batched_top_k_prob_proofs_wmc(ctx, tags.iter().map(|tag| &tag.proofs))
+  }
+}
+
+/// Weighted model count of a heap of top-k proofs; this is a log-probability
+/// when the context is in log-space
+pub(crate) fn top_k_prob_proofs_wmc(ctx: &ProbProofContext, proofs: &BinaryHeap<TopKProbProof>) -> f64 {
+  // Shortcut for false tag and always true tag
+  if proofs.is_empty() {
+    return ctx.score(0.0);
+  } else if proofs.iter().any(|p| p.facts.is_empty() && p.neg_facts.is_empty()) {
+    return ctx.one_score();
+  }
+
+  let form = top_k_prob_proofs_to_boolean_formula(proofs);
+  let vars = form.collect_vars();
+  let config = SDDBuilderConfig::with_formula(&form);
+  let sdd = SDDBuilder::with_config(config).build(&form);
+  let var_assign = prob_var_assign(ctx, &vars);
+  if ctx.log_space {
+    sdd.eval_t(&var_assign, &LogProbabilitySemiring)
+  } else {
+    sdd.eval_t(&var_assign, &ProbabilitySemiring)
   }
 }
 
-fn top_k_prob_proofs_to_boolean_formula<const K: usize>(
-  proofs: &TopKProbProofs<K>,
-) -> BooleanFormula {
-  let mut iter = proofs.proofs.iter();
-  let mut acc = top_k_prob_proof_to_boolean_formula(iter.next().unwrap());
-  while let Some(proof) = iter.next() {
-    acc = acc | top_k_prob_proof_to_boolean_formula(proof);
+/// Weighted model counts of many heaps of top-k proofs, sharing one SDD arena
+pub(crate) fn batched_top_k_prob_proofs_wmc<'a, I>(ctx: &ProbProofContext, tags: I) -> Vec<f64>
+where
+  I: Iterator<Item = &'a BinaryHeap<TopKProbProof>>,
+{
+  let forms = tags
+    .map(|proofs| {
+      if proofs.iter().any(|p| p.facts.is_empty() && p.neg_facts.is_empty()) {
+        BooleanFormula::True
+      } else {
+        top_k_prob_proofs_to_boolean_formula(proofs)
+      }
+    })
+    .collect::<Vec<_>>();
+  batched_prob_wmc(ctx, &forms)
+}
+
+impl<const K: usize> ToBooleanFormula for TopKProbProofs<K> {
+  fn to_boolean_formula(&self) -> BooleanFormula {
+    top_k_prob_proofs_to_boolean_formula(&self.proofs)
+  }
+}
+
+impl ToBooleanFormula for DynTopKProbProofs {
+  fn to_boolean_formula(&self) -> BooleanFormula {
+    top_k_prob_proofs_to_boolean_formula(&self.proofs)
+  }
+}
+
+pub(crate) fn top_k_prob_proofs_to_boolean_formula(proofs: &BinaryHeap<TopKProbProof>) -> BooleanFormula {
+  let mut iter = proofs.iter();
+  match iter.next() {
+    Some(first) => iter.fold(top_k_prob_proof_to_boolean_formula(first), |acc, proof| {
+      acc | top_k_prob_proof_to_boolean_formula(proof)
+    }),
+    None => BooleanFormula::False,
   }
-  acc
 }
 
 fn top_k_prob_proof_to_boolean_formula(proof: &TopKProbProof) -> BooleanFormula {
-  let mut iter = proof.facts.iter();
-  let mut acc = BooleanFormula::Pos {
-    var_id: iter.next().unwrap().clone(),
-  };
-  while let Some(var_id) = iter.next() {
-    acc = acc
-      & BooleanFormula::Pos {
-        var_id: var_id.clone(),
-      };
-  }
-  acc
+  let pos = proof.facts.iter().map(|var_id| BooleanFormula::Pos { var_id: *var_id });
+  let neg = proof.neg_facts.iter().map(|var_id| BooleanFormula::Neg { var_id: *var_id });
+  let mut iter = pos.chain(neg);
+  match iter.next() {
+    Some(first) => iter.fold(first, |acc, lit| acc & lit),
+    None => BooleanFormula::True,
+  }
 }
@@ -10,4 +10,16 @@ pub trait WeightedModelCounter: Send + Sync {
     ctx: &<Self::Tag as Semiring>::Context,
     tag: &Self::Tag
   ) -> Self::Output;
+
+  /// Weighted model counts of many tags at once, in the same order as `tags`
+  ///
+  /// By default each tag is counted separately; counters that compile tags
+  /// into SDDs override this to share a single SDD arena among all the tags.
+  fn wmc_batch(
+    &self,
+    ctx: &<Self::Tag as Semiring>::Context,
+    tags: &[&Self::Tag],
+  ) -> Vec<Self::Output> {
+    tags.iter().map(|tag| self.wmc(ctx, tag)).collect()
+  }
 }
@@ -0,0 +1,71 @@
+use scallop_runtime::wmc::*;
+use scallop_runtime::*;
+
+/// (a /\ b) \/ (b /\ c) \/ (c /\ d) over facts of probability 0.5
+fn overlapping_proofs(ctx: &mut ProbProofContext) -> ProbProofs {
+  let facts: Vec<ProbProofs> = (0..4).map(|_| ctx.base_tag(0.5)).collect();
+  (0..3).fold(ProbProofs::zero(ctx), |acc, i| {
+    let proof = ProbProofs::mult(ctx, &facts[i], &facts[i + 1]);
+    ProbProofs::add(ctx, &acc, &proof)
+  })
+}
+
+#[test]
+fn test_sampling_wmc_within_interval() {
+  let mut ctx = ProbProofContext::default();
+  let tag = overlapping_proofs(&mut ctx);
+  let exact = ProbProofsWMC.wmc(&ctx, &tag);
+  assert!((exact - 8.0 / 16.0).abs() < 1e-9);
+
+  let estimate = SamplingWMC::<ProbProofs>::new(10000).with_seed(7).wmc(&ctx, &tag);
+  assert!(!estimate.exact);
+  assert!(estimate.lower <= exact && exact <= estimate.upper);
+  assert!(estimate.upper - estimate.lower < 0.05);
+
+  // The estimate is reproducible for a given seed
+  let again = SamplingWMC::<ProbProofs>::new(10000).with_seed(7).wmc(&ctx, &tag);
+  assert_eq!(estimate, again);
+}
+
+#[test]
+fn test_sampling_wmc_constant_tags() {
+  let ctx = ProbProofContext::default();
+  let wmc = SamplingWMC::<ProbProofs>::new(100);
+  assert_eq!(wmc.wmc(&ctx, &ProbProofs::one(&ctx)), ProbEstimate::exact(1.0));
+  assert_eq!(wmc.wmc(&ctx, &ProbProofs::zero(&ctx)), ProbEstimate::exact(0.0));
+}
+
+#[test]
+fn test_hybrid_wmc() {
+  let mut ctx = ProbProofContext::default();
+  let tag = overlapping_proofs(&mut ctx);
+  let sampling = SamplingWMC::<ProbProofs>::new(10000);
+
+  // Within budget, the result is exact
+  let hybrid = HybridWMC::new(SDDBudget::unlimited(), sampling.clone());
+  let estimate = hybrid.wmc(&ctx, &tag);
+  assert!(estimate.exact);
+  assert!((estimate.prob - 0.5).abs() < 1e-9);
+
+  // Out of budget, the result falls back to sampling
+  let hybrid = HybridWMC::new(SDDBudget::new(Some(1), None), sampling.clone());
+  let estimate = hybrid.wmc(&ctx, &tag);
+  assert!(!estimate.exact);
+  assert_eq!(estimate, sampling.wmc(&ctx, &tag));
+}
+
+#[test]
+fn test_hybrid_wmc_log_space() {
+  let mut ctx = ProbProofContext::with_log_space();
+  let tag = overlapping_proofs(&mut ctx);
+  let sampling = SamplingWMC::<ProbProofs>::new(10000);
+
+  // Both the exact and the sampled estimates are log-probabilities
+  let exact = HybridWMC::new(SDDBudget::unlimited(), sampling.clone()).wmc(&ctx, &tag);
+  assert!(exact.exact);
+  assert!((exact.prob - 0.5f64.ln()).abs() < 1e-9);
+  let sampled = HybridWMC::new(SDDBudget::new(Some(1), None), sampling.clone()).wmc(&ctx, &tag);
+  assert!(!sampled.exact);
+  assert!(sampled.lower <= exact.prob && exact.prob <= sampled.upper);
+  assert_eq!(sampled, sampling.wmc(&ctx, &tag));
+}
@@ -0,0 +1,108 @@
+use scallop_runtime::dataflows::*;
+use scallop_runtime::wmc::*;
+use scallop_runtime::*;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+  (a - b).abs() < 1e-9
+}
+
+/// ``` datalog
+/// decl digit(ImageID, Int).
+/// decl sum(ImageID, ImageID, Int).
+///
+/// sum(A, B, D1 + D2) :- digit(A, D1), digit(B, D2).
+/// ```
+struct Sum2<Tag: Semiring<Context = ProbProofContext>> {
+  iter: Iteration<Tag>,
+  digit: Variable<(usize, u8), Tag>,
+  sum: Variable<((usize, usize), u8), Tag>,
+}
+
+impl<Tag: Semiring<Context = ProbProofContext>> Program<Tag> for Sum2<Tag> {
+  fn new() -> Self {
+    let mut iter = Iteration::new();
+    let digit = iter.variable::<(usize, u8)>();
+    let sum = iter.variable::<((usize, usize), u8)>();
+    Self { iter, digit, sum }
+  }
+
+  fn iteration(&self) -> &Iteration<Tag> {
+    &self.iter
+  }
+
+  fn iteration_mut(&mut self) -> &mut Iteration<Tag> {
+    &mut self.iter
+  }
+
+  fn update(&self) {
+    self.iter.insert_dataflow(
+      &self.sum,
+      self
+        .iter
+        .product(self.digit.find(0), self.digit.find(1))
+        .project(|((o1, d1), (o2, d2))| ((o1, o2), d1 + d2)),
+    );
+  }
+}
+
+fn run_sum2<Tag>(ctx: ProbProofContext) -> Sum2<Tag>
+where
+  Tag: Semiring<Context = ProbProofContext>,
+  ProbProofContext: SemiringContext<Tag, Info = f64>,
+{
+  let mut prog = Sum2::<Tag>::new();
+  prog.iter.semiring_ctx = ctx;
+  prog.iter.insert_disjunction(
+    &prog.digit,
+    vec![(0.1, (0, 0)), (0.6, (0, 1)), (0.2, (0, 2)), (0.1, (0, 3))],
+  );
+  prog.iter.insert_disjunction(
+    &prog.digit,
+    vec![(0.6, (1, 0)), (0.1, (1, 1)), (0.2, (1, 2)), (0.1, (1, 3))],
+  );
+  prog.run();
+  prog
+}
+
+#[test]
+fn test_batched_wmc_proofs() {
+  let mut prog = run_sum2::<ProbProofs>(ProbProofContext::default());
+  let result = prog.iter.variable_handle(&prog.sum).complete_with_batched_wmc(&ProbProofsWMC);
+  assert_eq!(result.len(), 7);
+  for (_, tag, prob) in &result {
+    assert!(approx_eq(*prob, ProbProofsWMC.wmc(&prog.iter.semiring_ctx, tag)));
+  }
+
+  // The sum 0 has a single proof, digit(0, 0) and digit(1, 0)
+  let (_, _, prob_0) = result.iter().find(|(tup, _, _)| tup.1 == 0).unwrap();
+  assert!(approx_eq(*prob_0, 0.1 * 0.6));
+}
+
+#[test]
+fn test_batched_wmc_dyn_top_k_log_space() {
+  let mut ctx = ProbProofContext::with_log_space();
+  ctx.top_k = 2;
+  let mut prog = run_sum2::<DynTopKProbProofs>(ctx);
+  let result = prog
+    .iter
+    .variable_handle(&prog.sum)
+    .complete_with_batched_wmc(&DynTopKProbProofsWMC);
+  for (_, tag, log_prob) in &result {
+    assert!(approx_eq(*log_prob, DynTopKProbProofsWMC.wmc(&prog.iter.semiring_ctx, tag)));
+  }
+}
+
+#[test]
+fn test_batched_wmc_constant_tags() {
+  let mut ctx = ProbProofContext::default();
+  let a: ProbProofs = ctx.base_tag(0.3);
+  let one = ProbProofs::one(&ctx);
+  let zero = ProbProofs::zero(&ctx);
+  assert_eq!(ProbProofsWMC.wmc_batch(&ctx, &[&one, &zero]), vec![1.0, 0.0]);
+
+  let results = ProbProofsWMC.wmc_batch(&ctx, &[&zero, &a, &ProbProofs::add(&ctx, &a, &one)]);
+  assert_eq!(results.len(), 3);
+  assert!(approx_eq(results[0], 0.0));
+  assert!(approx_eq(results[1], 0.3));
+  assert!(approx_eq(results[2], 1.0));
+}
@@ -0,0 +1,138 @@
+use scallop_runtime::dataflows::*;
+use scallop_runtime::wmc::*;
+use scallop_runtime::*;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+  (a - b).abs() < 1e-9
+}
+
+/// ``` datalog
+/// decl cause(Int).
+/// decl alarm().
+///
+/// 0.1::cause(0). // burglary
+/// 0.2::cause(1). // earthquake
+///
+/// alarm() :- cause(_).
+/// ```
+struct Alarm<Tag: Semiring<Context = ProbProofContext>> {
+  iter: Iteration<Tag>,
+  cause: Variable<usize, Tag>,
+  alarm: Variable<(), Tag>,
+}
+
+impl<Tag: Semiring<Context = ProbProofContext>> Program<Tag> for Alarm<Tag> {
+  fn new() -> Self {
+    let mut iter = Iteration::new();
+    let cause = iter.variable::<usize>();
+    let alarm = iter.variable::<()>();
+    Self { iter, cause, alarm }
+  }
+
+  fn iteration(&self) -> &Iteration<Tag> {
+    &self.iter
+  }
+
+  fn iteration_mut(&mut self) -> &mut Iteration<Tag> {
+    &mut self.iter
+  }
+
+  fn update(&self) {
+    self.iter.insert_dataflow(&self.alarm, self.cause.project(|_| ()));
+  }
+}
+
+#[test]
+fn test_conditional_prob_diagnosis() {
+  let mut prog = Alarm::<ProbProofs>::new();
+  prog.iter.insert_with_tag_info(&prog.cause, vec![(0.1, 0), (0.2, 1)]);
+  prog.run();
+
+  // Observe the alarm
+  let alarm = prog.iter.complete(&prog.alarm);
+  let mut evidence = Evidence::new();
+  evidence.observe(&alarm.elements[0].tag, true);
+
+  // P(cause | alarm) = P(cause) / P(alarm)
+  let p_alarm = 1.0 - 0.9 * 0.8;
+  let result = prog.iter.variable_handle(&prog.cause).complete_with_evidence(&evidence);
+  assert_eq!(result.len(), 2);
+  assert!(approx_eq(result[0].2.unwrap(), 0.1 / p_alarm));
+  assert!(approx_eq(result[1].2.unwrap(), 0.2 / p_alarm));
+}
+
+#[test]
+fn test_conditional_prob_negative_evidence() {
+  let mut ctx = ProbProofContext::default();
+  let burglary: ProbProofs = ctx.base_tag(0.1);
+  let earthquake: ProbProofs = ctx.base_tag(0.2);
+  let alarm = ProbProofs::add(&ctx, &burglary, &earthquake);
+
+  // P(alarm | ~earthquake) = P(burglary)
+  let mut evidence = Evidence::new();
+  evidence.observe(&earthquake, false);
+  assert!(approx_eq(conditional_prob(&ctx, &alarm, &evidence).unwrap(), 0.1));
+
+  // P(burglary | alarm, ~earthquake) = 1
+  evidence.observe(&alarm, true);
+  assert!(approx_eq(conditional_prob(&ctx, &burglary, &evidence).unwrap(), 1.0));
+
+  // Impossible evidence
+  evidence.observe(&burglary, false);
+  assert_eq!(conditional_prob(&ctx, &alarm, &evidence), None);
+
+  // Without evidence, the conditional probability is the marginal
+  let no_evidence = Evidence::new();
+  assert!(no_evidence.is_empty());
+  assert!(approx_eq(conditional_prob(&ctx, &alarm, &no_evidence).unwrap(), 0.28));
+}
+
+#[test]
+fn test_conditional_prob_log_space() {
+  let mut ctx = ProbProofContext::with_log_space();
+  let burglary: DynTopKProbProofs = ctx.base_tag(0.1);
+  let earthquake: DynTopKProbProofs = ctx.base_tag(0.2);
+  let alarm = DynTopKProbProofs::add(&ctx, &burglary, &earthquake);
+
+  let mut evidence = Evidence::new();
+  evidence.observe(&alarm, true);
+  let log_prob = conditional_prob(&ctx, &burglary, &evidence).unwrap();
+  assert!(approx_eq(log_prob.exp(), 0.1 / 0.28));
+}
+
+#[test]
+fn test_conditional_prob_of_loaded_program() {
+  let ast = scallop_compiler::parser::parse_str(
+    "
+    decl cause(Symbol).
+    decl alarm().
+    decl calls(Symbol).
+    0.1::cause(burglary). 0.2::cause(earthquake).
+    0.9::calls(john); 0.1::calls(mary).
+    alarm() :- cause(_).
+    ",
+  )
+  .unwrap();
+  let mut prog = EmptyProgram::<ProbProofs>::new();
+  prog.iteration_mut().add_program(ast).unwrap();
+  prog.run();
+
+  // Observe the alarm tuple by its atom
+  let iter = prog.iteration();
+  let query = |atom: &str| iter.query(&scallop_compiler::parser::parse_query(atom).unwrap().node.atom);
+  let alarm = query("alarm()").unwrap();
+  let mut evidence = Evidence::new();
+  evidence.observe(&alarm[0].tag, true);
+
+  let p_alarm = 1.0 - 0.9 * 0.8;
+  let burglary = query("cause(burglary)").unwrap();
+  assert_eq!(burglary.len(), 1);
+  let prob = conditional_prob(&iter.semiring_ctx, &burglary[0].tag, &evidence).unwrap();
+  assert!(approx_eq(prob, 0.1 / p_alarm));
+  assert_eq!(query("cause(_)").unwrap().len(), 2);
+
+  // The facts of a disjunction are registered as such
+  assert_eq!(query("calls(X)").unwrap().len(), 2);
+  assert_eq!(iter.semiring_ctx.disjunctions.len(), 1);
+  assert!(matches!(query("unknown(1)"), Err(error::RuntimeError::UndefinedVariable)));
+}
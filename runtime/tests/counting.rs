@@ -0,0 +1,161 @@
+use scallop_runtime::dataflows::*;
+use scallop_runtime::*;
+
+/// ``` datalog
+/// decl edge(Int, Int).
+/// decl two_hop(Int, Int).
+///
+/// two_hop(A, C) :- edge(A, B), edge(B, C).
+/// ```
+struct TwoHop<Tag: Semiring> {
+  iter: Iteration<Tag>,
+  edge: Variable<(usize, usize), Tag>,
+  edge_inv: Variable<(usize, usize), Tag>,
+  two_hop: Variable<(usize, usize), Tag>,
+}
+
+impl<Tag: Semiring> Program<Tag> for TwoHop<Tag> {
+  fn new() -> Self {
+    let mut iter = Iteration::new();
+    let edge = iter.variable::<(usize, usize)>();
+    let edge_inv = iter.variable::<(usize, usize)>();
+    let two_hop = iter.variable::<(usize, usize)>();
+    Self { iter, edge, edge_inv, two_hop }
+  }
+
+  fn iteration(&self) -> &Iteration<Tag> {
+    &self.iter
+  }
+
+  fn iteration_mut(&mut self) -> &mut Iteration<Tag> {
+    &mut self.iter
+  }
+
+  fn update(&self) {
+    self
+      .iter
+      .insert_dataflow(&self.edge_inv, self.edge.project(|(a, b)| (b, a)));
+    self.iter.insert_dataflow(
+      &self.two_hop,
+      self
+        .iter
+        .join(&self.edge_inv, &self.edge)
+        .project(|(_, a, c)| (a, c)),
+    );
+  }
+}
+
+/// ``` datalog
+/// decl edge(Int, Int).
+/// decl path(Int, Int).
+///
+/// path(A, B) :- edge(A, B).
+/// path(A, C) :- path(A, B), edge(B, C).
+/// ```
+struct Paths<Tag: Semiring> {
+  iter: Iteration<Tag>,
+  edge: Variable<(usize, usize), Tag>,
+  path: Variable<(usize, usize), Tag>,
+  path_inv: Variable<(usize, usize), Tag>,
+}
+
+impl<Tag: Semiring> Program<Tag> for Paths<Tag> {
+  fn new() -> Self {
+    let mut iter = Iteration::new();
+    let edge = iter.variable::<(usize, usize)>();
+    let path = iter.variable::<(usize, usize)>();
+    let path_inv = iter.variable::<(usize, usize)>();
+    Self { iter, edge, path, path_inv }
+  }
+
+  fn iteration(&self) -> &Iteration<Tag> {
+    &self.iter
+  }
+
+  fn iteration_mut(&mut self) -> &mut Iteration<Tag> {
+    &mut self.iter
+  }
+
+  fn update(&self) {
+    self.iter.insert_dataflow(&self.path, &self.edge);
+    self
+      .iter
+      .insert_dataflow(&self.path_inv, self.path.project(|(a, b)| (b, a)));
+    self.iter.insert_dataflow(
+      &self.path,
+      self
+        .iter
+        .join(&self.path_inv, &self.edge)
+        .project(|(_, a, c)| (a, c)),
+    );
+  }
+}
+
+fn count_paths<const MAX: usize>(edges: Vec<(usize, usize)>) -> Vec<((usize, usize), usize)> {
+  let mut prog = Paths::<DerivationCount<MAX>>::new();
+  prog.iter.insert(&prog.edge, edges);
+  prog.run();
+  let result = prog.iter.complete(&prog.path);
+  result.iter().map(|elem| (elem.tup, elem.tag.count)).collect()
+}
+
+#[test]
+fn test_counting_two_hop() {
+  let mut prog = TwoHop::<DerivationCount<1000>>::new();
+  prog
+    .iter
+    .insert(&prog.edge, vec![(0, 1), (0, 2), (1, 3), (2, 3), (3, 4)]);
+  prog.run();
+
+  let result = prog.iter.complete(&prog.two_hop);
+  let counts = result
+    .iter()
+    .map(|elem| (elem.tup, elem.tag.count))
+    .collect::<Vec<_>>();
+  assert_eq!(counts, vec![((0, 3), 2), ((1, 4), 1), ((2, 4), 1)]);
+}
+
+#[test]
+fn test_counting_saturation() {
+  let ctx = CountingSemiringContext;
+  let three = DerivationCount::<5>::new(3);
+  let sum = DerivationCount::add(&ctx, &three, &three);
+  assert_eq!(sum.count, 5);
+  assert!(sum.is_saturated());
+  let prod = DerivationCount::mult(&ctx, &three, &DerivationCount::zero(&ctx));
+  assert_eq!(prod.count, 0);
+  assert!(!prod.is_valid(&ctx));
+}
+
+#[test]
+fn test_counting_recursive_paths() {
+  // The two paths from 0 to 2 are derived in different rounds
+  let counts = count_paths::<1000>(vec![(0, 1), (1, 2), (0, 2)]);
+  assert_eq!(counts, vec![((0, 1), 1), ((0, 2), 2), ((1, 2), 1)]);
+
+  // The later derivations propagate further: 4 paths from 0 to 4
+  let counts = count_paths::<1000>(vec![(0, 1), (1, 2), (0, 2), (2, 3), (3, 4), (2, 4)]);
+  assert!(counts.contains(&((0, 4), 4)));
+  assert!(counts.contains(&((1, 4), 2)));
+}
+
+#[test]
+fn test_counting_recursive_saturation() {
+  // Infinitely many paths in a cycle saturate the counts
+  let counts = count_paths::<50>(vec![(0, 1), (1, 0)]);
+  assert!(counts.iter().all(|(_, count)| *count == 50));
+}
+
+#[test]
+fn test_counting_recursive_product() {
+  // Late derivations are propagated through the counting component of a product
+  let mut prog = Paths::<(bool, DerivationCount<1000>)>::new();
+  prog.iter.variable_handle(&prog.edge).insert(vec![(0, 1), (1, 2), (0, 2)]);
+  prog.run();
+  let result = prog.iter.complete(&prog.path);
+  let counts = result
+    .iter()
+    .map(|elem| (elem.tup, elem.tag.0, elem.tag.1.count))
+    .collect::<Vec<_>>();
+  assert_eq!(counts, vec![((0, 1), true, 1), ((0, 2), true, 2), ((1, 2), true, 1)]);
+}
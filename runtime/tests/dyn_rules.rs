@@ -0,0 +1,18 @@
+use scallop_compiler::error::CompileError;
+use scallop_runtime::error::RuntimeError;
+use scallop_runtime::*;
+
+#[test]
+fn test_named_arguments_in_dynamic_rules() {
+  let mut prog = EmptyProgram::<()>::new();
+  let pair = TupleType::Tuple(vec![TupleType::Integer, TupleType::Integer]);
+  prog.add_variable("edge", pair.clone()).unwrap();
+  prog.add_variable("path", pair).unwrap();
+
+  // The dynamic relations have no named fields
+  match prog.add_rule("path(A, B) :- edge(to: B, from: A).") {
+    Err(RuntimeError::CompileError(CompileError::UnknownField { field, .. })) => assert_eq!(field, "to"),
+    _ => panic!("Expected an unknown field"),
+  }
+  prog.add_rule("path(A, B) :- edge(A, B).").unwrap();
+}
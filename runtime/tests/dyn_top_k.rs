@@ -0,0 +1,38 @@
+use scallop_runtime::wmc::*;
+use scallop_runtime::*;
+
+#[test]
+fn test_dyn_top_k_keeps_k_proofs() {
+  for k in 1..4 {
+    let mut ctx = ProbProofContext::with_top_k(k);
+    let tags: Vec<DynTopKProbProofs> = vec![0.1, 0.4, 0.3, 0.2].into_iter().map(|p| ctx.base_tag(p)).collect();
+    let sum = tags
+      .iter()
+      .fold(DynTopKProbProofs::zero(&ctx), |acc, t| DynTopKProbProofs::add(&ctx, &acc, t));
+    assert_eq!(sum.proofs.len(), k);
+
+    // The kept proofs are the most probable ones
+    let mut probs = sum.proofs.iter().map(|p| p.prob).collect::<Vec<_>>();
+    probs.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    assert_eq!(probs, vec![0.4, 0.3, 0.2][..k].to_vec());
+  }
+}
+
+#[test]
+fn test_dyn_top_k_matches_static_top_k() {
+  let mut ctx = ProbProofContext::with_top_k(2);
+  let a: DynTopKProbProofs = ctx.base_tag(0.5);
+  let b: DynTopKProbProofs = ctx.base_tag(0.6);
+  let c: DynTopKProbProofs = ctx.base_tag(0.7);
+  let dyn_result = DynTopKProbProofs::add(&ctx, &DynTopKProbProofs::mult(&ctx, &a, &b), &c);
+
+  let mut ctx_2 = ProbProofContext::default();
+  let a: TopKProbProofs<2> = ctx_2.base_tag(0.5);
+  let b: TopKProbProofs<2> = ctx_2.base_tag(0.6);
+  let c: TopKProbProofs<2> = ctx_2.base_tag(0.7);
+  let static_result = TopKProbProofs::add(&ctx_2, &TopKProbProofs::mult(&ctx_2, &a, &b), &c);
+
+  let dyn_prob = DynTopKProbProofsWMC.wmc(&ctx, &dyn_result);
+  let static_prob = TopKProbProofsWMC::<2>.wmc(&ctx_2, &static_result);
+  assert!((dyn_prob - static_prob).abs() < 1e-6);
+}
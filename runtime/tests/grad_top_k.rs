@@ -0,0 +1,61 @@
+use scallop_runtime::wmc::*;
+use scallop_runtime::*;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+  (a - b).abs() < 1e-9
+}
+
+fn grad_of(grad: &[(usize, f64)], fact_id: usize) -> f64 {
+  grad.iter().find(|(id, _)| *id == fact_id).map(|(_, d)| *d).unwrap_or(0.0)
+}
+
+#[test]
+fn test_grad_top_k_disjunction() {
+  let mut ctx = ProbProofContext::default();
+  let a: GradTopKProbProofs<3> = ctx.base_tag(0.3);
+  let b: GradTopKProbProofs<3> = ctx.base_tag(0.6);
+  let a_or_b = GradTopKProbProofs::add(&ctx, &a, &b);
+
+  // Pr(a \/ b) = 1 - (1 - a)(1 - b)
+  let (prob, grad) = GradTopKProbProofsWMC::<3>.wmc(&ctx, &a_or_b);
+  assert!(approx_eq(prob, 0.72));
+  assert!(approx_eq(grad_of(&grad, 0), 0.4));
+  assert!(approx_eq(grad_of(&grad, 1), 0.7));
+}
+
+#[test]
+fn test_grad_top_k_conjunction() {
+  let mut ctx = ProbProofContext::default();
+  let a: GradTopKProbProofs<3> = ctx.base_tag(0.3);
+  let b: GradTopKProbProofs<3> = ctx.base_tag(0.6);
+  let a_and_b = GradTopKProbProofs::mult(&ctx, &a, &b);
+
+  // Pr(a /\ b) = ab
+  let (prob, grad) = GradTopKProbProofsWMC::<3>.wmc(&ctx, &a_and_b);
+  assert!(approx_eq(prob, 0.18));
+  assert!(approx_eq(grad_of(&grad, 0), 0.6));
+  assert!(approx_eq(grad_of(&grad, 1), 0.3));
+}
+
+#[test]
+fn test_grad_top_k_constants() {
+  let ctx = ProbProofContext::default();
+  let (prob, grad) = GradTopKProbProofsWMC::<3>.wmc(&ctx, &GradTopKProbProofs::zero(&ctx));
+  assert_eq!((prob, grad.len()), (0.0, 0));
+  let (prob, grad) = GradTopKProbProofsWMC::<3>.wmc(&ctx, &GradTopKProbProofs::one(&ctx));
+  assert_eq!((prob, grad.len()), (1.0, 0));
+}
+
+#[test]
+fn test_grad_top_k_difference() {
+  let mut ctx = ProbProofContext::default();
+  let a: GradTopKProbProofs<3> = ctx.base_tag(0.3);
+  let b: GradTopKProbProofs<3> = ctx.base_tag(0.6);
+  let a_minus_b = GradTopKProbProofs::minus(&ctx, &a, &b);
+
+  // Pr(a /\ ~b) = a(1 - b)
+  let (prob, grad) = GradTopKProbProofsWMC::<3>.wmc(&ctx, &a_minus_b);
+  assert!(approx_eq(prob, 0.12));
+  assert!(approx_eq(grad_of(&grad, 0), 0.4));
+  assert!(approx_eq(grad_of(&grad, 1), -0.3));
+}
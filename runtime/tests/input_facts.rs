@@ -0,0 +1,131 @@
+use scallop_runtime::dataflows::*;
+use scallop_runtime::*;
+
+/// ``` datalog
+/// decl edge(Int, Int).
+/// decl path(Int, Int).
+///
+/// 0.5::edge(0, 1).
+/// 0.8::edge(1, 2).
+/// 0.5::edge(0, 3).
+/// 0.5::edge(3, 2).
+///
+/// path(A, B) :- edge(A, B).
+/// path(A, C) :- edge(A, B), path(B, C).
+/// ```
+struct Path<Tag: Semiring<Context = ProbProofContext>> {
+  iter: Iteration<Tag>,
+  edge: Variable<(i64, i64), Tag>,
+  edge_inv: Variable<(i64, i64), Tag>,
+  path: Variable<(i64, i64), Tag>,
+}
+
+impl<Tag: Semiring<Context = ProbProofContext>> Program<Tag> for Path<Tag> {
+  fn new() -> Self {
+    let mut iter = Iteration::new();
+    let edge = iter.static_variable::<(i64, i64)>("edge");
+    let edge_inv = iter.variable::<(i64, i64)>();
+    let path = iter.static_variable::<(i64, i64)>("path");
+    Self { iter, edge, edge_inv, path }
+  }
+
+  fn iteration(&self) -> &Iteration<Tag> {
+    &self.iter
+  }
+
+  fn iteration_mut(&mut self) -> &mut Iteration<Tag> {
+    &mut self.iter
+  }
+
+  fn update(&self) {
+    self.iter.insert_dataflow(&self.path, &self.edge);
+    self.iter.insert_dataflow(&self.edge_inv, self.edge.project(|(a, b)| (b, a)));
+    self.iter.insert_dataflow(
+      &self.path,
+      self
+        .iter
+        .join(&self.edge_inv, &self.path)
+        .project(|(_, a, c)| (a, c)),
+    );
+  }
+}
+
+fn run_path<Tag>() -> Path<Tag>
+where
+  Tag: Semiring<Context = ProbProofContext>,
+  ProbProofContext: SemiringContext<Tag, Info = f64>,
+{
+  let mut prog = Path::<Tag>::new();
+  prog.iter.insert_with_tag_info(&prog.edge, vec![(0.5, (0, 1)), (0.8, (1, 2)), (0.5, (0, 3)), (0.5, (3, 2))]);
+  prog.run();
+  prog
+}
+
+#[test]
+fn test_input_fact_registry() {
+  let prog = run_path::<ProbProofs>();
+  let ctx = &prog.iter.semiring_ctx;
+  let fact = ctx.input_fact(1).unwrap();
+  assert_eq!(fact.relation.as_deref(), Some("edge"));
+  assert_eq!(fact.tuple, (1i64, 2i64).into());
+  assert_eq!(ctx.format_fact(1), "edge(1, 2)");
+  assert_eq!(ctx.format_fact(7), "#7");
+  assert_eq!(ctx.find_input_fact("edge", (0i64, 3i64)), Some(2));
+  assert_eq!(ctx.find_input_fact("path", (0i64, 3i64)), None);
+  assert_eq!(ctx.input_facts_of("edge").map(|(id, _)| id).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn test_input_fact_labels_and_proofs() {
+  let mut prog = run_path::<ProbProofs>();
+  let path = prog.iter.complete(&prog.path);
+  let ctx = &mut prog.iter.semiring_ctx;
+  assert!(ctx.set_label(2, "detour"));
+  assert!(!ctx.set_label(4, "missing"));
+  assert_eq!(ctx.find_label("detour"), Some(2));
+  assert_eq!(ctx.format_fact(2), "edge(0, 3) [detour]");
+
+  let elem = path.iter().find(|elem| elem.tup == (0, 2)).unwrap();
+  assert_eq!(elem.tag.format(ctx), "{edge(0, 1), edge(1, 2)} | {edge(0, 3) [detour], edge(3, 2)}");
+}
+
+#[test]
+fn test_input_fact_top_k_format() {
+  let prog = run_path::<DynTopKProbProofs>();
+  let path = prog.iter.complete(&prog.path);
+  let ctx = &prog.iter.semiring_ctx;
+  let elem = path.iter().find(|elem| elem.tup == (0, 2)).unwrap();
+  assert_eq!(elem.tag.format(ctx), "0.4::{edge(0, 1), edge(1, 2)} | 0.25::{edge(0, 3), edge(3, 2)}");
+}
+
+#[test]
+fn test_input_fact_ids_are_stable() {
+  let first = run_path::<ProbProofs>();
+  let second = run_path::<ProbProofs>();
+  assert_eq!(first.iter.semiring_ctx.input_facts, second.iter.semiring_ctx.input_facts);
+  let first_path = first.iter.complete(&first.path);
+  let second_path = second.iter.complete(&second.path);
+  let first_tags = first_path.iter().map(|elem| (elem.tup, elem.tag.clone())).collect::<Vec<_>>();
+  let second_tags = second_path.iter().map(|elem| (elem.tup, elem.tag.clone())).collect::<Vec<_>>();
+  assert_eq!(first_tags, second_tags);
+}
+
+#[test]
+fn test_input_facts_of_dynamic_relations() {
+  let mut prog = run_path::<ProbProofs>();
+  let ty = TupleType::Tuple(vec![TupleType::Integer, TupleType::Integer]);
+  let extra = prog.iter.dynamic_variable("extra", ty).unwrap();
+  extra.insert_with_context(&mut prog.iter.semiring_ctx, vec![(0.3, (5i64, 6i64).into())]);
+  let ctx = &mut prog.iter.semiring_ctx;
+  assert_eq!(ctx.find_input_fact("extra", (5i64, 6i64)), Some(4));
+
+  // Symbols are not confused with integers printed the same way
+  assert_eq!(ctx.find_input_fact("extra", (5usize, 6usize)), None);
+  assert_eq!(ctx.format_fact(4), "extra(5, 6)");
+
+  // Relabeled facts are only found by their new label
+  assert!(ctx.set_label(4, "first"));
+  assert!(ctx.set_label(4, "second"));
+  assert_eq!(ctx.find_label("first"), None);
+  assert_eq!(ctx.find_label("second"), Some(4));
+}
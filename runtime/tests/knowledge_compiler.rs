@@ -0,0 +1,80 @@
+use sdd::{bf, BooleanFormula, VTreeHeuristic};
+
+use scallop_runtime::wmc::*;
+use scallop_runtime::*;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+  (a - b).abs() < 1e-9
+}
+
+/// Tags of a small diamond-shaped graph with probabilistic edges, where the
+/// paths share edges
+fn diamond_tags(ctx: &mut ProbProofContext) -> Vec<ProbProofs> {
+  let edges: Vec<ProbProofs> = vec![0.3, 0.6, 0.8, 0.5, 0.9].into_iter().map(|p| ctx.base_tag(p)).collect();
+  let path_1 = ProbProofs::mult(ctx, &edges[0], &edges[1]);
+  let path_2 = ProbProofs::mult(ctx, &edges[2], &edges[3]);
+  let path_3 = ProbProofs::mult(ctx, &edges[0], &ProbProofs::mult(ctx, &edges[4], &edges[3]));
+  let reach = ProbProofs::add(ctx, &ProbProofs::add(ctx, &path_1, &path_2), &path_3);
+  let unreach = ProbProofs::minus(ctx, &ProbProofs::one(ctx), &reach);
+  vec![reach, unreach, path_3, ProbProofs::zero(ctx), ProbProofs::one(ctx)]
+}
+
+#[test]
+fn test_backends_agree() {
+  let mut ctx = ProbProofContext::default();
+  let tags = diamond_tags(&mut ctx);
+  let tag_refs = tags.iter().collect::<Vec<_>>();
+  let expected = ProbProofsWMC.wmc_batch(&ctx, &tag_refs);
+  let backends = vec![
+    CompiledWMC::<ProbProofs, _>::new(OBDDCompiler::new(OBDDVarOrder::Sorted)).wmc_batch(&ctx, &tag_refs),
+    CompiledWMC::<ProbProofs, _>::new(OBDDCompiler::new(OBDDVarOrder::FirstOccurrence)).wmc_batch(&ctx, &tag_refs),
+    CompiledWMC::<ProbProofs, _>::new(OBDDCompiler::new(OBDDVarOrder::Given(vec![3, 0]))).wmc_batch(&ctx, &tag_refs),
+    CompiledWMC::<ProbProofs, _>::new(SDDCompiler::new(VTreeHeuristic::CoOccurrence)).wmc_batch(&ctx, &tag_refs),
+  ];
+  for results in backends {
+    for (found, expected) in results.iter().zip(&expected) {
+      assert!(approx_eq(*found, *expected), "{} != {}", found, expected);
+    }
+  }
+  assert!(approx_eq(expected[0] + expected[1], 1.0));
+  assert!(approx_eq(expected[2], 0.3 * 0.9 * 0.5));
+}
+
+#[test]
+fn test_backends_agree_in_log_space() {
+  let mut ctx = ProbProofContext::with_log_space();
+  let tags = diamond_tags(&mut ctx);
+  for tag in &tags {
+    let expected = ProbProofsWMC.wmc(&ctx, tag);
+    let found = CompiledWMC::<ProbProofs, _>::new(OBDDCompiler::default()).wmc(&ctx, tag);
+    assert!(expected == found || approx_eq(expected, found));
+  }
+}
+
+#[test]
+fn test_obdd_is_reduced() {
+  // Both formulas are equivalent, so they share a single root node
+  let forms = vec![
+    (bf(0) & bf(1)) | (bf(0) & bf(2)),
+    bf(0) & (bf(1) | bf(2)),
+    bf(3) | !bf(3),
+  ];
+  let obdd = OBDDCompiler::new(OBDDVarOrder::Sorted).compile(&forms);
+  assert_eq!(obdd.num_roots(), 3);
+  assert_eq!(obdd.roots()[0], obdd.roots()[1]);
+  assert!(matches!(obdd.nodes()[obdd.roots()[2]], OBDDNode::True));
+
+  // Three decision nodes on top of the two constants
+  assert_eq!(obdd.size(), 5);
+}
+
+#[test]
+fn test_obdd_variable_order_matters() {
+  // (x0 & y0) | (x1 & y1) | (x2 & y2) is linear when the pairs are adjacent
+  // and exponential when all the x's come first
+  let form: BooleanFormula = (0..3).map(|i| bf(i) & bf(i + 3)).fold(BooleanFormula::False, |acc, p| acc | p);
+  let forms = vec![form];
+  let interleaved = OBDDCompiler::new(OBDDVarOrder::FirstOccurrence).compile(&forms);
+  let separated = OBDDCompiler::new(OBDDVarOrder::Sorted).compile(&forms);
+  assert!(interleaved.size() < separated.size());
+}
@@ -0,0 +1,54 @@
+use scallop_runtime::wmc::*;
+use scallop_runtime::*;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+  (a - b).abs() < 1e-6
+}
+
+/// The conjunction of `n` fresh facts each of probability `prob`
+fn long_proof(ctx: &mut ProbProofContext, n: usize, prob: f64) -> DynTopKProbProofs {
+  (0..n).fold(DynTopKProbProofs::one(ctx), |acc, _| {
+    let fact: DynTopKProbProofs = ctx.base_tag(prob);
+    DynTopKProbProofs::mult(ctx, &acc, &fact)
+  })
+}
+
+#[test]
+fn test_log_space_long_proofs_do_not_underflow() {
+  let mut ctx = ProbProofContext::with_log_space();
+  ctx.top_k = 1;
+
+  // Both proofs have a probability below the smallest positive f64
+  let likely = long_proof(&mut ctx, 200, 0.02);
+  let unlikely = long_proof(&mut ctx, 200, 0.01);
+  let sum = DynTopKProbProofs::add(&ctx, &unlikely, &likely);
+
+  assert_eq!(sum.proofs.len(), 1);
+  let kept = sum.proofs.peek().unwrap();
+  assert_eq!(kept.facts, likely.proofs.peek().unwrap().facts);
+  assert!(approx_eq(kept.prob, 200.0 * 0.02f64.ln()));
+  assert!(approx_eq(DynTopKProbProofsWMC.wmc(&ctx, &sum), 200.0 * 0.02f64.ln()));
+}
+
+#[test]
+fn test_log_space_matches_linear_space() {
+  let program = |ctx: &mut ProbProofContext| {
+    let a: TopKProbProofs<3> = ctx.base_tag(0.3);
+    let b: TopKProbProofs<3> = ctx.base_tag(0.6);
+    let c: TopKProbProofs<3> = ctx.base_tag(0.5);
+    let a_and_b = TopKProbProofs::mult(ctx, &a, &b);
+    let result = TopKProbProofs::add(ctx, &a_and_b, &c);
+    TopKProbProofs::minus(ctx, &result, &a)
+  };
+
+  let mut ctx = ProbProofContext::default();
+  let linear = program(&mut ctx);
+  let linear_prob = TopKProbProofsWMC::<3>.wmc(&ctx, &linear);
+
+  let mut log_ctx = ProbProofContext::with_log_space();
+  let log = program(&mut log_ctx);
+  let log_prob = TopKProbProofsWMC::<3>.wmc(&log_ctx, &log);
+
+  assert!(approx_eq(linear_prob, 0.5 * 0.7));
+  assert!(approx_eq(log_prob.exp(), linear_prob));
+}
@@ -0,0 +1,111 @@
+use scallop_runtime::dataflows::*;
+use scallop_runtime::wmc::*;
+use scallop_runtime::*;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+  (a - b).abs() < 1e-9
+}
+
+/// ``` datalog
+/// decl digit(Int, Int).
+/// decl sum(Int, Int, Int).
+///
+/// sum(A, B, D1 + D2) :- digit(A, D1), digit(B, D2).
+/// ```
+struct Sum2<Tag: Semiring<Context = ProbProofContext>> {
+  iter: Iteration<Tag>,
+  digit: Variable<(i64, i64), Tag>,
+  sum: Variable<((i64, i64), i64), Tag>,
+}
+
+impl<Tag: Semiring<Context = ProbProofContext>> Program<Tag> for Sum2<Tag> {
+  fn new() -> Self {
+    let mut iter = Iteration::new();
+    let digit = iter.variable::<(i64, i64)>();
+    let sum = iter.variable::<((i64, i64), i64)>();
+    Self { iter, digit, sum }
+  }
+
+  fn iteration(&self) -> &Iteration<Tag> {
+    &self.iter
+  }
+
+  fn iteration_mut(&mut self) -> &mut Iteration<Tag> {
+    &mut self.iter
+  }
+
+  fn update(&self) {
+    self.iter.insert_dataflow(
+      &self.sum,
+      self
+        .iter
+        .product(self.digit.find(0), self.digit.find(1))
+        .project(|((o1, d1), (o2, d2))| ((o1, o2), d1 + d2)),
+    );
+  }
+}
+
+#[test]
+fn test_mpe_sum2() {
+  let mut prog = Sum2::<ProbProofs>::new();
+  prog.iter.insert_disjunction(&prog.digit, vec![(0.3, (0, 0)), (0.7, (0, 1))]);
+  prog.iter.insert_disjunction(&prog.digit, vec![(0.6, (1, 0)), (0.4, (1, 1))]);
+  prog.run();
+
+  // sum = 1 is explained either by (0, 1) or by (1, 0); the latter is more
+  // probable, with digit(0, 1) and digit(1, 0)
+  let result = prog.iter.complete(&prog.sum);
+  let elem = result.iter().find(|elem| elem.tup.1 == 1).unwrap();
+  let ctx = &prog.iter.semiring_ctx;
+  let mpe = most_probable_explanation(ctx, &elem.tag).unwrap();
+  assert_eq!(mpe.true_facts(), vec![1, 2]);
+  assert!(approx_eq(mpe.prob, 0.7 * 0.6 * (1.0 - 0.3) * (1.0 - 0.4)));
+  assert_eq!(
+    mpe.tuples(ctx),
+    vec![
+      ("(0, 0)".to_string(), false),
+      ("(0, 1)".to_string(), true),
+      ("(1, 0)".to_string(), true),
+      ("(1, 1)".to_string(), false),
+    ]
+  );
+}
+
+#[test]
+fn test_mpe_respects_disjunctions() {
+  let mut ctx = ProbProofContext::default();
+  let a: ProbProofs = ctx.base_tag(0.8);
+  let b: ProbProofs = ctx.base_tag(0.15);
+  let c: ProbProofs = ctx.base_tag(0.9);
+  let d: ProbProofs = ctx.base_tag(0.01);
+  ctx.disjunctions.push(vec![0, 1].into_iter().collect());
+
+  // (b /\ c) \/ (a /\ d); a would be true along with b and c if it were not
+  // in the same disjunction as b
+  let tag = ProbProofs::add(&ctx, &ProbProofs::mult(&ctx, &b, &c), &ProbProofs::mult(&ctx, &a, &d));
+  let mpe = most_probable_explanation(&ctx, &tag).unwrap();
+  assert_eq!(mpe.facts, vec![(0, false), (1, true), (2, true), (3, false)]);
+  assert!(approx_eq(mpe.prob, 0.2 * 0.15 * 0.9 * 0.99));
+}
+
+#[test]
+fn test_mpe_with_evidence() {
+  let mut ctx = ProbProofContext::default();
+  let burglary: ProbProofs = ctx.base_tag(0.1);
+  let earthquake: ProbProofs = ctx.base_tag(0.2);
+  let alarm = ProbProofs::add(&ctx, &burglary, &earthquake);
+
+  // The most probable explanation of the alarm is the earthquake alone
+  let mpe = most_probable_explanation(&ctx, &alarm).unwrap();
+  assert_eq!(mpe.facts, vec![(0, false), (1, true)]);
+
+  // Unless the earthquake is ruled out
+  let mut evidence = Evidence::new();
+  evidence.observe(&earthquake, false);
+  let mpe = most_probable_explanation_given(&ctx, &alarm, &evidence).unwrap();
+  assert_eq!(mpe.facts, vec![(0, true), (1, false)]);
+  assert!(approx_eq(mpe.prob, 0.1 * 0.8));
+
+  // Impossible
+  assert_eq!(most_probable_explanation(&ctx, &ProbProofs::zero(&ctx)), None);
+}
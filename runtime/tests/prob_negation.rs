@@ -0,0 +1,57 @@
+use scallop_runtime::wmc::*;
+use scallop_runtime::*;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+  (a - b).abs() < 1e-5
+}
+
+#[test]
+fn test_prob_proofs_minus() {
+  let mut ctx = ProbProofContext::default();
+  let a: ProbProofs = ctx.base_tag(0.3);
+  let b: ProbProofs = ctx.base_tag(0.6);
+  let c: ProbProofs = ctx.base_tag(0.5);
+
+  // a - (b \/ c) = a /\ ~b /\ ~c
+  let b_or_c = ProbProofs::add(&ctx, &b, &c);
+  let result = ProbProofs::minus(&ctx, &a, &b_or_c);
+  assert_eq!(result.proofs.len(), 1);
+  let proof = result.proofs.iter().next().unwrap();
+  assert_eq!(proof.facts.iter().cloned().collect::<Vec<_>>(), vec![0]);
+  assert_eq!(proof.neg_facts.iter().cloned().collect::<Vec<_>>(), vec![1, 2]);
+  assert!(approx_eq(ProbProofsWMC.wmc(&ctx, &result), 0.3 * 0.4 * 0.5));
+
+  // a - a = false
+  let empty = ProbProofs::minus(&ctx, &a, &a);
+  assert!(!empty.is_valid(&ctx));
+  assert!(approx_eq(ProbProofsWMC.wmc(&ctx, &empty), 0.0));
+}
+
+#[test]
+fn test_prob_proofs_minus_conjunction() {
+  let mut ctx = ProbProofContext::default();
+  let a: ProbProofs = ctx.base_tag(0.3);
+  let b: ProbProofs = ctx.base_tag(0.6);
+  let c: ProbProofs = ctx.base_tag(0.5);
+
+  // c - (a /\ b) = (c /\ ~a) \/ (c /\ ~b)
+  let a_and_b = ProbProofs::mult(&ctx, &a, &b);
+  let result = ProbProofs::minus(&ctx, &c, &a_and_b);
+  assert_eq!(result.proofs.len(), 2);
+  assert!(approx_eq(ProbProofsWMC.wmc(&ctx, &result), 0.5 * (1.0 - 0.3 * 0.6)));
+}
+
+#[test]
+fn test_top_k_prob_proofs_minus() {
+  let mut ctx = ProbProofContext::default();
+  let a: TopKProbProofs<3> = ctx.base_tag(0.3);
+  let b: TopKProbProofs<3> = ctx.base_tag(0.6);
+  let c: TopKProbProofs<3> = ctx.base_tag(0.5);
+
+  let b_or_c = TopKProbProofs::add(&ctx, &b, &c);
+  let result = TopKProbProofs::minus(&ctx, &a, &b_or_c);
+  assert_eq!(result.proofs.len(), 1);
+  let proof = result.proofs.peek().unwrap();
+  assert!(approx_eq(proof.prob, 0.3 * 0.4 * 0.5));
+  assert!(approx_eq(TopKProbProofsWMC::<3>.wmc(&ctx, &result), 0.3 * 0.4 * 0.5));
+}
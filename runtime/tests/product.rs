@@ -0,0 +1,51 @@
+use scallop_runtime::*;
+
+type BoolCount = (bool, DerivationCount<10>);
+
+#[test]
+fn test_product_semiring_ops() {
+  let ctx = <BoolCount as Semiring>::Context::default();
+  let one = BoolCount::one(&ctx);
+  let zero = BoolCount::zero(&ctx);
+  let two = BoolCount::add(&ctx, &one, &one);
+  assert_eq!(two, (true, DerivationCount::new(2)));
+  assert_eq!(BoolCount::mult(&ctx, &two, &two), (true, DerivationCount::new(4)));
+  assert_eq!(BoolCount::mult(&ctx, &two, &zero), zero);
+  assert!(!zero.is_valid(&ctx));
+}
+
+#[test]
+fn test_product_semiring_base_tag() {
+  let mut ctx = <(TopKProbProofs<3>, bool) as Semiring>::Context::default();
+  let (proofs, flag): (TopKProbProofs<3>, bool) = ctx.base_tag((0.5, ()));
+  assert_eq!(ctx.0.prob_table[&0], 0.5);
+  assert_eq!(proofs.proofs.len(), 1);
+  assert!(flag);
+}
+
+#[test]
+fn test_product_semiring_relation_merge() {
+  let ctx = <BoolCount as Semiring>::Context::default();
+  let one = BoolCount::one(&ctx);
+  let elements = vec![
+    Element { tup: (0, 1), tag: one },
+    Element { tup: (0, 1), tag: one },
+    Element { tup: (1, 2), tag: one },
+  ];
+  let relation = Relation::from_vec(elements, &ctx);
+  let counts = relation
+    .iter()
+    .map(|elem| (elem.tup, elem.tag.1.count))
+    .collect::<Vec<_>>();
+  assert_eq!(counts, vec![((0, 1), 2), ((1, 2), 1)]);
+}
+
+#[test]
+fn test_product_semiring_insert() {
+  let mut iter = Iteration::<BoolCount>::new();
+  let var = iter.variable::<(usize, usize)>();
+  iter.insert(&var, vec![(0, 1), (0, 1)]);
+  while iter.changed() {}
+  let counts = iter.complete(&var).iter().map(|elem| (elem.tup, elem.tag)).collect::<Vec<_>>();
+  assert_eq!(counts, vec![((0, 1), (true, DerivationCount::new(2)))]);
+}
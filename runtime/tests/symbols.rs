@@ -0,0 +1,87 @@
+// The compiled program imports and declares more than this test uses
+#![allow(unused_imports, dead_code)]
+
+use scallop_runtime::*;
+
+// The program compiled from `symbols/zoo.scl`; the compiler output is checked
+// against it in `test_symbols_in_compiled_programs`
+include!("symbols/zoo.rs");
+
+use scallop_prog::Prog;
+
+/// ``` datalog
+/// decl habitat(Symbol, Symbol).
+/// ```
+struct Zoo {
+  iter: Iteration<()>,
+  habitat: Variable<(usize, usize), ()>,
+}
+
+impl Program<()> for Zoo {
+  fn new() -> Self {
+    let mut iter = Iteration::new();
+    let habitat = iter.static_variable::<(usize, usize)>("habitat");
+    Self { iter, habitat }
+  }
+
+  fn iteration(&self) -> &Iteration<()> {
+    &self.iter
+  }
+
+  fn iteration_mut(&mut self) -> &mut Iteration<()> {
+    &mut self.iter
+  }
+}
+
+#[test]
+fn test_symbols_in_dynamic_rules() {
+  let mut prog = Zoo::new();
+  let zebra = symbols::intern("zebra");
+  let savanna = symbols::intern("savanna");
+  let reef = symbols::intern("reef");
+  prog.iter.insert_ground(&prog.habitat, vec![(zebra, savanna), (savanna, reef)]);
+  prog
+    .add_variable("kind", TupleType::Tuple(vec![TupleType::Symbol, TupleType::Symbol]))
+    .unwrap();
+  prog.add_rule("kind(A, mammal) :- habitat(A, savanna).").unwrap();
+  prog.run();
+
+  // The symbols of the rule are interned in the same table
+  let mammal = symbols::symbol_id("mammal").unwrap();
+  let kind = prog.get_variable("kind").unwrap().complete();
+  let tuples = kind.iter().map(|elem| format!("{:?}", elem.tup)).collect::<Vec<_>>();
+  assert_eq!(tuples, vec!["(zebra, mammal)".to_string()]);
+  assert_eq!(symbols::format_tuple(&(zebra, mammal)), "(zebra, mammal)");
+}
+
+#[test]
+fn test_symbols_in_compiled_programs() {
+  let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/symbols/zoo.scl");
+  let compiled = scallop_compiler::compile_file_to_rs(path).unwrap().to_string();
+  assert_eq!(compiled, include_str!("symbols/zoo.rs").trim_end());
+
+  // The symbols interned before do not conflict with the ones of the program,
+  // nor does the integer used as a symbol
+  symbols::intern("aardvark");
+  let mut prog = Prog::<()>::new();
+  prog.run();
+  let kind = prog.kind().complete();
+  let tuples = kind.iter().map(|elem| symbols::format_tuple(&elem.tup)).collect::<Vec<_>>();
+  assert_eq!(tuples, vec!["(#3, mammal)".to_string(), "(zebra, mammal)".to_string()]);
+  assert!(symbols::symbol_id("zebra").unwrap() >= symbols::FIRST_INTERNED_ID);
+}
+
+#[test]
+fn test_registered_symbols() {
+  assert!(symbols::register_symbols(&[(1000, "okapi"), (1001, "tapir")]).is_empty());
+  assert_eq!(symbols::symbol_id("tapir"), Some(1001));
+  assert_eq!(symbols::format_symbol(1000), "okapi");
+
+  // Registering the same symbols again is fine; conflicting ones are returned
+  assert!(symbols::register_symbols(&[(1000, "okapi")]).is_empty());
+  assert_eq!(symbols::register_symbols(&[(1000, "tapir"), (1002, "quagga")]), vec![(1000, "tapir")]);
+  assert_eq!(symbols::symbol_id("quagga"), Some(1002));
+
+  // Ids without a symbol are printed distinctly
+  assert_eq!(symbols::format_symbol(999_999), "#999999");
+}
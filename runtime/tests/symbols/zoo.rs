@@ -0,0 +1 @@
+mod scallop_prog { pub use scallop_runtime :: * ; use scallop_runtime :: dataflows :: * ; use scallop_runtime :: interpreter :: * ; pub struct Prog < Tag > where Tag : Semiring { iter : Iteration < Tag > , habitat : Variable < (usize , usize) , Tag > , kind : Variable < (usize , usize) , Tag > } impl < Tag > Prog < Tag > where Tag : Semiring { pub fn habitat < 'a > (& 'a mut self) -> VariableHandle < 'a , (usize , usize) , Tag > { self . iter . variable_handle (& self . habitat) } pub fn kind < 'a > (& 'a mut self) -> VariableHandle < 'a , (usize , usize) , Tag > { self . iter . variable_handle (& self . kind) } } impl < Tag > Program < Tag > for Prog < Tag > where Tag : Semiring { fn new () -> Self { let mut iter = Iteration :: new () ; let habitat = iter . static_variable :: < (usize , usize) > ("habitat") ; let kind = iter . static_variable :: < (usize , usize) > ("kind") ; Self { iter , habitat , kind } } fn iteration (& self) -> & Iteration < Tag > { & self . iter } fn iteration_mut (& mut self) -> & mut Iteration < Tag > { & mut self . iter } fn initialize (& mut self) { self . iter . insert_ground (& self . habitat , [(symbol_zebra () , symbol_savanna ()) , (symbol_clownfish () , symbol_reef ()) , (3usize , symbol_savanna ())] . to_vec ()) ; } fn update (& self) { self . iter . insert_dataflow (& self . kind , self . habitat . filter (| arg | arg . 1 == symbol_savanna ()) . project (| arg | arg . 0) . project (| arg | (arg , symbol_mammal ()))) ; } } fn symbol_clownfish () -> usize { static ID : std :: sync :: OnceLock < usize > = std :: sync :: OnceLock :: new () ; * ID . get_or_init (|| scallop_runtime :: symbols :: intern ("clownfish")) } fn symbol_mammal () -> usize { static ID : std :: sync :: OnceLock < usize > = std :: sync :: OnceLock :: new () ; * ID . get_or_init (|| scallop_runtime :: symbols :: intern ("mammal")) } fn symbol_reef () -> usize { static ID : std :: sync :: OnceLock < usize > = std :: sync :: OnceLock :: new () ; * ID . get_or_init (|| scallop_runtime :: symbols :: intern ("reef")) } fn symbol_savanna () -> usize { static ID : std :: sync :: OnceLock < usize > = std :: sync :: OnceLock :: new () ; * ID . get_or_init (|| scallop_runtime :: symbols :: intern ("savanna")) } fn symbol_zebra () -> usize { static ID : std :: sync :: OnceLock < usize > = std :: sync :: OnceLock :: new () ; * ID . get_or_init (|| scallop_runtime :: symbols :: intern ("zebra")) } } use scallop_prog :: * ;
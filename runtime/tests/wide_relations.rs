@@ -0,0 +1,86 @@
+// The compiled program imports more than it uses and spells out its wide
+// tuple types
+#![allow(unused_imports, clippy::type_complexity)]
+
+use scallop_runtime::interpreter::*;
+use scallop_runtime::*;
+
+/// The columns of a relation wider than the widest flat tuple are split in
+/// nested halves
+type Wide = (((i64, i64, i64, i64, i64, i64, i64, i64), (i64, i64, i64, i64, i64, i64, i64, i64)), ((i64, i64, i64, i64, i64, i64, i64, i64), (i64, i64, i64, i64, i64, i64, i64, i64)));
+
+// The program compiled from `wide_relations/wide.scl`; the compiler output is
+// checked against it in `test_compiled_wide_program`
+include!("wide_relations/prog.rs");
+
+use scallop_prog::Prog;
+
+/// The variables `C<i>` of the columns, separated by commas
+fn vars(columns: impl Iterator<Item = usize>) -> String {
+  columns.map(|i| format!("C{}", i)).collect::<Vec<_>>().join(", ")
+}
+
+fn wide_tuple(start: i64) -> Wide {
+  let c = |i: i64| start + i;
+  (
+    (
+      (c(0), c(1), c(2), c(3), c(4), c(5), c(6), c(7)),
+      (c(8), c(9), c(10), c(11), c(12), c(13), c(14), c(15)),
+    ),
+    (
+      (c(16), c(17), c(18), c(19), c(20), c(21), c(22), c(23)),
+      (c(24), c(25), c(26), c(27), c(28), c(29), c(30), c(31)),
+    ),
+  )
+}
+
+#[test]
+fn test_tuple_layout() {
+  let columns = vec![TupleType::Integer; 32];
+  let ty = TupleType::from_columns(columns.clone());
+  assert_eq!(ty, <TupleType as FromType<Wide>>::from_type());
+  assert_eq!(ty.columns(), Some(columns));
+  assert_eq!(ty.type_of(&TupleAccessor::from_indices(&[1, 1, 7])), &TupleType::Integer);
+
+  let tup: DynTuple = wide_tuple(0).into();
+  assert_eq!(tup, DynTuple::from_columns((0..32i64).map(DynTuple::from).collect()));
+  assert_eq!(tup[TupleAccessor::from_indices(&[1, 0, 3])], DynTuple::Integer(19));
+}
+
+#[test]
+fn test_compiled_wide_program() {
+  let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/wide_relations/wide.scl");
+  let compiled = scallop_compiler::compile_file_to_rs(path).unwrap().to_string();
+  assert_eq!(compiled, include_str!("wide_relations/prog.rs").trim_end());
+}
+
+#[test]
+fn test_wide_relations_in_static_and_dynamic_rules() {
+  let mut prog = Prog::<()>::new();
+  prog.wide().insert_ground(vec![wide_tuple(0), wide_tuple(100)]);
+
+  // A dynamic relation as wide as the static one, derived from and joined with it
+  prog.add_variable("rotated", TupleType::Tuple(vec![TupleType::Integer; 32])).unwrap();
+  prog.add_variable("middle", TupleType::Tuple(vec![TupleType::Integer; 3])).unwrap();
+  let rotated = format!("rotated({}, C0) :- wide({}).", vars(1..32), vars(0..32));
+  prog.add_rule(&rotated).unwrap();
+  let middle = format!("middle(C31, C15, C30) :- ends(C31, C30), rotated({}).", vars(0..32));
+  prog.add_rule(&middle).unwrap();
+  prog.run();
+
+  let ends = prog.ends().complete();
+  let ends = ends.iter().map(|elem| elem.tup).collect::<Vec<_>>();
+  assert_eq!(ends, vec![(0, 31), (100, 131)]);
+  let copy = prog.copy().complete();
+  let copy = copy.iter().map(|elem| elem.tup).collect::<Vec<_>>();
+  assert_eq!(copy, vec![wide_tuple(0), wide_tuple(100)]);
+
+  let rotated = prog.get_variable("rotated").unwrap().complete();
+  let rotated = rotated.iter().map(|elem| elem.tup.columns()).collect::<Vec<_>>();
+  let expected = (1..32i64).chain(0..1).map(DynTuple::from).collect::<Vec<_>>();
+  assert_eq!(rotated[0], expected);
+
+  let middle = prog.get_variable("middle").unwrap().complete();
+  let middle = middle.iter().map(|elem| format!("{:?}", elem.tup)).collect::<Vec<_>>();
+  assert_eq!(middle, vec!["(0, 16, 31)".to_string(), "(100, 116, 131)".to_string()]);
+}
@@ -0,0 +1 @@
+mod scallop_prog { pub use scallop_runtime :: * ; use scallop_runtime :: dataflows :: * ; use scallop_runtime :: interpreter :: * ; pub struct Prog < Tag > where Tag : Semiring { iter : Iteration < Tag > , wide : Variable < (((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)) , ((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64))) , Tag > , ends : Variable < (i64 , i64) , Tag > , copy : Variable < (((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)) , ((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64))) , Tag > , _tmp_0 : Variable < ((i64 , i64) , (((i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)) , ((i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)))) , Tag > } impl < Tag > Prog < Tag > where Tag : Semiring { pub fn wide < 'a > (& 'a mut self) -> VariableHandle < 'a , (((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)) , ((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64))) , Tag > { self . iter . variable_handle (& self . wide) } pub fn ends < 'a > (& 'a mut self) -> VariableHandle < 'a , (i64 , i64) , Tag > { self . iter . variable_handle (& self . ends) } pub fn copy < 'a > (& 'a mut self) -> VariableHandle < 'a , (((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)) , ((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64))) , Tag > { self . iter . variable_handle (& self . copy) } } impl < Tag > Program < Tag > for Prog < Tag > where Tag : Semiring { fn new () -> Self { let mut iter = Iteration :: new () ; let wide = iter . static_variable :: < (((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)) , ((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64))) > ("wide") ; let ends = iter . static_variable :: < (i64 , i64) > ("ends") ; let copy = iter . static_variable :: < (((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)) , ((i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64))) > ("copy") ; let _tmp_0 = iter . static_variable :: < ((i64 , i64) , (((i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)) , ((i64 , i64 , i64 , i64 , i64 , i64 , i64) , (i64 , i64 , i64 , i64 , i64 , i64 , i64 , i64)))) > ("_tmp_0") ; Self { iter , wide , ends , copy , _tmp_0 } } fn iteration (& self) -> & Iteration < Tag > { & self . iter } fn iteration_mut (& mut self) -> & mut Iteration < Tag > { & mut self . iter } fn initialize (& mut self) { } fn update (& self) { self . iter . insert_dataflow (& self . ends , self . wide . project (| arg | (arg . 0 . 0 . 0 , arg . 1 . 1 . 7))) ; self . iter . insert_dataflow (& self . _tmp_0 , self . wide . project (| arg | ((arg . 0 . 0 . 0 , arg . 1 . 1 . 7) , (((arg . 0 . 0 . 1 , arg . 0 . 0 . 2 , arg . 0 . 0 . 3 , arg . 0 . 0 . 4 , arg . 0 . 0 . 5 , arg . 0 . 0 . 6 , arg . 0 . 0 . 7) , (arg . 0 . 1 . 0 , arg . 0 . 1 . 1 , arg . 0 . 1 . 2 , arg . 0 . 1 . 3 , arg . 0 . 1 . 4 , arg . 0 . 1 . 5 , arg . 0 . 1 . 6 , arg . 0 . 1 . 7)) , ((arg . 1 . 0 . 0 , arg . 1 . 0 . 1 , arg . 1 . 0 . 2 , arg . 1 . 0 . 3 , arg . 1 . 0 . 4 , arg . 1 . 0 . 5 , arg . 1 . 0 . 6) , (arg . 1 . 0 . 7 , arg . 1 . 1 . 0 , arg . 1 . 1 . 1 , arg . 1 . 1 . 2 , arg . 1 . 1 . 3 , arg . 1 . 1 . 4 , arg . 1 . 1 . 5 , arg . 1 . 1 . 6)))))) ; self . iter . insert_dataflow (& self . copy , self . iter . join (self . ends . project (| arg | (arg , ())) , & self . _tmp_0) . project (| arg | (((arg . 0 . 0 , arg . 2 . 0 . 0 . 0 , arg . 2 . 0 . 0 . 1 , arg . 2 . 0 . 0 . 2 , arg . 2 . 0 . 0 . 3 , arg . 2 . 0 . 0 . 4 , arg . 2 . 0 . 0 . 5 , arg . 2 . 0 . 0 . 6) , (arg . 2 . 0 . 1 . 0 , arg . 2 . 0 . 1 . 1 , arg . 2 . 0 . 1 . 2 , arg . 2 . 0 . 1 . 3 , arg . 2 . 0 . 1 . 4 , arg . 2 . 0 . 1 . 5 , arg . 2 . 0 . 1 . 6 , arg . 2 . 0 . 1 . 7)) , ((arg . 2 . 1 . 0 . 0 , arg . 2 . 1 . 0 . 1 , arg . 2 . 1 . 0 . 2 , arg . 2 . 1 . 0 . 3 , arg . 2 . 1 . 0 . 4 , arg . 2 . 1 . 0 . 5 , arg . 2 . 1 . 0 . 6 , arg . 2 . 1 . 1 . 0) , (arg . 2 . 1 . 1 . 1 , arg . 2 . 1 . 1 . 2 , arg . 2 . 1 . 1 . 3 , arg . 2 . 1 . 1 . 4 , arg . 2 . 1 . 1 . 5 , arg . 2 . 1 . 1 . 6 , arg . 2 . 1 . 1 . 7 , arg . 0 . 1))))) ; } } } use scallop_prog :: * ;
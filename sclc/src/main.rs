@@ -9,7 +9,7 @@ use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use structopt::StructOpt;
 
-use scallop_compiler::{ast2ram, ast_analysis, ast_transform, error::*, parser, ram, ram2rs, options};
+use scallop_compiler::{ast, ast2ram, ast_analysis, ast_transform, error::*, explain, parser, ram, ram2rs, options};
 
 #[derive(Debug)]
 enum EmitType {
@@ -34,6 +34,7 @@ enum SemiringType {
   Proofs,
   TopKProofs,
   Boolean,
+  Counting,
   Empty,
 }
 
@@ -44,6 +45,7 @@ impl FromStr for SemiringType {
     match emit {
       "empty" => Ok(Self::Empty),
       "boolean" => Ok(Self::Boolean),
+      "counting" => Ok(Self::Counting),
       "proofs" => Ok(Self::Proofs),
       "top-k-proofs" => Ok(Self::TopKProofs),
       _ => Err(CompileError::UnknownSemiringType),
@@ -87,11 +89,36 @@ struct Options {
   #[structopt(short = "k", default_value = "3")]
   pub k: usize,
 
+  #[structopt(long, default_value = "1000000")]
+  pub max_count: usize,
+
   #[structopt(long)]
   pub opt_level: Option<usize>,
 
   #[structopt(long)]
   pub no_remove_rs: bool,
+
+  /// Print the derivation trees of the tuples matching the atom, e.g.
+  /// `path(0, X)`, instead of compiling
+  #[structopt(long, value_name = "ATOM")]
+  pub explain: Option<String>,
+
+  /// Print the explanations as a Graphviz graph
+  #[structopt(long)]
+  pub explain_dot: bool,
+
+  /// Print why no tuple matching the atom is derived, instead of compiling
+  #[structopt(long, value_name = "ATOM")]
+  pub why_not: Option<String>,
+
+  /// Observe that a tuple holds, e.g. `alarm()`; the probabilistic outputs
+  /// are then conditioned on all the evidence
+  #[structopt(long, value_name = "ATOM")]
+  pub observe: Vec<String>,
+
+  /// Observe that a tuple does not hold
+  #[structopt(long, value_name = "ATOM")]
+  pub observe_not: Vec<String>,
 }
 
 fn main() -> Result<(), CompileError> {
@@ -103,6 +130,17 @@ fn main() -> Result<(), CompileError> {
     println!("{:?}", ast_program);
   }
 
+  // Explanations are computed on the rules as written
+  if let Some(atom) = &options.explain {
+    return print_explanations(&ast_program, atom, options.explain_dot);
+  }
+  if let Some(atom) = &options.why_not {
+    let query = parser::parse_query(atom)?;
+    let explainer = explain::Explainer::new(&ast_program)?;
+    print!("{}", explainer.why_not(&query.node.atom));
+    return Ok(());
+  }
+
   // Then analyze and do transformation (optimizations)
   let comp_opts = options::CompileOptions::default();
   let mut analysis_result = ast_analysis::analyze(&ast_program, &comp_opts)?;
@@ -132,65 +170,213 @@ fn main() -> Result<(), CompileError> {
   Ok(())
 }
 
+fn print_explanations(prog: &ast::Program, atom: &str, dot: bool) -> Result<(), CompileError> {
+  let query = parser::parse_query(atom)?;
+  let explainer = explain::Explainer::new(prog)?;
+  let derivations = explainer.explain(&query.node.atom);
+  if dot {
+    print!("{}", explain::to_dot(&derivations));
+  } else if derivations.is_empty() {
+    println!("No tuple matches {}", query.node.atom.codify());
+  } else {
+    for derivation in &derivations {
+      print!("{}", derivation);
+    }
+  }
+  Ok(())
+}
+
+/// The tuples observed with `--observe` and `--observe-not`: the relation,
+/// the columns of the tuple as `DynTuple`s and whether it holds
+fn observed_tuples(ram: &ram::Program, options: &Options) -> Result<Vec<(String, Vec<TokenStream>, bool)>, CompileError> {
+  let observations = options.observe.iter().map(|atom| (atom, true));
+  let observations = observations.chain(options.observe_not.iter().map(|atom| (atom, false)));
+  let mut observed = vec![];
+  for (atom, holds) in observations {
+    let atom = parser::parse_query(atom)?.node.atom;
+    if !ram.variables.iter().any(|var| !var.is_temporary && var.name == atom.node.predicate) {
+      return Err(CompileError::UnknownRelation {
+        loc: atom.location.clone(),
+        rela_name: atom.node.predicate.clone(),
+      });
+    }
+    let mut columns = vec![];
+    for arg in &atom.node.args {
+      let column = match arg {
+        ast::Argument::Constant(c) => match &c.node {
+          ast::ConstantNode::Symbol(s) => quote! { scallop_runtime::symbols::intern(#s) },
+          ast::ConstantNode::SymbolId(i) => quote! { #i },
+          ast::ConstantNode::String(s) => quote! { #s },
+          ast::ConstantNode::Integer(i) => quote! { #i },
+          ast::ConstantNode::Boolean(b) => quote! { #b },
+        },
+        _ => return Err(CompileError::FactWithNonConstant { loc: atom.location.clone() }),
+      };
+      columns.push(quote! { scallop_runtime::interpreter::DynTuple::from(#column) });
+    }
+    observed.push((atom.node.predicate.clone(), columns, holds));
+  }
+  Ok(observed)
+}
+
 fn generate_main(
   ram: &ram::Program,
   options: &Options,
   analysis: &ast_analysis::AnalysisResult,
 ) -> Result<TokenStream, CompileError> {
   let name = format_ident!("{}", options.name);
+  let observed = observed_tuples(ram, options)?;
+  let semiring_type = if analysis.is_probabilistic {
+    match &options.prob_semiring {
+      SemiringType::Proofs | SemiringType::TopKProofs => &options.prob_semiring,
+      _ => return Err(CompileError::ShouldNotHappen),
+    }
+  } else {
+    &options.semiring
+  };
+  let semiring = match semiring_type {
+    SemiringType::Proofs => quote! { ProbProofs },
+    SemiringType::TopKProofs => quote! { DynTopKProbProofs },
+    SemiringType::Boolean => quote! { bool },
+    SemiringType::Counting => {
+      let max_count = options.max_count;
+      quote! { DerivationCount<#max_count> }
+    }
+    SemiringType::Empty => quote! { () },
+  };
   let mut cmd_args = vec![];
   let mut arg_parses = vec![];
+  let mut completes = vec![];
+  let mut observes = vec![];
   let mut outputs = vec![];
   for var in &ram.variables {
     if !var.is_temporary {
       let name = &var.name;
-      let name_ident = format_ident!("{}", var.name);
-      let output_name_ident = format_ident!("output_{}", var.name);
+      let name_ident = ram2rs::relation_ident(&var.name);
+      let output_name_ident = format_ident!("output_{}", name_ident);
+      let elems_ident = format_ident!("elems_{}", name_ident);
       cmd_args.push(quote! {
         let mut #output_name_ident = false;
       });
       arg_parses.push(quote! {
-        if arg == format!("--output-{}", #name) {
+        if arg == &format!("--output-{}", #name) {
           #output_name_ident = true;
         }
       });
-      outputs.push(quote! {
-        if #output_name_ident {
-          for elem in prog.#name_ident().complete().iter() {
-            println!("{:?}", elem);
+      if analysis.is_probabilistic {
+        // Every tuple of an evidence relation is observed to be true, and no
+        // tuple of a negative evidence relation holds
+        let evidence_ident = format_ident!("evidence_{}", name_ident);
+        let neg_evidence_ident = format_ident!("neg_evidence_{}", name_ident);
+        cmd_args.push(quote! {
+          let mut #evidence_ident = false;
+          let mut #neg_evidence_ident = false;
+        });
+        arg_parses.push(quote! {
+          if arg == &format!("--evidence-{}", #name) {
+            #evidence_ident = true;
           }
-        }
-      });
+          if arg == &format!("--neg-evidence-{}", #name) {
+            #neg_evidence_ident = true;
+          }
+        });
+        // A single observed tuple that is not derived has probability 0
+        let observed = observed.iter().filter(|(rela, _, _)| rela == name).collect::<Vec<_>>();
+        let is_observed = !observed.is_empty();
+        let observe_tuples = observed.iter().map(|(_, columns, holds)| {
+          quote! {
+            let columns = vec![#(#columns),*];
+            let elem = #elems_ident.iter().find(|elem| {
+              let tup: scallop_runtime::interpreter::DynTuple = elem.tup.clone().into();
+              tup.columns() == columns
+            });
+            match elem {
+              Some(elem) => evidence.observe(&elem.tag, #holds),
+              None => evidence.observe(&#semiring::zero(&prog.iteration().semiring_ctx), #holds),
+            }
+          }
+        });
+        completes.push(quote! {
+          let #elems_ident = if #output_name_ident || #evidence_ident || #neg_evidence_ident || #is_observed {
+            prog.#name_ident().complete().elements
+          } else {
+            vec![]
+          };
+        });
+        observes.push(quote! {
+          #({ #observe_tuples })*
+        });
+        observes.push(quote! {
+          for elem in &#elems_ident {
+            if #evidence_ident {
+              evidence.observe(&elem.tag, true);
+            }
+            if #neg_evidence_ident {
+              evidence.observe(&elem.tag, false);
+            }
+          }
+        });
+        outputs.push(quote! {
+          if #output_name_ident {
+            let ctx = &prog.iteration().semiring_ctx;
+            for elem in &#elems_ident {
+              if evidence.is_empty() {
+                println!("{}: {:?}", scallop_runtime::symbols::format_tuple(&elem.tup), elem.tag);
+              } else {
+                let tup = scallop_runtime::symbols::format_tuple(&elem.tup);
+                println!("{}: {:?}", tup, scallop_runtime::wmc::conditional_prob(ctx, &elem.tag, &evidence));
+              }
+            }
+          }
+        });
+      } else {
+        outputs.push(quote! {
+          if #output_name_ident {
+            for elem in prog.#name_ident().complete().iter() {
+              println!("{}: {:?}", scallop_runtime::symbols::format_tuple(&elem.tup), elem.tag);
+            }
+          }
+        });
+      }
     }
   }
-  let semiring = if analysis.is_probabilistic {
-    match &options.prob_semiring {
-      SemiringType::Proofs => quote! { ProbProofs },
-      SemiringType::TopKProofs => {
-        let k = options.k;
-        quote! { TopKProbProofs<#k> }
-      }
-      _ => return Err(CompileError::ShouldNotHappen),
+  let evidence_setup = if analysis.is_probabilistic {
+    quote! {
+      #(#completes)*
+      let mut evidence = scallop_runtime::wmc::Evidence::new();
+      #(#observes)*
     }
   } else {
-    match &options.semiring {
-      SemiringType::Proofs => quote! { ProbProofs },
-      SemiringType::TopKProofs => {
-        let k = options.k;
-        quote! { TopKProbProofs<#k> }
-      }
-      SemiringType::Boolean => quote! { bool },
-      SemiringType::Empty => quote! { () },
+    quote! {}
+  };
+  let (top_k_arg, top_k_parse, top_k_setup) = match semiring_type {
+    SemiringType::TopKProofs => {
+      let k = options.k;
+      (
+        quote! { let mut top_k: usize = #k; },
+        quote! {
+          if arg == "-k" {
+            top_k = args.get(i + 1).and_then(|k| k.parse().ok()).expect("Expected a number after -k");
+          }
+        },
+        quote! { prog.iteration_mut().semiring_ctx.top_k = top_k; },
+      )
     }
+    _ => (quote! {}, quote! {}, quote! {}),
   };
   let result = quote! {
     fn main() {
+      #top_k_arg
       #(#cmd_args)*
-      for arg in std::env::args() {
+      let args = std::env::args().collect::<Vec<_>>();
+      for (i, arg) in args.iter().enumerate() {
+        #top_k_parse
         #(#arg_parses)*
       }
       let mut prog = #name::<#semiring>::new();
+      #top_k_setup
       prog.run();
+      #evidence_setup
       #(#outputs)*
     }
   };
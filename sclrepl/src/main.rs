@@ -3,13 +3,24 @@ use std::str::FromStr;
 use structopt::StructOpt;
 use linefeed::{Interface, ReadResult};
 
-use scallop_runtime::{Program, EmptyProgram, Semiring, TupleType};
-use scallop_compiler::{parser, ast::TypeNode};
+use scallop_runtime::{Program, EmptyProgram, Semiring, SemiringContext, TupleType, DerivationCount, ProbProofs, DynTopKProbProofs, ProbProofContext};
+use scallop_runtime::{error::RuntimeError, interpreter::DynCompileError, wmc::*};
+use scallop_compiler::{ast, explain, parser, ast::TypeNode};
+
+/// Saturation bound of the derivation counts in the counting semiring
+const MAX_COUNT: usize = 1000000;
+
+/// The atoms observed with `observe`, and whether they hold
+type Observations = Vec<(bool, ast::Atom)>;
+
+/// The `prob` command of the probabilistic semirings
+type ProbCommand = Box<dyn Fn(&ast::Program, &Observations, &str)>;
 
 #[derive(Debug)]
 enum SemiringType {
   Empty,
   Boolean,
+  Counting,
   Proofs,
   TopKProofs,
 }
@@ -21,6 +32,7 @@ impl FromStr for SemiringType {
     match emit {
       "empty" => Ok(Self::Empty),
       "boolean" => Ok(Self::Boolean),
+      "counting" => Ok(Self::Counting),
       "proofs" => Ok(Self::Proofs),
       "top-k-proofs" => Ok(Self::TopKProofs),
       _ => Err("Unknown semiring type"),
@@ -33,53 +45,101 @@ impl FromStr for SemiringType {
 struct Options {
   #[structopt(long, default_value = "empty")]
   pub semiring: SemiringType,
+
+  #[structopt(short = "k", default_value = "3")]
+  pub k: usize,
 }
 
 fn main() -> std::io::Result<()> {
   let options = Options::from_args();
   match options.semiring {
-    SemiringType::Empty => run::<()>(),
-    SemiringType::Boolean => run::<bool>(),
-    SemiringType::Proofs => panic!("Not implemented"),
-    SemiringType::TopKProofs => panic!("Not implemented"),
+    SemiringType::Empty => run::<()>(Default::default(), None),
+    SemiringType::Boolean => run::<bool>(Default::default(), None),
+    SemiringType::Counting => run::<DerivationCount<MAX_COUNT>>(Default::default(), None),
+    SemiringType::Proofs => {
+      let prob = Box::new(|prog: &_, obs: &_, args: &_| {
+        prob_command::<ProbProofs>(ProbProofContext::default(), prog, obs, args)
+      });
+      run::<ProbProofs>(Default::default(), Some(prob))
+    }
+    SemiringType::TopKProofs => {
+      let k = options.k;
+      let prob = Box::new(move |prog: &_, obs: &_, args: &_| {
+        prob_command::<DynTopKProbProofs>(ProbProofContext::with_top_k(k), prog, obs, args)
+      });
+      run::<DynTopKProbProofs>(ProbProofContext::with_top_k(k), Some(prob))
+    }
   }
 }
 
-fn run<Tag: Semiring>() -> std::io::Result<()> {
+fn run<Tag: Semiring>(semiring_ctx: Tag::Context, prob: Option<ProbCommand>) -> std::io::Result<()> {
   let mut prog = EmptyProgram::<Tag>::new();
+  prog.iteration_mut().semiring_ctx = semiring_ctx;
   let reader = Interface::new("sclrepl")?;
   reader.set_prompt("scallop> ")?;
+
+  // Everything entered so far, for the explanations
+  let mut ast_program = ast::Program::default();
+  let mut observations = Observations::new();
   while let ReadResult::Input(input) = reader.read_line()? {
-    let maybe_item = parser::parse_item(&input);
+    if let Some(args) = input.trim().strip_prefix("explain ") {
+      explain_command(&ast_program, args);
+      continue;
+    }
+    if let Some(args) = input.trim().strip_prefix("why-not ") {
+      why_not_command(&ast_program, args);
+      continue;
+    }
+    if let Some(args) = input.trim().strip_prefix("observe ") {
+      observe_command(&mut observations, args);
+      continue;
+    }
+    if input.trim() == "unobserve" {
+      observations.clear();
+      continue;
+    }
+    if let Some(args) = input.trim().strip_prefix("prob ") {
+      match &prob {
+        Some(prob) => prob(&ast_program, &observations, args),
+        None => println!("Probabilities need a probabilistic semiring"),
+      }
+      continue;
+    }
+    let maybe_item = parser::parse_item_with_decls(&input, &ast_program.decls);
     match maybe_item {
       Ok(item) => match item {
+        parser::Item::TypeDecl(t) => {
+          ast_program.type_decls.push(t);
+        },
         parser::Item::Decl(var) => {
-          let tuple_type = TupleType::Tuple(var.node.arg_types.iter().map(|ty| {
-            match ty.node {
-              TypeNode::Boolean => TupleType::Boolean,
-              TypeNode::Integer => TupleType::Integer,
-              TypeNode::String => TupleType::String,
-              TypeNode::Symbol => TupleType::Symbol,
-            }
-          }).collect::<Vec<_>>());
-          let var_name = var.node.predicate;
-          match prog.add_variable(&var_name, tuple_type) {
-            Ok(_) => {},
-            Err(e) => println!("{}", e),
-          }
+          add_decl(&mut prog, &var, &ast_program.type_decls);
+          ast_program.decls.push(var);
         },
         parser::Item::Fact(f) => {
           println!("Trying to decl fact {:?}", f);
+          ast_program.facts.push(f);
         },
         parser::Item::Disjunction(d) => {
           println!("Trying to decl disjunction {:?}", d);
+          ast_program.disjunctions.push(d);
         },
         parser::Item::Rule(r) => {
           println!("Trying to decl rule {:?}", r);
+          ast_program.rules.push(r);
         },
         parser::Item::Query(q) => {
           println!("Trying to decl query {:?}", q);
         }
+        parser::Item::Import(i) => match parser::parse_import(&i) {
+          Ok(imported) => {
+            let decls = imported.decls.clone();
+            ast_program.extend(imported);
+            for decl in &decls {
+              add_decl(&mut prog, decl, &ast_program.type_decls);
+            }
+          }
+          Err(e) => println!("{}", e),
+        },
       },
       Err(e) => {
         println!("{}", e);
@@ -88,3 +148,130 @@ fn run<Tag: Semiring>() -> std::io::Result<()> {
   }
   Ok(())
 }
+
+fn add_decl<Tag: Semiring>(prog: &mut EmptyProgram<Tag>, decl: &ast::Decl, type_decls: &[ast::TypeDecl]) {
+  let tuple_types = decl.node.arg_types.iter().map(|ty| {
+    tuple_type(&ty.node, type_decls).ok_or_else(|| format!("Unknown type {}", ty.node))
+  }).collect::<Result<Vec<_>, _>>();
+  let result = tuple_types.and_then(|tuple_types| {
+    prog.add_variable(&decl.node.predicate, TupleType::Tuple(tuple_types)).map_err(|e| e.to_string())
+  });
+  match result {
+    Ok(_) => {},
+    Err(e) => println!("{}", e),
+  }
+}
+
+/// The tuple type of a type, following the declared types back to their base
+fn tuple_type(ty: &TypeNode, type_decls: &[ast::TypeDecl]) -> Option<TupleType> {
+  match ty {
+    TypeNode::Boolean => Some(TupleType::Boolean),
+    TypeNode::Integer => Some(TupleType::Integer),
+    TypeNode::String => Some(TupleType::String),
+    TypeNode::Symbol => Some(TupleType::Symbol),
+    TypeNode::Named(n) => {
+      let i = type_decls.iter().rposition(|t| &t.node.name == n)?;
+      tuple_type(&type_decls[i].node.base.node, &type_decls[..i])
+    }
+  }
+}
+
+/// `explain [--dot] <atom>`: print the derivation trees of the tuples
+/// matching the atom
+fn explain_command(prog: &ast::Program, args: &str) {
+  let args = args.trim();
+  let (dot, atom) = match args.strip_prefix("--dot") {
+    Some(atom) => (true, atom.trim()),
+    None => (false, args),
+  };
+  let atom = atom.trim_end_matches('.');
+  let result = parser::parse_query(atom).and_then(|query| {
+    let explainer = explain::Explainer::new(prog)?;
+    Ok(explainer.explain(&query.node.atom))
+  });
+  match result {
+    Ok(derivations) if dot => print!("{}", explain::to_dot(&derivations)),
+    Ok(derivations) if derivations.is_empty() => println!("No tuple matches {}", atom),
+    Ok(derivations) => {
+      for derivation in &derivations {
+        print!("{}", derivation);
+      }
+    }
+    Err(e) => println!("{}", e),
+  }
+}
+
+/// `why-not <atom>`: print why no tuple matching the atom is derived
+fn why_not_command(prog: &ast::Program, args: &str) {
+  let atom = args.trim().trim_end_matches('.');
+  let result = parser::parse_query(atom).and_then(|query| {
+    let explainer = explain::Explainer::new(prog)?;
+    Ok(explainer.why_not(&query.node.atom))
+  });
+  match result {
+    Ok(why_not) => print!("{}", why_not),
+    Err(e) => println!("{}", e),
+  }
+}
+
+/// `observe [~]<atom>`: observe that the tuples matching the atom hold, or
+/// do not hold with `~`, until `unobserve`
+fn observe_command(observations: &mut Observations, args: &str) {
+  let args = args.trim().trim_end_matches('.');
+  let (holds, atom) = match args.strip_prefix('~') {
+    Some(atom) => (false, atom.trim()),
+    None => (true, args),
+  };
+  match parser::parse_query(atom) {
+    Ok(query) => observations.push((holds, query.node.atom)),
+    Err(e) => println!("{}", e),
+  }
+}
+
+/// `prob <atom>`: print the probability of each tuple matching the atom
+/// given the observations, running the program entered so far
+fn prob_command<Tag>(ctx: ProbProofContext, prog: &ast::Program, observations: &Observations, args: &str)
+where
+  Tag: Semiring<Context = ProbProofContext> + ToBooleanFormula,
+  ProbProofContext: SemiringContext<Tag, Info = f64>,
+{
+  let atom = args.trim().trim_end_matches('.');
+  let result = parser::parse_query(atom).map_err(RuntimeError::CompileError).and_then(|query| {
+    let mut program = EmptyProgram::<Tag>::new();
+    program.iteration_mut().semiring_ctx = ctx;
+    program
+      .iteration_mut()
+      .add_program(prog.clone())
+      .map_err(|DynCompileError::CompileError(e)| RuntimeError::CompileError(e))?;
+    program.run();
+
+    // An observed atom matching no tuple makes the evidence impossible
+    let iter = program.iteration();
+    let mut evidence = Evidence::new();
+    for (holds, atom) in observations {
+      let elems = iter.query(atom)?;
+      if elems.is_empty() {
+        evidence.observe(&Tag::zero(&iter.semiring_ctx), *holds);
+      }
+      for elem in &elems {
+        evidence.observe(&elem.tag, *holds);
+      }
+    }
+    let elems = iter.query(&query.node.atom)?;
+    let tags = elems.iter().map(|elem| &elem.tag).collect::<Vec<_>>();
+    let probs = conditional_prob_batch(&iter.semiring_ctx, &tags, &evidence);
+    Ok(elems.iter().map(|elem| elem.tup.clone()).zip(probs).collect::<Vec<_>>())
+  });
+  match result {
+    Ok(probs) if probs.is_empty() => println!("No tuple matches {}", atom),
+    Ok(probs) => {
+      for (tup, prob) in probs {
+        match prob {
+          Some(prob) => println!("{:?}: {}", tup, prob),
+          None => println!("{:?}: the evidence is impossible", tup),
+        }
+      }
+    }
+    Err(e) => println!("{}", e),
+  }
+}
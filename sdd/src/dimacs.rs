@@ -0,0 +1,277 @@
+use std::collections::*;
+
+use super::io::*;
+use super::*;
+
+/// A literal of a clause: the variable and whether it is positive
+pub type CNFLiteral = (usize, bool);
+
+/// A formula in conjunctive normal form, as read from a DIMACS file
+///
+/// DIMACS variables are numbered from 1, so DIMACS variable `v` is the
+/// variable `v - 1` here. Every declared variable is part of the models, even
+/// if it occurs in no clause.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CNF {
+  pub num_vars: usize,
+  pub clauses: Vec<Vec<CNFLiteral>>,
+
+  /// Weights of the positive and the negative literal of the weighted
+  /// variables; the literals of the other variables weigh 1
+  pub weights: HashMap<usize, (f64, f64)>,
+}
+
+impl CNF {
+  pub fn new(num_vars: usize, clauses: Vec<Vec<CNFLiteral>>) -> Self {
+    Self {
+      num_vars,
+      clauses,
+      weights: HashMap::new(),
+    }
+  }
+
+  /// Parse a DIMACS CNF string
+  ///
+  /// Literal weights are read in both common variants: `w <var> <weight>`
+  /// lines, which weigh the negative literal `1 - weight` (or leave the
+  /// variable unweighted when the weight is `-1`), and `c p weight <lit>
+  /// <weight> 0` lines, which weigh each literal separately.
+  pub fn from_dimacs_str(s: &str) -> Result<Self, SDDParseError> {
+    let mut header = None;
+    let mut clauses = vec![];
+    let mut clause = vec![];
+    let mut weights = HashMap::<usize, (f64, f64)>::new();
+    for (i, content) in s.lines().enumerate() {
+      let line = i + 1;
+      let invalid = || SDDParseError::InvalidLine {
+        line,
+        content: content.to_string(),
+      };
+      let tokens = content.split_whitespace().collect::<Vec<_>>();
+      match tokens.as_slice() {
+        [] => {}
+        ["%", ..] => break, // End marker of the SATLIB benchmarks
+        ["c", "p", "weight", lit, weight, rest @ ..] => {
+          let (var_id, positive) = parse_literal(lit, header).ok_or_else(invalid)?;
+          let weight = weight.parse::<f64>().map_err(|_| invalid())?;
+          if rest.iter().any(|token| *token != "0") {
+            return Err(invalid());
+          }
+          let entry = weights.entry(var_id).or_insert((1.0, 1.0));
+          if positive {
+            entry.0 = weight;
+          } else {
+            entry.1 = weight;
+          }
+        }
+        ["c", ..] => {}
+        ["p", "cnf", num_vars, _num_clauses] => {
+          header = Some(num_vars.parse::<usize>().map_err(|_| invalid())?);
+        }
+        ["w", var, weight, rest @ ..] => {
+          let (var_id, _) = parse_literal(var, header).ok_or_else(invalid)?;
+          let weight = weight.parse::<f64>().map_err(|_| invalid())?;
+          if rest.iter().any(|token| *token != "0") {
+            return Err(invalid());
+          }
+          if weight != -1.0 {
+            weights.insert(var_id, (weight, 1.0 - weight));
+          }
+        }
+        tokens => {
+          // Clauses are terminated by 0 and may span several lines
+          if header.is_none() {
+            return Err(SDDParseError::MissingHeader);
+          }
+          for token in tokens {
+            if *token == "0" {
+              clauses.push(std::mem::take(&mut clause));
+            } else {
+              clause.push(parse_literal(token, header).ok_or_else(invalid)?);
+            }
+          }
+        }
+      }
+    }
+    if !clause.is_empty() {
+      clauses.push(clause);
+    }
+    match header {
+      Some(num_vars) => Ok(Self {
+        num_vars,
+        clauses,
+        weights,
+      }),
+      None => Err(SDDParseError::MissingHeader),
+    }
+  }
+
+  pub fn load_dimacs(file_name: &str) -> Result<Self, SDDParseError> {
+    Self::from_dimacs_str(&read_file(file_name)?)
+  }
+
+  /// The DIMACS string of the formula; weights are written as `c p weight`
+  /// lines
+  pub fn to_dimacs_string(&self) -> String {
+    let mut lines = vec![format!("p cnf {} {}", self.num_vars, self.clauses.len())];
+    let weights = self.weights.iter().collect::<BTreeMap<_, _>>();
+    for (var_id, (pos, neg)) in weights {
+      lines.push(format!("c p weight {} {} 0", var_id + 1, pos));
+      lines.push(format!("c p weight -{} {} 0", var_id + 1, neg));
+    }
+    for clause in &self.clauses {
+      let lits = clause
+        .iter()
+        .map(|(var_id, positive)| format!("{}{}", if *positive { "" } else { "-" }, var_id + 1))
+        .collect::<Vec<_>>();
+      lines.push(format!("{} 0", lits.join(" ")));
+    }
+    lines.join("\n") + "\n"
+  }
+
+  pub fn save_dimacs(&self, file_name: &str) -> std::io::Result<()> {
+    write_file(file_name, &self.to_dimacs_string())
+  }
+
+  /// The formula as a conjunction of disjunctions of literals
+  pub fn to_boolean_formula(&self) -> BooleanFormula {
+    self.clauses.iter().fold(BooleanFormula::True, |acc, clause| {
+      let clause = clause.iter().fold(BooleanFormula::False, |acc, (var_id, positive)| {
+        let lit = BooleanFormula::Pos { var_id: *var_id };
+        acc | if *positive { lit } else { !lit }
+      });
+      acc & clause
+    })
+  }
+
+  /// A vtree over all the declared variables; with the co-occurrence
+  /// heuristic, the variables sharing clauses are clustered
+  pub fn create_vtree(&self, heuristic: &VTreeHeuristic) -> VTree {
+    if self.num_vars == 0 {
+      return VTree::empty();
+    }
+    let vars = (0..self.num_vars).collect::<Vec<_>>();
+    match heuristic {
+      VTreeHeuristic::Shape(ty) => VTree::new_with_type(vars, *ty),
+      VTreeHeuristic::CoOccurrence => {
+        let groups = self
+          .clauses
+          .iter()
+          .map(|clause| clause.iter().map(|(var_id, _)| *var_id).collect())
+          .collect::<Vec<_>>();
+        VTree::create_clustered(vars, &groups)
+      }
+      VTreeHeuristic::Groups(groups) => VTree::create_grouped(vars, groups),
+    }
+  }
+
+  /// Compile the formula into an SDD, clause by clause; without declared
+  /// variables, the SDD is a constant
+  pub fn compile(&self, heuristic: &VTreeHeuristic) -> SDD {
+    let config = SDDBuilderConfig::with_vtree(self.create_vtree(heuristic));
+    let mut builder = SDDBuilder::with_config(config);
+    builder.add_cnf(self);
+    builder.build_arena()
+  }
+
+  /// Weighted model count of the formula given its SDD
+  ///
+  /// The literal weights of each variable are normalized to sum to 1, so that
+  /// they can be evaluated as probabilities, and the count is scaled back.
+  pub fn weighted_model_count(&self, sdd: &SDD) -> f64 {
+    let mut scale = 1.0;
+    let mut var_assign = HashMap::new();
+    for var_id in 0..self.num_vars {
+      let (pos, neg) = self.weights.get(&var_id).cloned().unwrap_or((1.0, 1.0));
+      let total = pos + neg;
+      scale *= total;
+      var_assign.insert(var_id, if total == 0.0 { 0.0 } else { pos / total });
+    }
+    scale * sdd.eval_t(&var_assign, &NormalizedWeightSemiring)
+  }
+}
+
+impl SDDBuilder {
+  /// Build a clause as a disjunction of its literals
+  pub fn build_clause(&mut self, clause: &[CNFLiteral]) -> SDDNodeIndex {
+    clause.iter().fold(self.build_sdd(&BooleanFormula::False), |acc, (var_id, positive)| {
+      let lit = if *positive {
+        BooleanFormula::Pos { var_id: *var_id }
+      } else {
+        BooleanFormula::Neg { var_id: *var_id }
+      };
+      let lit = self.build_sdd(&lit);
+      self.disjoin(acc, lit)
+    })
+  }
+
+  /// Build a CNF by conjoining its clauses one after the other, without
+  /// introducing auxiliary variables
+  pub fn build_cnf(&mut self, cnf: &CNF) -> SDDNodeIndex {
+    let mut node = self.build_sdd(&BooleanFormula::True);
+    for clause in &cnf.clauses {
+      let clause = self.build_clause(clause);
+      node = self.conjoin(node, clause);
+      if self.is_budget_exceeded() {
+        break;
+      }
+    }
+    node
+  }
+
+  /// Build a CNF and add it as a new root; returns the index of the root
+  pub fn add_cnf(&mut self, cnf: &CNF) -> usize {
+    let root = self.build_cnf(cnf);
+    assert!(!self.is_budget_exceeded(), "SDD compilation exceeded its budget");
+    self.add_root(root)
+  }
+
+  /// Like `add_cnf`, but returns `None` if the budget is exceeded
+  pub fn try_add_cnf(&mut self, cnf: &CNF) -> Option<usize> {
+    let root = self.build_cnf(cnf);
+    if self.is_budget_exceeded() {
+      None
+    } else {
+      Some(self.add_root(root))
+    }
+  }
+}
+
+/// Parse a non-zero DIMACS literal, checking it against the declared number
+/// of variables
+fn parse_literal(token: &str, num_vars: Option<usize>) -> Option<CNFLiteral> {
+  let lit = token.parse::<i64>().ok()?;
+  let var = lit.unsigned_abs() as usize;
+  if var == 0 || num_vars.is_some_and(|num_vars| var > num_vars) {
+    None
+  } else {
+    Some((var - 1, lit > 0))
+  }
+}
+
+/// Probabilities, where the negative literal weighs one minus the positive one
+struct NormalizedWeightSemiring;
+
+impl Semiring for NormalizedWeightSemiring {
+  type Element = f64;
+
+  fn zero(&self) -> Self::Element {
+    0.0
+  }
+
+  fn one(&self) -> Self::Element {
+    1.0
+  }
+
+  fn add(&self, a: Self::Element, b: Self::Element) -> Self::Element {
+    a + b
+  }
+
+  fn mult(&self, a: Self::Element, b: Self::Element) -> Self::Element {
+    a * b
+  }
+
+  fn negate(&self, a: Self::Element) -> Self::Element {
+    1.0 - a
+  }
+}
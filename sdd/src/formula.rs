@@ -62,6 +62,19 @@ impl std::ops::Not for BooleanFormula {
 }
 
 impl BooleanFormula {
+  /// Evaluate the formula under a full assignment of its variables
+  pub fn eval(&self, var_assign: &HashMap<usize, bool>) -> bool {
+    match self {
+      Self::True => true,
+      Self::False => false,
+      Self::Pos { var_id } => var_assign[var_id],
+      Self::Neg { var_id } => !var_assign[var_id],
+      Self::Not { form } => !form.eval(var_assign),
+      Self::And { left, right } => left.eval(var_assign) && right.eval(var_assign),
+      Self::Or { left, right } => left.eval(var_assign) || right.eval(var_assign),
+    }
+  }
+
   pub fn collect_vars(&self) -> Vec<usize> {
     let mut set = BTreeSet::new();
     self.collect_vars_helper(&mut set);
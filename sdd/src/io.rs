@@ -0,0 +1,279 @@
+use std::collections::*;
+use std::fs::File;
+use std::io::prelude::*;
+
+use petgraph::{graph::NodeIndex, Graph};
+
+use super::*;
+
+/// Error when reading a vtree or an SDD in the `.vtree`/`.sdd` text formats,
+/// or a CNF in the DIMACS format
+#[derive(Debug)]
+pub enum SDDParseError {
+  Io(std::io::Error),
+  InvalidLine { line: usize, content: String },
+  UnknownNode { line: usize, id: usize },
+  Empty,
+  MissingHeader,
+}
+
+impl From<std::io::Error> for SDDParseError {
+  fn from(err: std::io::Error) -> Self {
+    Self::Io(err)
+  }
+}
+
+impl std::fmt::Display for SDDParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Io(err) => err.fmt(f),
+      Self::InvalidLine { line, content } => f.write_fmt(format_args!("Invalid line {}: `{}`", line, content)),
+      Self::UnknownNode { line, id } => f.write_fmt(format_args!("Unknown node {} at line {}", id, line)),
+      Self::Empty => f.write_str("No node found"),
+      Self::MissingHeader => f.write_str("Missing `p cnf` header"),
+    }
+  }
+}
+
+/// Lines of a file as their line number, node kind and numeric arguments
+type Lines<'a> = Vec<(usize, &'a str, Vec<i64>)>;
+
+/// The non-comment lines of a file, along with their line numbers, split
+/// into the node kind and the numeric arguments
+fn parse_lines(s: &str) -> Result<Lines<'_>, SDDParseError> {
+  let mut lines = vec![];
+  for (i, content) in s.lines().enumerate() {
+    let mut tokens = content.split_whitespace();
+    let kind = match tokens.next() {
+      Some("c") | None => continue,
+      Some(kind) => kind,
+    };
+    let args = tokens
+      .map(|token| token.parse::<i64>())
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|_| SDDParseError::InvalidLine {
+        line: i + 1,
+        content: content.to_string(),
+      })?;
+    lines.push((i + 1, kind, args));
+  }
+  Ok(lines)
+}
+
+fn invalid_line(line: usize, kind: &str, args: &[i64]) -> SDDParseError {
+  let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+  SDDParseError::InvalidLine {
+    line,
+    content: format!("{} {}", kind, args.join(" ")),
+  }
+}
+
+pub(crate) fn read_file(file_name: &str) -> Result<String, SDDParseError> {
+  let mut content = String::new();
+  File::open(file_name)?.read_to_string(&mut content)?;
+  Ok(content)
+}
+
+pub(crate) fn write_file(file_name: &str, content: &str) -> std::io::Result<()> {
+  let mut file = File::create(file_name)?;
+  file.write_all(content.as_bytes())
+}
+
+/// In the `.vtree` format, the id of a vtree node is its in-order position
+/// and the variables are numbered from 1, so variable `var_id` is written as
+/// `var_id + 1`. Nodes appear bottom-up, children before their parents.
+impl VTree {
+  pub fn to_vtree_string(&self) -> String {
+    fn traverse(vtree: &VTree, node: VTreeNodeIndex, lines: &mut Vec<String>) {
+      let pos = vtree.position(node);
+      match &vtree.tree[node.0] {
+        VTreeNode::Leaf { var_id } => lines.push(format!("L {} {}", pos, var_id + 1)),
+        VTreeNode::Branch { left, right } => {
+          traverse(vtree, *left, lines);
+          traverse(vtree, *right, lines);
+          lines.push(format!("I {} {} {}", pos, vtree.position(*left), vtree.position(*right)));
+        }
+      }
+    }
+
+    let mut lines = vec![format!("vtree {}", self.tree.node_count())];
+    traverse(self, self.root_id(), &mut lines);
+    lines.join("\n") + "\n"
+  }
+
+  pub fn save_vtree(&self, file_name: &str) -> std::io::Result<()> {
+    write_file(file_name, &self.to_vtree_string())
+  }
+
+  /// Parse a vtree; the root is the last node of the file
+  pub fn from_vtree_str(s: &str) -> Result<Self, SDDParseError> {
+    let mut tree = Graph::new();
+    let mut id_map = HashMap::<i64, NodeIndex>::new();
+    let mut root = None;
+    for (line, kind, args) in parse_lines(s)? {
+      let node = match (kind, &args[..]) {
+        ("vtree", [_]) => continue,
+        ("L", [id, var]) if *var > 0 => {
+          let node = tree.add_node(VTreeNode::Leaf { var_id: (var - 1) as usize });
+          id_map.insert(*id, node);
+          node
+        }
+        ("I", [id, left, right]) => {
+          let get = |child: &i64| {
+            id_map.get(child).cloned().ok_or(SDDParseError::UnknownNode { line, id: *child as usize })
+          };
+          let (left, right) = (get(left)?, get(right)?);
+          let node = tree.add_node(VTreeNode::Branch {
+            left: VTreeNodeIndex(left),
+            right: VTreeNodeIndex(right),
+          });
+          tree.add_edge(node, left, ());
+          tree.add_edge(node, right, ());
+          id_map.insert(*id, node);
+          node
+        }
+        _ => return Err(invalid_line(line, kind, &args)),
+      };
+      root = Some(node);
+    }
+
+    let root = root.ok_or(SDDParseError::Empty)?;
+    let vars = tree
+      .node_indices()
+      .filter_map(|node| match &tree[node] {
+        VTreeNode::Leaf { var_id } => Some(*var_id),
+        _ => None,
+      })
+      .collect();
+    Ok(Self::post_process(tree, vars, root))
+  }
+
+  pub fn load_vtree(file_name: &str) -> Result<Self, SDDParseError> {
+    Self::from_vtree_str(&read_file(file_name)?)
+  }
+}
+
+/// In the `.sdd` format, nodes are numbered from 0 and appear bottom-up, the
+/// root being the last one. Literals and decision nodes refer to the vtree
+/// nodes by their in-order positions, as in the `.vtree` format.
+impl SDD {
+  /// The vtree the SDD is normalized for
+  pub fn vtree(&self) -> &VTree {
+    &self.vtree
+  }
+
+  /// Serialize the first root in the `.sdd` format
+  pub fn to_sdd_string(&self) -> String {
+    self.to_sdd_string_of_root(0)
+  }
+
+  /// Serialize the `i`-th root in the `.sdd` format
+  pub fn to_sdd_string_of_root(&self, i: usize) -> String {
+    fn traverse(sdd: &SDD, node: SDDNodeIndex, ids: &mut HashMap<SDDNodeIndex, usize>, lines: &mut Vec<String>) {
+      if ids.contains_key(&node) {
+        return;
+      }
+      let line = match &sdd.sdd_nodes[node] {
+        SDDNode::Literal { literal } => match literal {
+          SDDLiteral::False => format!("F {}", ids.len()),
+          SDDLiteral::True => format!("T {}", ids.len()),
+          SDDLiteral::PosVar { var_id } => {
+            let pos = sdd.vtree.position(sdd.vtree.var_to_node_id_map[var_id]);
+            format!("L {} {} {}", ids.len(), pos, var_id + 1)
+          }
+          SDDLiteral::NegVar { var_id } => {
+            let pos = sdd.vtree.position(sdd.vtree.var_to_node_id_map[var_id]);
+            format!("L {} {} -{}", ids.len(), pos, var_id + 1)
+          }
+        },
+        SDDNode::Or { children } => {
+          for SDDElement { prime, sub } in children {
+            traverse(sdd, *prime, ids, lines);
+            traverse(sdd, *sub, ids, lines);
+          }
+          let pos = sdd.vtree.position(sdd.sdd_node_to_vtree_node_map[&node]);
+          let elems = children
+            .iter()
+            .map(|SDDElement { prime, sub }| format!("{} {}", ids[prime], ids[sub]))
+            .collect::<Vec<_>>();
+          format!("D {} {} {} {}", ids.len(), pos, children.len(), elems.join(" "))
+        }
+      };
+      ids.insert(node, ids.len());
+      lines.push(line);
+    }
+
+    let mut ids = HashMap::new();
+    let mut lines = vec![];
+    traverse(self, self.roots[i], &mut ids, &mut lines);
+    format!("sdd {}\n{}\n", lines.len(), lines.join("\n"))
+  }
+
+  pub fn save_sdd(&self, file_name: &str) -> std::io::Result<()> {
+    write_file(file_name, &self.to_sdd_string())
+  }
+
+  /// Parse an SDD normalized for `vtree`; the root is the last node of the
+  /// file
+  pub fn from_sdd_str(s: &str, vtree: VTree) -> Result<Self, SDDParseError> {
+    let position_to_vtree_node = vtree
+      .in_order_positions
+      .iter()
+      .map(|(node, pos)| (*pos as i64, *node))
+      .collect::<HashMap<_, _>>();
+    let mut sdd_nodes = SDDNodes::new();
+    let mut sdd_node_to_vtree_node_map = HashMap::new();
+    let mut id_map = HashMap::<i64, SDDNodeIndex>::new();
+    let mut root = None;
+    for (line, kind, args) in parse_lines(s)? {
+      let vtree_node = |pos: &i64| {
+        position_to_vtree_node.get(pos).cloned().ok_or(SDDParseError::UnknownNode { line, id: *pos as usize })
+      };
+      let (id, node) = match (kind, &args[..]) {
+        ("sdd", [_]) => continue,
+        ("F", [id]) => (*id, sdd_nodes.add_node(SDDNode::Literal { literal: SDDLiteral::False })),
+        ("T", [id]) => (*id, sdd_nodes.add_node(SDDNode::Literal { literal: SDDLiteral::True })),
+        ("L", [id, pos, lit]) if *lit != 0 => {
+          // The literal must be normalized for the leaf of its variable
+          let var_id = (lit.abs() - 1) as usize;
+          let leaf = vtree_node(pos)?;
+          if vtree.var_to_node_id_map.get(&var_id) != Some(&leaf) {
+            return Err(invalid_line(line, kind, &args));
+          }
+          let literal = if *lit > 0 { SDDLiteral::PosVar { var_id } } else { SDDLiteral::NegVar { var_id } };
+          let node = sdd_nodes.add_node(SDDNode::Literal { literal });
+          sdd_node_to_vtree_node_map.insert(node, leaf);
+          (*id, node)
+        }
+        ("D", [id, pos, num_elems, elems @ ..]) if elems.len() as i64 == 2 * num_elems => {
+          let get = |child: &i64| {
+            id_map.get(child).cloned().ok_or(SDDParseError::UnknownNode { line, id: *child as usize })
+          };
+          let children = elems
+            .chunks(2)
+            .map(|elem| Ok(SDDElement { prime: get(&elem[0])?, sub: get(&elem[1])? }))
+            .collect::<Result<Vec<_>, SDDParseError>>()?;
+          let node = sdd_nodes.add_node(SDDNode::Or { children });
+          sdd_node_to_vtree_node_map.insert(node, vtree_node(pos)?);
+          (*id, node)
+        }
+        _ => return Err(invalid_line(line, kind, &args)),
+      };
+      id_map.insert(id, node);
+      root = Some(node);
+    }
+
+    let root = root.ok_or(SDDParseError::Empty)?;
+    Ok(Self {
+      sdd_nodes,
+      roots: vec![root],
+      vtree,
+      sdd_node_to_vtree_node_map,
+    })
+  }
+
+  pub fn load_sdd(sdd_file_name: &str, vtree_file_name: &str) -> Result<Self, SDDParseError> {
+    let vtree = VTree::load_vtree(vtree_file_name)?;
+    Self::from_sdd_str(&read_file(sdd_file_name)?, vtree)
+  }
+}
@@ -1,9 +1,17 @@
+mod dimacs;
 mod formula;
+mod io;
+mod queries;
 mod semiring;
 mod vtree;
+mod vtree_heuristics;
 mod sdd;
 
+pub use dimacs::*;
 pub use formula::*;
+pub use io::*;
+pub use queries::*;
 pub use semiring::*;
 pub use vtree::*;
+pub use vtree_heuristics::*;
 pub use crate::sdd::*;
@@ -0,0 +1,145 @@
+use std::collections::*;
+
+use super::*;
+
+/// A model of an SDD, assigning a value to every variable of its vtree
+pub type Model = BTreeMap<usize, bool>;
+
+/// Unweighted queries on the models of an SDD, over all the variables of its
+/// vtree
+impl SDD {
+  /// The number of models of the first root, or `None` if it does not fit
+  /// in a `u128`
+  pub fn model_count(&self) -> Option<u128> {
+    self.model_count_root(0)
+  }
+
+  /// The number of models of the `i`-th root, or `None` if it does not fit
+  /// in a `u128`
+  pub fn model_count_root(&self, i: usize) -> Option<u128> {
+    if self.vtree.num_vars() == 0 {
+      return Some(self.models_root(i).len() as u128);
+    }
+    let mut memo = HashMap::new();
+    self.model_count_at(self.roots[i], self.vtree.root_id(), &mut memo)
+  }
+
+  /// The number of models of `node` over the variables of the vtree node `at`
+  fn model_count_at(
+    &self,
+    node: SDDNodeIndex,
+    at: VTreeNodeIndex,
+    memo: &mut HashMap<SDDNodeIndex, Option<u128>>,
+  ) -> Option<u128> {
+    let num_vars = self.vtree.num_vars_in_subtree(at);
+    match &self.sdd_nodes[node] {
+      SDDNode::Literal { literal: SDDLiteral::True } => checked_shift(1, num_vars),
+      SDDNode::Literal { literal: SDDLiteral::False } => Some(0),
+      SDDNode::Literal { .. } => checked_shift(1, num_vars - 1),
+      SDDNode::Or { .. } => {
+        let vtree_node = self.sdd_node_to_vtree_node_map[&node];
+        let count = self.model_count_node(node, vtree_node, memo)?;
+        checked_shift(count, num_vars - self.vtree.num_vars_in_subtree(vtree_node))
+      }
+    }
+  }
+
+  /// The number of models of `node` over the variables of its own vtree node
+  fn model_count_node(
+    &self,
+    node: SDDNodeIndex,
+    vtree_node: VTreeNodeIndex,
+    memo: &mut HashMap<SDDNodeIndex, Option<u128>>,
+  ) -> Option<u128> {
+    if let Some(count) = memo.get(&node) {
+      return *count;
+    }
+    let count = match &self.sdd_nodes[node] {
+      SDDNode::Or { children } => {
+        let (left, right) = self.vtree.children(vtree_node).unwrap(); // Decision nodes are normalized for branches
+        children.iter().try_fold(0u128, |acc, SDDElement { prime, sub }| {
+          let primes = self.model_count_at(*prime, left, memo)?;
+          let subs = self.model_count_at(*sub, right, memo)?;
+          acc.checked_add(primes.checked_mul(subs)?)
+        })
+      }
+      SDDNode::Literal { .. } => Some(1),
+    };
+    memo.insert(node, count);
+    count
+  }
+
+  /// All the models of the first root; there can be exponentially many
+  pub fn models(&self) -> Vec<Model> {
+    self.models_root(0)
+  }
+
+  /// All the models of the `i`-th root
+  pub fn models_root(&self, i: usize) -> Vec<Model> {
+    if self.vtree.num_vars() == 0 {
+      // Only the constants are normalized for the empty vtree
+      return match &self.sdd_nodes[self.roots[i]] {
+        SDDNode::Literal { literal: SDDLiteral::True } => vec![Model::new()],
+        _ => vec![],
+      };
+    }
+    self.models_at(self.roots[i], self.vtree.root_id())
+  }
+
+  /// The models of `node` over the variables of the vtree node `at`
+  fn models_at(&self, node: SDDNodeIndex, at: VTreeNodeIndex) -> Vec<Model> {
+    let models = match &self.sdd_nodes[node] {
+      SDDNode::Literal { literal } => match literal {
+        SDDLiteral::True => vec![Model::new()],
+        SDDLiteral::False => vec![],
+        SDDLiteral::PosVar { var_id } => vec![vec![(*var_id, true)].into_iter().collect()],
+        SDDLiteral::NegVar { var_id } => vec![vec![(*var_id, false)].into_iter().collect()],
+      },
+      SDDNode::Or { children } => {
+        let vtree_node = self.sdd_node_to_vtree_node_map[&node];
+        let (left, right) = self.vtree.children(vtree_node).unwrap(); // Decision nodes are normalized for branches
+        children
+          .iter()
+          .flat_map(|SDDElement { prime, sub }| {
+            let subs = self.models_at(*sub, right);
+            self.models_at(*prime, left).into_iter().flat_map(move |p| {
+              subs.clone().into_iter().map(move |mut s| {
+                s.extend(p.clone());
+                s
+              })
+            })
+          })
+          .collect()
+      }
+    };
+
+    // Complete the models with all the values of the unassigned variables
+    self.vtree.vars_in_subtree(at).into_iter().fold(models, |models, var_id| {
+      if models.first().is_none_or(|model| model.contains_key(&var_id)) {
+        models
+      } else {
+        models
+          .into_iter()
+          .flat_map(|model| {
+            vec![true, false].into_iter().map(move |value| {
+              let mut model = model.clone();
+              model.insert(var_id, value);
+              model
+            })
+          })
+          .collect()
+      }
+    })
+  }
+}
+
+/// `count << n`, or `None` if it overflows
+fn checked_shift(count: u128, n: usize) -> Option<u128> {
+  if count == 0 {
+    Some(0)
+  } else if n <= count.leading_zeros() as usize {
+    Some(count << n)
+  } else {
+    None
+  }
+}
@@ -4,8 +4,8 @@ use super::*;
 
 #[derive(Clone, PartialOrd, PartialEq, Ord, Eq, Hash)]
 pub struct SDDElement {
-  prime: SDDNodeIndex,
-  sub: SDDNodeIndex,
+  pub(crate) prime: SDDNodeIndex,
+  pub(crate) sub: SDDNodeIndex,
 }
 
 impl std::fmt::Debug for SDDElement {
@@ -52,7 +52,7 @@ impl std::fmt::Debug for SDDNode {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct SDDNodeIndex(usize);
+pub struct SDDNodeIndex(pub(crate) usize);
 
 impl std::fmt::Debug for SDDNodeIndex {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -137,8 +137,13 @@ impl std::fmt::Debug for SDDNodes {
 
 #[derive(Clone, Debug)]
 pub struct SDD {
-  sdd_nodes: SDDNodes,
-  roots: Vec<SDDNodeIndex>,
+  pub(crate) sdd_nodes: SDDNodes,
+  pub(crate) roots: Vec<SDDNodeIndex>,
+
+  // The vtree the SDD is normalized for, and the vtree node of each
+  // decision and literal node
+  pub(crate) vtree: VTree,
+  pub(crate) sdd_node_to_vtree_node_map: HashMap<SDDNodeIndex, VTreeNodeIndex>,
 }
 
 impl SDD {
@@ -181,6 +186,162 @@ impl SDD {
     self.eval_node_t(self.roots[0], var_assign, semiring)
   }
 
+  /// The number of roots in the arena
+  pub fn num_roots(&self) -> usize {
+    self.roots.len()
+  }
+
+  /// The number of nodes in the arena, including the constants
+  pub fn num_nodes(&self) -> usize {
+    self.sdd_nodes.len()
+  }
+
+  /// Evaluate the `i`-th root of the arena
+  pub fn eval_root_t<T: Semiring>(
+    &self,
+    i: usize,
+    var_assign: &HashMap<usize, <T as Semiring>::Element>,
+    semiring: &T,
+  ) -> <T as Semiring>::Element {
+    self.eval_node_t(self.roots[i], var_assign, semiring)
+  }
+
+  /// Most probable explanation of the first root
+  ///
+  /// See `mpe_root`.
+  pub fn mpe(&self, var_probs: &HashMap<usize, f64>) -> Option<(f64, BTreeMap<usize, bool>)> {
+    self.mpe_root(0, var_probs)
+  }
+
+  /// Most probable explanation of the `i`-th root: the most probable
+  /// assignment of the variables in `var_probs` satisfying the root, along
+  /// with its probability, where `var_probs` maps each variable to its
+  /// probability of being true. Returns `None` if the root is unsatisfiable.
+  ///
+  /// This is a max-product evaluation followed by a traceback of the chosen
+  /// elements. The weights are normalized by the most probable value of each
+  /// variable, so that variables not mentioned along the chosen path simply
+  /// take their most probable value.
+  pub fn mpe_root(&self, i: usize, var_probs: &HashMap<usize, f64>) -> Option<(f64, BTreeMap<usize, bool>)> {
+    let mut memo = HashMap::new();
+    let norm_prob = self.mpe_node(self.roots[i], var_probs, &mut memo)?;
+
+    // Traceback the chosen elements; unmentioned variables are set to their
+    // most probable value
+    let mut assign = var_probs.iter().map(|(var_id, p)| (*var_id, *p >= 0.5)).collect();
+    self.mpe_traceback(self.roots[i], &memo, &mut assign);
+    let max_prob = var_probs.values().map(|p| p.max(1.0 - p)).product::<f64>();
+    Some((norm_prob * max_prob, assign))
+  }
+
+  fn mpe_node(
+    &self,
+    node_id: SDDNodeIndex,
+    var_probs: &HashMap<usize, f64>,
+    memo: &mut HashMap<SDDNodeIndex, Option<(f64, usize)>>,
+  ) -> Option<f64> {
+    if let Some(result) = memo.get(&node_id) {
+      return result.map(|(prob, _)| prob);
+    }
+    let result = match &self.sdd_nodes[node_id] {
+      SDDNode::Or { children } => {
+        let mut best: Option<(f64, usize)> = None;
+        for (j, SDDElement { prime, sub }) in children.iter().enumerate() {
+          let prime_res = self.mpe_node(*prime, var_probs, memo);
+          let sub_res = self.mpe_node(*sub, var_probs, memo);
+          if let (Some(p), Some(s)) = (prime_res, sub_res) {
+            if best.is_none_or(|(b, _)| p * s > b) {
+              best = Some((p * s, j));
+            }
+          }
+        }
+        best
+      }
+      SDDNode::Literal { literal } => {
+        let norm = |var_id: &usize, value: bool| {
+          let p = var_probs[var_id];
+          let prob = if value { p } else { 1.0 - p };
+          (prob / p.max(1.0 - p), 0)
+        };
+        match literal {
+          SDDLiteral::PosVar { var_id } => Some(norm(var_id, true)),
+          SDDLiteral::NegVar { var_id } => Some(norm(var_id, false)),
+          SDDLiteral::True => Some((1.0, 0)),
+          SDDLiteral::False => None,
+        }
+      }
+    };
+    memo.insert(node_id, result);
+    result.map(|(prob, _)| prob)
+  }
+
+  fn mpe_traceback(
+    &self,
+    node_id: SDDNodeIndex,
+    memo: &HashMap<SDDNodeIndex, Option<(f64, usize)>>,
+    assign: &mut BTreeMap<usize, bool>,
+  ) {
+    match &self.sdd_nodes[node_id] {
+      SDDNode::Or { children } => {
+        if let Some((_, j)) = memo[&node_id] {
+          let SDDElement { prime, sub } = &children[j];
+          self.mpe_traceback(*prime, memo, assign);
+          self.mpe_traceback(*sub, memo, assign);
+        }
+      }
+      SDDNode::Literal { literal } => match literal {
+        SDDLiteral::PosVar { var_id } => {
+          assign.insert(*var_id, true);
+        }
+        SDDLiteral::NegVar { var_id } => {
+          assign.insert(*var_id, false);
+        }
+        _ => {}
+      },
+    }
+  }
+
+  /// Evaluate all the roots of the arena; nodes shared between roots are
+  /// evaluated only once
+  pub fn eval_roots_t<T: Semiring>(
+    &self,
+    var_assign: &HashMap<usize, <T as Semiring>::Element>,
+    semiring: &T,
+  ) -> Vec<<T as Semiring>::Element> {
+    let mut memo = HashMap::new();
+    self
+      .roots
+      .iter()
+      .map(|root| self.eval_node_t_memo(*root, var_assign, semiring, &mut memo))
+      .collect()
+  }
+
+  fn eval_node_t_memo<T: Semiring>(
+    &self,
+    node_id: SDDNodeIndex,
+    var_assign: &HashMap<usize, <T as Semiring>::Element>,
+    semiring: &T,
+    memo: &mut HashMap<SDDNodeIndex, <T as Semiring>::Element>,
+  ) -> <T as Semiring>::Element {
+    if let Some(result) = memo.get(&node_id) {
+      return result.clone();
+    }
+    let result = match &self.sdd_nodes[node_id] {
+      SDDNode::Or { children } => {
+        children
+          .iter()
+          .fold(semiring.zero(), |acc, SDDElement { prime, sub }| {
+            let prime_res = self.eval_node_t_memo(*prime, var_assign, semiring, memo);
+            let sub_res = self.eval_node_t_memo(*sub, var_assign, semiring, memo);
+            semiring.add(acc, semiring.mult(prime_res, sub_res))
+          })
+      }
+      SDDNode::Literal { .. } => self.eval_node_t(node_id, var_assign, semiring),
+    };
+    memo.insert(node_id, result.clone());
+    result
+  }
+
   fn eval_node_t<T: Semiring>(
     &self,
     node_id: SDDNodeIndex,
@@ -310,9 +471,39 @@ impl SDD {
   }
 }
 
+/// Limits on the size of a compilation
+///
+/// The compilation is aborted as soon as one of the limits is exceeded;
+/// `SDDBuilder::try_build` then returns `None` while `SDDBuilder::build`
+/// panics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SDDBudget {
+  pub max_apply_count: Option<usize>,
+  pub max_num_nodes: Option<usize>,
+}
+
+impl SDDBudget {
+  pub fn new(max_apply_count: Option<usize>, max_num_nodes: Option<usize>) -> Self {
+    Self {
+      max_apply_count,
+      max_num_nodes,
+    }
+  }
+
+  pub fn unlimited() -> Self {
+    Self::default()
+  }
+
+  fn is_exceeded(&self, apply_count: usize, num_nodes: usize) -> bool {
+    self.max_apply_count.is_some_and(|max| apply_count > max)
+      || self.max_num_nodes.is_some_and(|max| num_nodes > max)
+  }
+}
+
 pub struct SDDBuilderConfig {
   vtree: VTree,
   garbage_collect: bool,
+  budget: SDDBudget,
 }
 
 impl SDDBuilderConfig {
@@ -321,18 +512,40 @@ impl SDDBuilderConfig {
     Self {
       vtree,
       garbage_collect,
+      budget: SDDBudget::unlimited(),
     }
   }
 
+  /// Config with a vtree over the variables of the formula constructed by the
+  /// default heuristic
   pub fn with_formula(form: &BooleanFormula) -> Self {
-    let vars = form.collect_vars();
-    let vtree = VTree::new_with_type(vars, VTreeType::default());
+    Self::with_formulas(std::iter::once(form))
+  }
+
+  /// Config with a common vtree over the variables of all the formulas, to be
+  /// used when compiling all of them into the same arena
+  pub fn with_formulas<'a, I: IntoIterator<Item = &'a BooleanFormula>>(forms: I) -> Self {
+    Self::with_formulas_and_heuristic(forms, &VTreeHeuristic::default())
+  }
+
+  /// Config with a given vtree
+  pub fn with_vtree(vtree: VTree) -> Self {
     Self {
       vtree,
       garbage_collect: true,
+      budget: SDDBudget::unlimited(),
     }
   }
 
+  /// Config with a vtree over the variables of the formulas constructed by the
+  /// heuristic
+  pub fn with_formulas_and_heuristic<'a, I>(forms: I, heuristic: &VTreeHeuristic) -> Self
+  where
+    I: IntoIterator<Item = &'a BooleanFormula>,
+  {
+    Self::with_vtree(VTree::create_from_formulas(forms, heuristic))
+  }
+
   pub fn disable_garbage_collect(mut self) -> Self {
     self.garbage_collect = false;
     self
@@ -342,6 +555,11 @@ impl SDDBuilderConfig {
     self.garbage_collect = true;
     self
   }
+
+  pub fn with_budget(mut self, budget: SDDBudget) -> Self {
+    self.budget = budget;
+    self
+  }
 }
 
 pub struct SDDBuilder {
@@ -362,6 +580,7 @@ pub struct SDDBuilder {
 
   // Builder states
   apply_depth: usize,
+  budget_exceeded: bool,
 
   // Statistics
   apply_count: usize,
@@ -465,6 +684,7 @@ impl SDDBuilder {
 
       // States
       apply_depth: 0,
+      budget_exceeded: false,
 
       // Statistics
       apply_count: 0,
@@ -473,27 +693,142 @@ impl SDDBuilder {
 
   pub fn build(mut self, formula: &BooleanFormula) -> SDD {
     let root = self.build_sdd(formula);
+    assert!(!self.budget_exceeded, "SDD compilation exceeded its budget");
     self.roots.push(root);
 
-    // Do garbage collection if presented
-    if self.config.garbage_collect {
-      self.garbage_collect();
-    }
+    // Do garbage collection if presented, and create an SDD
+    self.build_arena()
+  }
 
-    // Create an SDD
-    SDD {
-      sdd_nodes: self.sdd_nodes,
-      roots: self.roots,
+  /// Build the SDD of a formula within the budget of the config; returns
+  /// `None` if the budget is exceeded during the compilation
+  pub fn try_build(mut self, formula: &BooleanFormula) -> Option<SDD> {
+    let root = self.build_sdd(formula);
+    if self.budget_exceeded {
+      return None;
     }
+    self.roots.push(root);
+    Some(self.build_arena())
+  }
+
+  /// The number of non-trivial applies performed so far
+  pub fn apply_count(&self) -> usize {
+    self.apply_count
+  }
+
+  /// The number of nodes currently in the arena
+  pub fn num_nodes(&self) -> usize {
+    self.sdd_nodes.len()
   }
 
   pub fn add_formula(&mut self, formula: &BooleanFormula) -> usize {
     let num_roots = self.roots.len();
     let new_root = self.build_sdd(formula);
+    assert!(!self.budget_exceeded, "SDD compilation exceeded its budget");
     self.roots.push(new_root);
     num_roots
   }
 
+  /// Like `add_formula`, but returns `None` if the budget is exceeded
+  pub fn try_add_formula(&mut self, formula: &BooleanFormula) -> Option<usize> {
+    let num_roots = self.roots.len();
+    let new_root = self.build_sdd(formula);
+    if self.budget_exceeded {
+      return None;
+    }
+    self.roots.push(new_root);
+    Some(num_roots)
+  }
+
+  /// Add an already built node as a new root of the arena; returns the index
+  /// of the root
+  pub fn add_root(&mut self, node: SDDNodeIndex) -> usize {
+    self.roots.push(node);
+    self.roots.len() - 1
+  }
+
+  /// The conjunction of two built nodes
+  pub fn conjoin(&mut self, lhs: SDDNodeIndex, rhs: SDDNodeIndex) -> SDDNodeIndex {
+    self.apply(lhs, rhs, ApplyOp::Conjoin)
+  }
+
+  /// The disjunction of two built nodes
+  pub fn disjoin(&mut self, lhs: SDDNodeIndex, rhs: SDDNodeIndex) -> SDDNodeIndex {
+    self.apply(lhs, rhs, ApplyOp::Disjoin)
+  }
+
+  /// Whether the budget has been exceeded, in which case the nodes built
+  /// since are meaningless
+  pub fn is_budget_exceeded(&self) -> bool {
+    self.budget_exceeded
+  }
+
+  /// The negation of a built node
+  pub fn negate(&mut self, node: SDDNodeIndex) -> SDDNodeIndex {
+    self.negate_node(node)
+  }
+
+  /// Condition a built node on the variable `var_id` having the value `value`
+  pub fn condition(&mut self, node: SDDNodeIndex, var_id: usize, value: bool) -> SDDNodeIndex {
+    match self.config.vtree.var_to_node_id_map.get(&var_id) {
+      Some(leaf) => {
+        let leaf_pos = self.config.vtree.position(*leaf);
+        self.condition_helper(node, var_id, leaf_pos, value, &mut HashMap::new())
+      }
+      None => node,
+    }
+  }
+
+  fn condition_helper(
+    &mut self,
+    node: SDDNodeIndex,
+    var_id: usize,
+    leaf_pos: usize,
+    value: bool,
+    memo: &mut HashMap<SDDNodeIndex, SDDNodeIndex>,
+  ) -> SDDNodeIndex {
+    if let Some(result) = memo.get(&node) {
+      return *result;
+    }
+    let result = match self.sdd_nodes[node].clone() {
+      SDDNode::Literal { literal } => match literal {
+        SDDLiteral::PosVar { var_id: v } if v == var_id => if value { self.true_node } else { self.false_node },
+        SDDLiteral::NegVar { var_id: v } if v == var_id => if value { self.false_node } else { self.true_node },
+        _ => node,
+      },
+      SDDNode::Or { children } => {
+        // Nodes not depending on the variable are kept as is
+        let vtree_node = self.vtree_node(node);
+        let first = self.config.vtree.position(self.config.vtree.first_in_subtree(vtree_node));
+        let last = self.config.vtree.position(self.config.vtree.last_in_subtree(vtree_node));
+        if leaf_pos < first || leaf_pos > last {
+          node
+        } else {
+          children.into_iter().fold(self.false_node, |acc, SDDElement { prime, sub }| {
+            let prime = self.condition_helper(prime, var_id, leaf_pos, value, memo);
+            let sub = self.condition_helper(sub, var_id, leaf_pos, value, memo);
+            let elem = self.apply(prime, sub, ApplyOp::Conjoin);
+            self.apply(acc, elem, ApplyOp::Disjoin)
+          })
+        }
+      }
+    };
+    memo.insert(node, result);
+    result
+  }
+
+  /// Existentially quantify the variable `var_id` out of a built node
+  pub fn exists(&mut self, node: SDDNodeIndex, var_id: usize) -> SDDNodeIndex {
+    let pos = self.condition(node, var_id, true);
+    let neg = self.condition(node, var_id, false);
+    self.apply(pos, neg, ApplyOp::Disjoin)
+  }
+
+  /// Existentially quantify many variables out of a built node
+  pub fn exists_many(&mut self, node: SDDNodeIndex, var_ids: &[usize]) -> SDDNodeIndex {
+    var_ids.iter().fold(node, |acc, var_id| self.exists(acc, *var_id))
+  }
+
   pub fn build_arena(mut self) -> SDD {
     if self.config.garbage_collect {
       self.garbage_collect();
@@ -502,6 +837,8 @@ impl SDDBuilder {
     SDD {
       sdd_nodes: self.sdd_nodes,
       roots: self.roots,
+      vtree: self.config.vtree,
+      sdd_node_to_vtree_node_map: self.sdd_node_to_vtree_node_map,
     }
   }
 
@@ -845,6 +1182,15 @@ impl SDDBuilder {
       return cached_node_id;
     }
 
+    // Give up when the budget is exceeded; the result is meaningless from
+    // now on and is discarded by `try_build`
+    if self.budget_exceeded {
+      return self.false_node;
+    } else if self.config.budget.is_exceeded(self.apply_count, self.sdd_nodes.len()) {
+      self.budget_exceeded = true;
+      return self.false_node;
+    }
+
     // Increment depth
     self.apply_depth += 1;
 
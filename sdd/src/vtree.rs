@@ -43,7 +43,7 @@ impl VTreeNode {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct VTreeNodeIndex(NodeIndex);
+pub struct VTreeNodeIndex(pub(crate) NodeIndex);
 
 impl std::fmt::Debug for VTreeNodeIndex {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -104,6 +104,19 @@ impl VTree {
     }
   }
 
+  /// The vtree over no variable, which only normalizes the constants
+  pub fn empty() -> Self {
+    Self {
+      tree: Graph::new(),
+      vars: vec![],
+      root: NodeIndex::end(),
+      var_to_node_id_map: HashMap::new(),
+      in_order_positions: HashMap::new(),
+      first_in_subtree_map: HashMap::new(),
+      last_in_subtree_map: HashMap::new(),
+    }
+  }
+
   pub fn create_left_linear(num_vars: usize) -> Self {
     Self::create_left_linear_from_vars((0..num_vars).collect())
   }
@@ -190,7 +203,7 @@ impl VTree {
     Self::post_process(tree, vars, root)
   }
 
-  fn post_process(tree: Graph<VTreeNode, ()>, vars: Vec<usize>, root: NodeIndex) -> Self {
+  pub(crate) fn post_process(tree: Graph<VTreeNode, ()>, vars: Vec<usize>, root: NodeIndex) -> Self {
     // In order position
     fn in_order_traversal<F: FnMut(NodeIndex, &VTreeNode)>(
       tree: &Graph<VTreeNode, ()>,
@@ -309,6 +322,32 @@ impl VTree {
     }
   }
 
+  /// The left and right children of a branch node
+  pub fn children(&self, node: VTreeNodeIndex) -> Option<(VTreeNodeIndex, VTreeNodeIndex)> {
+    match &self.tree[node.0] {
+      VTreeNode::Branch { left, right } => Some((*left, *right)),
+      VTreeNode::Leaf { .. } => None,
+    }
+  }
+
+  /// The variables in the subtree of a node, from left to right
+  pub fn vars_in_subtree(&self, node: VTreeNodeIndex) -> Vec<usize> {
+    match &self.tree[node.0] {
+      VTreeNode::Leaf { var_id } => vec![*var_id],
+      VTreeNode::Branch { left, right } => {
+        let mut vars = self.vars_in_subtree(*left);
+        vars.extend(self.vars_in_subtree(*right));
+        vars
+      }
+    }
+  }
+
+  /// The number of variables in the subtree of a node
+  pub fn num_vars_in_subtree(&self, node: VTreeNodeIndex) -> usize {
+    // Leaves and branches alternate in the in-order traversal
+    (self.position(self.last_in_subtree(node)) - self.position(self.first_in_subtree(node))) / 2 + 1
+  }
+
   pub fn num_vars(&self) -> usize {
     self.vars.len()
   }
@@ -356,6 +395,9 @@ impl VTree {
       _ => return Err(VTreeMutationError::LeftNodeIsNotBranch),
     };
 
+    // The parent has to be found before the edges are modified
+    let parent = self.parent(x);
+
     // Modify the connectivities
     match &mut self.tree[x.0] {
       VTreeNode::Branch { right, .. } => { *right = b; },
@@ -367,23 +409,22 @@ impl VTree {
     }
 
     // Modify the graph edges
+    // Note: removing an edge invalidates the index of the last edge, so each
+    // edge is looked up right before it is removed
     let y_to_b = self.tree.find_edge(y.0, b.0).unwrap(); // unwrap because there has to be an edge
-    let x_to_y = self.tree.find_edge(x.0, y.0).unwrap(); // unwrap because there has to be an edge
     self.tree.remove_edge(y_to_b);
+    let x_to_y = self.tree.find_edge(x.0, y.0).unwrap(); // unwrap because there has to be an edge
     self.tree.remove_edge(x_to_y);
     self.tree.add_edge(y.0, x.0, ());
     self.tree.add_edge(x.0, b.0, ());
 
     // Update the parent
-    if self.is_root(x) {
-      self.root = y.0;
-    } else {
-      let parent = self.parent(x).unwrap(); // Since y is not root, y has a parent
-      let parent_to_x = self.tree.find_edge(parent.0, x.0).unwrap(); // There has to be an edge
-      self.tree.remove_edge(parent_to_x);
-      self.tree.add_edge(parent.0, y.0, ());
+    match parent {
+      Some(parent) => self.replace_child(parent, x, y),
+      None => self.root = y.0,
     }
 
+    self.recompute_caches();
     Ok(())
   }
 
@@ -408,6 +449,9 @@ impl VTree {
       _ => return Err(VTreeMutationError::LeftNodeIsNotBranch),
     };
 
+    // The parent has to be found before the edges are modified
+    let parent = self.parent(y);
+
     // Modify the connectivities
     match &mut self.tree[y.0] {
       VTreeNode::Branch { left, .. } => { *left = b; },
@@ -419,23 +463,22 @@ impl VTree {
     }
 
     // Modify the graph edges
+    // Note: removing an edge invalidates the index of the last edge, so each
+    // edge is looked up right before it is removed
     let x_to_b = self.tree.find_edge(x.0, b.0).unwrap(); // unwrap because there has to be an edge
-    let y_to_x = self.tree.find_edge(y.0, x.0).unwrap(); // unwrap because there has to be an edge
     self.tree.remove_edge(x_to_b);
+    let y_to_x = self.tree.find_edge(y.0, x.0).unwrap(); // unwrap because there has to be an edge
     self.tree.remove_edge(y_to_x);
     self.tree.add_edge(x.0, y.0, ());
     self.tree.add_edge(y.0, b.0, ());
 
     // Update the parent
-    if self.is_root(y) {
-      self.root = x.0;
-    } else {
-      let parent = self.parent(y).unwrap(); // Since y is not root, y has a parent
-      let parent_to_y = self.tree.find_edge(parent.0, y.0).unwrap(); // There has to be an edge
-      self.tree.remove_edge(parent_to_y);
-      self.tree.add_edge(parent.0, x.0, ());
+    match parent {
+      Some(parent) => self.replace_child(parent, y, x),
+      None => self.root = x.0,
     }
 
+    self.recompute_caches();
     Ok(())
   }
 
@@ -452,10 +495,37 @@ impl VTree {
         let tmp = *left;
         *left = *right;
         *right = tmp;
-        Ok(())
       }
-      VTreeNode::Leaf { .. } => Err(VTreeMutationError::NodeIsNotBranch)
+      VTreeNode::Leaf { .. } => return Err(VTreeMutationError::NodeIsNotBranch),
+    }
+
+    self.recompute_caches();
+    Ok(())
+  }
+
+  /// Replace the child `old` of `parent` by `new`, both in the node and in the
+  /// graph edges
+  fn replace_child(&mut self, parent: VTreeNodeIndex, old: VTreeNodeIndex, new: VTreeNodeIndex) {
+    match &mut self.tree[parent.0] {
+      VTreeNode::Branch { left, right } => {
+        if *left == old {
+          *left = new;
+        } else {
+          *right = new;
+        }
+      }
+      _ => panic!("Should not happen"),
     }
+    let parent_to_old = self.tree.find_edge(parent.0, old.0).unwrap(); // There has to be an edge
+    self.tree.remove_edge(parent_to_old);
+    self.tree.add_edge(parent.0, new.0, ());
+  }
+
+  /// Recompute the in-order positions and the subtree caches after a mutation
+  fn recompute_caches(&mut self) {
+    let tree = std::mem::take(&mut self.tree);
+    let vars = std::mem::take(&mut self.vars);
+    *self = Self::post_process(tree, vars, self.root);
   }
 
   pub fn dot(&self) -> String {
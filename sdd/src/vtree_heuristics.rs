@@ -0,0 +1,355 @@
+use petgraph::{graph::NodeIndex, Graph};
+use std::cmp::Ordering;
+use std::collections::*;
+
+use super::*;
+
+/// How to construct the vtree of a set of formulas
+#[derive(Clone, Debug, Default)]
+pub enum VTreeHeuristic {
+  /// A vtree of the given shape over the sorted variables, ignoring the
+  /// structure of the formulas
+  Shape(VTreeType),
+
+  /// Variables co-occurring in the same conjunctions (e.g. proofs) or clauses
+  /// are clustered bottom-up into the same subtrees
+  #[default]
+  CoOccurrence,
+
+  /// Each group of variables (e.g. a disjunction of mutually exclusive facts)
+  /// is placed in its own balanced subtree, in the given order; variables in
+  /// no group come last
+  Groups(Vec<Vec<usize>>),
+}
+
+impl VTree {
+  /// Create the vtree over the variables of all the formulas following the
+  /// heuristic
+  pub fn create_from_formulas<'a, I>(forms: I, heuristic: &VTreeHeuristic) -> Self
+  where
+    I: IntoIterator<Item = &'a BooleanFormula>,
+  {
+    let forms = forms.into_iter().collect::<Vec<_>>();
+    let vars = forms
+      .iter()
+      .flat_map(|form| form.collect_vars())
+      .collect::<BTreeSet<_>>()
+      .into_iter()
+      .collect::<Vec<_>>();
+    match heuristic {
+      VTreeHeuristic::Shape(ty) => Self::new_with_type(vars, *ty),
+      VTreeHeuristic::CoOccurrence => {
+        let mut groups = vec![];
+        for form in forms {
+          collect_co_occurring_groups(form, &mut groups);
+        }
+        Self::create_clustered(vars, &groups)
+      }
+      VTreeHeuristic::Groups(groups) => Self::create_grouped(vars, groups),
+    }
+  }
+
+  /// Create a vtree by clustering the variables bottom-up: the two clusters
+  /// with the strongest co-occurrence in `groups`, relative to their sizes,
+  /// are repeatedly merged under a new branch node
+  ///
+  /// Clusters sharing no group are finally combined into a balanced tree.
+  pub fn create_clustered(vars: Vec<usize>, groups: &[Vec<usize>]) -> Self {
+    let mut tree = Graph::new();
+    let mut clusters = vars
+      .iter()
+      .map(|var_id| Some(Cluster::leaf(&mut tree, *var_id)))
+      .collect::<Vec<_>>();
+    let var_to_cluster = vars.iter().enumerate().map(|(i, v)| (*v, i)).collect::<HashMap<_, _>>();
+
+    // Pairwise affinities; a group is worth 1 in total, so large groups do not
+    // dominate, and groups over all the variables carry no information
+    let mut affinities = vec![HashMap::<usize, f64>::new(); vars.len()];
+    for group in groups {
+      let ids = group
+        .iter()
+        .filter_map(|var_id| var_to_cluster.get(var_id).cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+      if ids.len() < 2 || ids.len() == vars.len() {
+        continue;
+      }
+      let weight = 1.0 / (ids.len() - 1) as f64;
+      for (k, i) in ids.iter().enumerate() {
+        for j in &ids[k + 1..] {
+          *affinities[*i].entry(*j).or_default() += weight;
+          *affinities[*j].entry(*i).or_default() += weight;
+        }
+      }
+    }
+
+    // Greedily merge the best pair of clusters; stale candidates are skipped
+    let mut candidates = BinaryHeap::new();
+    for (i, neighbors) in affinities.iter().enumerate() {
+      for (j, weight) in neighbors {
+        if i < *j {
+          candidates.push(Candidate::new(*weight, 1, 1, i, *j));
+        }
+      }
+    }
+    while let Some(Candidate { i, j, .. }) = candidates.pop() {
+      if clusters[i].is_none() || clusters[j].is_none() {
+        continue;
+      }
+      let (left, right) = (clusters[i].take().unwrap(), clusters[j].take().unwrap());
+      let merged = Cluster::branch(&mut tree, left, right);
+      let k = clusters.len();
+
+      // The affinity of the merged cluster is the sum of its parts
+      let mut neighbors = HashMap::<usize, f64>::new();
+      for (n, weight) in affinities[i].iter().chain(affinities[j].iter()) {
+        if clusters[*n].is_some() {
+          *neighbors.entry(*n).or_default() += weight;
+        }
+      }
+      for (n, weight) in &neighbors {
+        affinities[*n].insert(k, *weight);
+        let size = clusters[*n].as_ref().unwrap().size;
+        candidates.push(Candidate::new(*weight, size, merged.size, *n, k));
+      }
+      affinities.push(neighbors);
+      clusters.push(Some(merged));
+    }
+
+    // Combine the disconnected clusters, ordered by their first variable
+    let mut remaining = clusters.into_iter().flatten().collect::<Vec<_>>();
+    remaining.sort_by_key(|c| c.first_var);
+    let roots = remaining.into_iter().map(|c| c.node).collect::<Vec<_>>();
+    let root = combine_balanced(&mut tree, &roots);
+    Self::post_process(tree, vars, root)
+  }
+
+  /// Create a vtree where each group of variables sits in its own balanced
+  /// subtree; the subtrees are then combined into a balanced tree
+  pub fn create_grouped(vars: Vec<usize>, groups: &[Vec<usize>]) -> Self {
+    let mut tree = Graph::new();
+    let mut placed = BTreeSet::new();
+    let all_vars = vars.iter().cloned().collect::<BTreeSet<_>>();
+    let mut subtrees = vec![];
+    for group in groups {
+      let leaves = group
+        .iter()
+        .filter(|var_id| all_vars.contains(var_id) && placed.insert(**var_id))
+        .map(|var_id| tree.add_node(VTreeNode::Leaf { var_id: *var_id }))
+        .collect::<Vec<_>>();
+      if !leaves.is_empty() {
+        subtrees.push(combine_balanced(&mut tree, &leaves));
+      }
+    }
+    for var_id in &vars {
+      if !placed.contains(var_id) {
+        subtrees.push(tree.add_node(VTreeNode::Leaf { var_id: *var_id }));
+      }
+    }
+    let root = combine_balanced(&mut tree, &subtrees);
+    Self::post_process(tree, vars, root)
+  }
+}
+
+/// Greedy search of a vtree minimizing the size of the compiled SDD, by
+/// trying the rotations and swaps of every branch node
+///
+/// Every candidate vtree is compiled from scratch, so this is only worth it
+/// for formulas that are compiled many times or that blow up otherwise.
+#[derive(Clone, Debug)]
+pub struct VTreeSearch {
+  /// Maximum number of passes over the branch nodes
+  pub max_rounds: usize,
+
+  /// Budget for compiling each candidate; candidates exceeding it are dropped
+  pub budget: SDDBudget,
+}
+
+impl VTreeSearch {
+  pub fn new(max_rounds: usize) -> Self {
+    Self {
+      max_rounds,
+      budget: SDDBudget::unlimited(),
+    }
+  }
+
+  pub fn with_budget(mut self, budget: SDDBudget) -> Self {
+    self.budget = budget;
+    self
+  }
+
+  /// Search starting from `vtree`; the result is never worse than `vtree`
+  pub fn minimize(&self, forms: &[BooleanFormula], vtree: VTree) -> VTree {
+    let mut best_size = match sdd_size(forms, &vtree, SDDBudget::unlimited()) {
+      Some(size) => size,
+      None => return vtree,
+    };
+    let mut best = vtree;
+    for _ in 0..self.max_rounds {
+      let mut improved = false;
+      for node in best.branch_nodes() {
+        for mutation in &[VTreeMutation::RotateLeft, VTreeMutation::RotateRight, VTreeMutation::Swap] {
+          let mut candidate = best.clone();
+          if mutation.apply(&mut candidate, node).is_err() {
+            continue;
+          }
+          if let Some(size) = sdd_size(forms, &candidate, self.budget) {
+            if size < best_size {
+              best_size = size;
+              best = candidate;
+              improved = true;
+              break;
+            }
+          }
+        }
+      }
+      if !improved {
+        break;
+      }
+    }
+    best
+  }
+}
+
+/// Number of nodes of the SDD of all the formulas under the vtree, or `None`
+/// if the budget is exceeded
+pub fn sdd_size(forms: &[BooleanFormula], vtree: &VTree, budget: SDDBudget) -> Option<usize> {
+  let config = SDDBuilderConfig::with_vtree(vtree.clone()).with_budget(budget);
+  let mut builder = SDDBuilder::with_config(config);
+  for form in forms {
+    builder.try_add_formula(form)?;
+  }
+  builder.garbage_collect();
+  Some(builder.num_nodes())
+}
+
+#[derive(Clone, Copy, Debug)]
+enum VTreeMutation {
+  RotateLeft,
+  RotateRight,
+  Swap,
+}
+
+impl VTreeMutation {
+  fn apply(&self, vtree: &mut VTree, node: VTreeNodeIndex) -> Result<(), VTreeMutationError> {
+    match self {
+      Self::RotateLeft => vtree.rotate_left(node),
+      Self::RotateRight => vtree.rotate_right(node),
+      Self::Swap => vtree.swap(node),
+    }
+  }
+}
+
+/// Collect the variables of every maximal chain of conjunctions and of every
+/// maximal chain of disjunctions, i.e. the proofs of a DNF or the clauses of a
+/// CNF
+fn collect_co_occurring_groups(form: &BooleanFormula, groups: &mut Vec<Vec<usize>>) {
+  fn chain<'a>(form: &'a BooleanFormula, is_and: bool, children: &mut Vec<&'a BooleanFormula>) {
+    match form {
+      BooleanFormula::And { left, right } if is_and => {
+        chain(left, is_and, children);
+        chain(right, is_and, children);
+      }
+      BooleanFormula::Or { left, right } if !is_and => {
+        chain(left, is_and, children);
+        chain(right, is_and, children);
+      }
+      other => children.push(other),
+    }
+  }
+
+  match form {
+    BooleanFormula::And { .. } | BooleanFormula::Or { .. } => {
+      groups.push(form.collect_vars());
+      let mut children = vec![];
+      chain(form, matches!(form, BooleanFormula::And { .. }), &mut children);
+      for child in children {
+        collect_co_occurring_groups(child, groups);
+      }
+    }
+    BooleanFormula::Not { form } => collect_co_occurring_groups(form, groups),
+    _ => {}
+  }
+}
+
+/// Combine the subtrees into a balanced tree, keeping their order
+fn combine_balanced(tree: &mut Graph<VTreeNode, ()>, subtrees: &[NodeIndex]) -> NodeIndex {
+  if subtrees.len() == 1 {
+    subtrees[0]
+  } else {
+    let mid = subtrees.len() / 2;
+    let left = combine_balanced(tree, &subtrees[..mid]);
+    let right = combine_balanced(tree, &subtrees[mid..]);
+    let node = tree.add_node(VTreeNode::Branch {
+      left: VTreeNodeIndex(left),
+      right: VTreeNodeIndex(right),
+    });
+    tree.add_edge(node, left, ());
+    tree.add_edge(node, right, ());
+    node
+  }
+}
+
+struct Cluster {
+  node: NodeIndex,
+  size: usize,
+  first_var: usize,
+}
+
+impl Cluster {
+  fn leaf(tree: &mut Graph<VTreeNode, ()>, var_id: usize) -> Self {
+    let node = tree.add_node(VTreeNode::Leaf { var_id });
+    Self { node, size: 1, first_var: var_id }
+  }
+
+  fn branch(tree: &mut Graph<VTreeNode, ()>, left: Self, right: Self) -> Self {
+    // Keep the cluster with the smaller variable on the left
+    let (left, right) = if left.first_var < right.first_var { (left, right) } else { (right, left) };
+    let node = combine_balanced(tree, &[left.node, right.node]);
+    Self {
+      node,
+      size: left.size + right.size,
+      first_var: left.first_var,
+    }
+  }
+}
+
+/// A candidate merge of the clusters `i` and `j`, scored by their average
+/// affinity
+struct Candidate {
+  score: f64,
+  i: usize,
+  j: usize,
+}
+
+impl Candidate {
+  fn new(weight: f64, size_i: usize, size_j: usize, i: usize, j: usize) -> Self {
+    let score = weight / (size_i * size_j) as f64;
+    Self { score, i: i.min(j), j: i.max(j) }
+  }
+}
+
+impl PartialEq for Candidate {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Candidate {
+  fn cmp(&self, other: &Self) -> Ordering {
+    // Higher scores first, then older clusters for determinism
+    match self.score.partial_cmp(&other.score) {
+      Some(Ordering::Equal) | None => (other.i, other.j).cmp(&(self.i, self.j)),
+      Some(ord) => ord,
+    }
+  }
+}
@@ -0,0 +1,134 @@
+use std::collections::*;
+
+use sdd::*;
+
+fn approx_eq(a: f64, b: f64) -> bool {
+  (a - b).abs() < 1e-9
+}
+
+/// Brute-force weighted model count over all the declared variables
+fn brute_force_wmc(cnf: &CNF) -> f64 {
+  let form = cnf.to_boolean_formula();
+  (0..1usize << cnf.num_vars)
+    .map(|bits| (0..cnf.num_vars).map(|i| (i, bits & (1 << i) != 0)).collect::<HashMap<_, _>>())
+    .filter(|assign| form.eval(assign))
+    .map(|assign| {
+      assign.iter().fold(1.0, |acc, (var_id, value)| {
+        let (pos, neg) = cnf.weights.get(var_id).cloned().unwrap_or((1.0, 1.0));
+        acc * if *value { pos } else { neg }
+      })
+    })
+    .sum()
+}
+
+const EXAMPLE: &str = "c An example
+p cnf 5 4
+1 -2 0
+2 3 -4
+0
+-1 -3 0
+4 5 0
+";
+
+#[test]
+fn test_parse_dimacs() {
+  let cnf = CNF::from_dimacs_str(EXAMPLE).unwrap();
+  assert_eq!(cnf.num_vars, 5);
+  assert_eq!(
+    cnf.clauses,
+    vec![
+      vec![(0, true), (1, false)],
+      vec![(1, true), (2, true), (3, false)],
+      vec![(0, false), (2, false)],
+      vec![(3, true), (4, true)],
+    ]
+  );
+  assert_eq!(CNF::from_dimacs_str(&cnf.to_dimacs_string()).unwrap(), cnf);
+}
+
+#[test]
+fn test_dimacs_model_count() {
+  let cnf = CNF::from_dimacs_str(EXAMPLE).unwrap();
+  for heuristic in &[VTreeHeuristic::Shape(VTreeType::Balanced), VTreeHeuristic::CoOccurrence] {
+    let sdd = cnf.compile(heuristic);
+    assert_eq!(sdd.model_count().unwrap() as f64, brute_force_wmc(&cnf));
+  }
+
+  // Declared variables occurring in no clause double the count
+  let cnf = CNF::from_dimacs_str("p cnf 3 1\n1 2 0\n").unwrap();
+  assert_eq!(cnf.compile(&VTreeHeuristic::default()).model_count(), Some(6));
+}
+
+#[test]
+fn test_dimacs_weighted_model_count() {
+  let cachet = format!("{}w 1 0.3 0\nw 2 -1\nw 4 0.9\n", EXAMPLE);
+  let cnf = CNF::from_dimacs_str(&cachet).unwrap();
+  assert_eq!(cnf.weights[&0], (0.3, 0.7));
+  assert!(!cnf.weights.contains_key(&1));
+  let sdd = cnf.compile(&VTreeHeuristic::default());
+  assert!(approx_eq(cnf.weighted_model_count(&sdd), brute_force_wmc(&cnf)));
+
+  let mcc = format!("c p weight 1 2.5 0\nc p weight -1 0.5 0\nc p weight -5 4 0\n{}", EXAMPLE);
+  let cnf = CNF::from_dimacs_str(&mcc).unwrap();
+  assert_eq!(cnf.weights[&0], (2.5, 0.5));
+  assert_eq!(cnf.weights[&4], (1.0, 4.0));
+  let sdd = cnf.compile(&VTreeHeuristic::CoOccurrence);
+  assert!(approx_eq(cnf.weighted_model_count(&sdd), brute_force_wmc(&cnf)));
+}
+
+#[test]
+fn test_dimacs_unsat_and_satlib_end_marker() {
+  let cnf = CNF::from_dimacs_str("p cnf 1 2\n1 0\n-1 0\n%\n0\n").unwrap();
+  assert_eq!(cnf.clauses.len(), 2);
+  assert_eq!(cnf.compile(&VTreeHeuristic::default()).model_count(), Some(0));
+}
+
+#[test]
+fn test_dimacs_without_variables() {
+  let cnf = CNF::from_dimacs_str("p cnf 0 0\n").unwrap();
+  let sdd = cnf.compile(&VTreeHeuristic::default());
+  assert!(sdd.eval(&HashMap::new()));
+  assert_eq!(sdd.model_count(), Some(1));
+  assert_eq!(sdd.models(), vec![Model::new()]);
+  assert_eq!(cnf.weighted_model_count(&sdd), 1.0);
+
+  // The only clause without variables is the empty one
+  let cnf = CNF::from_dimacs_str("p cnf 0 1\n0\n").unwrap();
+  let sdd = cnf.compile(&VTreeHeuristic::CoOccurrence);
+  assert!(!sdd.eval(&HashMap::new()));
+  assert_eq!(sdd.model_count(), Some(0));
+  assert!(sdd.models().is_empty());
+}
+
+#[test]
+fn test_dimacs_many_variables() {
+  // 65 independent binary clauses over 130 variables
+  let clauses = (0..65).map(|i| format!("{} {} 0\n", 2 * i + 1, 2 * i + 2)).collect::<String>();
+  let cnf = CNF::from_dimacs_str(&format!("p cnf 130 65\n{}", clauses)).unwrap();
+  for heuristic in &[VTreeHeuristic::Shape(VTreeType::Balanced), VTreeHeuristic::CoOccurrence] {
+    let sdd = cnf.compile(heuristic);
+    let expected = 3.0f64.powi(65);
+    assert!((cnf.weighted_model_count(&sdd) - expected).abs() / expected < 1e-9);
+  }
+}
+
+#[test]
+fn test_dimacs_into_builder_with_budget() {
+  let cnf = CNF::from_dimacs_str(EXAMPLE).unwrap();
+  let config = SDDBuilderConfig::with_vtree(cnf.create_vtree(&VTreeHeuristic::default()))
+    .with_budget(SDDBudget::new(Some(1), None));
+  assert!(SDDBuilder::with_config(config).try_add_cnf(&cnf).is_none());
+}
+
+#[test]
+fn test_dimacs_parse_errors() {
+  assert!(matches!(CNF::from_dimacs_str("1 2 0\n"), Err(SDDParseError::MissingHeader)));
+  assert!(matches!(
+    CNF::from_dimacs_str("p cnf 2 1\n1 3 0\n"),
+    Err(SDDParseError::InvalidLine { line: 2, .. })
+  ));
+  assert!(matches!(
+    CNF::from_dimacs_str("p cnf 2 1\n1 x 0\n"),
+    Err(SDDParseError::InvalidLine { line: 2, .. })
+  ));
+}
@@ -0,0 +1,91 @@
+use sdd::*;
+
+fn all_assignments(num_vars: usize) -> Vec<Vec<(usize, bool)>> {
+  (0..1 << num_vars)
+    .map(|bits: usize| (0..num_vars).map(|i| (i, bits & (1 << i) != 0)).collect())
+    .collect()
+}
+
+#[test]
+fn test_vtree_round_trip() {
+  let vtree = VTree::new_with_type(vec![0, 1, 2, 3, 4], VTreeType::Balanced);
+  let s = vtree.to_vtree_string();
+  let parsed = VTree::from_vtree_str(&s).unwrap();
+  assert_eq!(parsed.num_vars(), 5);
+  assert_eq!(parsed.to_vtree_string(), s);
+}
+
+#[test]
+fn test_sdd_round_trip() {
+  let form = (bf(0) | !bf(1)) & (bf(2) | bf(3)) & !(bf(0) & bf(3));
+  let config = SDDBuilderConfig::with_formula(&form);
+  let sdd = SDDBuilder::with_config(config).build(&form);
+
+  let vtree = VTree::from_vtree_str(&sdd.vtree().to_vtree_string()).unwrap();
+  let parsed = SDD::from_sdd_str(&sdd.to_sdd_string(), vtree).unwrap();
+  for assign in all_assignments(4) {
+    assert_eq!(parsed.eval_i(assign.clone()), sdd.eval_i(assign));
+  }
+  assert_eq!(parsed.to_sdd_string(), sdd.to_sdd_string());
+}
+
+#[test]
+fn test_sdd_parse_external() {
+  // (A /\ B) \/ (C /\ D), over a balanced vtree
+  let vtree = "
+c ids of vtree nodes start at 0
+vtree 7
+L 0 1
+L 2 2
+I 1 0 2
+L 4 3
+L 6 4
+I 5 4 6
+I 3 1 5
+";
+  let sdd = "
+c ids of sdd nodes start at 0
+sdd 13
+F 0
+T 1
+L 2 0 1
+L 3 0 -1
+L 4 2 2
+L 5 2 -2
+D 6 1 2 2 4 3 0
+D 7 1 2 2 5 3 1
+L 8 4 3
+L 9 4 -3
+L 10 6 4
+D 11 5 2 8 10 9 0
+D 12 3 2 6 1 7 11
+";
+  let vtree = VTree::from_vtree_str(vtree).unwrap();
+  let sdd = SDD::from_sdd_str(sdd, vtree).unwrap();
+  for assign in all_assignments(4) {
+    let expected = (assign[0].1 && assign[1].1) || (assign[2].1 && assign[3].1);
+    assert_eq!(sdd.eval_i(assign), expected);
+  }
+}
+
+#[test]
+fn test_sdd_parse_errors() {
+  let vtree = VTree::from_vtree_str("vtree 1\nL 0 1\n").unwrap();
+  assert!(matches!(
+    SDD::from_sdd_str("sdd 1\nD 0 0 1 1 2\n", vtree.clone()),
+    Err(SDDParseError::UnknownNode { line: 2, id: 1 })
+  ));
+  assert!(matches!(
+    SDD::from_sdd_str("sdd 1\nX 0\n", vtree.clone()),
+    Err(SDDParseError::InvalidLine { line: 2, .. })
+  ));
+
+  // The literal of variable 2 is not at the leaf of variable 1
+  let vtree = VTree::from_vtree_str("vtree 3\nL 0 1\nL 2 2\nI 1 0 2\n").unwrap();
+  assert!(matches!(
+    SDD::from_sdd_str("sdd 1\nL 0 0 2\n", vtree.clone()),
+    Err(SDDParseError::InvalidLine { line: 2, .. })
+  ));
+  assert!(SDD::from_sdd_str("sdd 1\nL 0 2 -2\n", vtree.clone()).is_ok());
+  assert!(matches!(SDD::from_sdd_str("c nothing\n", vtree), Err(SDDParseError::Empty)));
+}
@@ -0,0 +1,106 @@
+use std::collections::*;
+
+use sdd::*;
+
+fn assignments(num_vars: usize) -> Vec<HashMap<usize, bool>> {
+  (0..1usize << num_vars)
+    .map(|bits| (0..num_vars).map(|i| (i, bits & (1 << i) != 0)).collect())
+    .collect()
+}
+
+fn formulas() -> Vec<BooleanFormula> {
+  vec![
+    (bf(0) | !bf(1)) & bf(2),
+    (bf(0) & bf(1)) | (!bf(0) & !bf(1)) | bf(3),
+    (bf(0) | bf(1)) & (bf(2) | bf(3)) & !(bf(1) & bf(2)),
+    bf(0) & !bf(0),
+    bf(2) | !bf(2),
+  ]
+}
+
+fn config(num_vars: usize) -> SDDBuilderConfig {
+  SDDBuilderConfig::new((0..num_vars).collect(), VTreeType::Balanced, false)
+}
+
+#[test]
+fn test_sdd_model_count() {
+  for form in formulas() {
+    let sdd = SDDBuilder::with_config(config(4)).build(&form);
+    let expected = assignments(4).iter().filter(|a| form.eval(a)).count();
+    assert_eq!(sdd.model_count(), Some(expected as u128));
+  }
+}
+
+#[test]
+fn test_sdd_model_count_overflow() {
+  let disj = |n: usize| (1..n).fold(bf(0), |acc, i| acc | bf(i));
+  let conj = |n: usize| (1..n).fold(bf(0), |acc, i| acc & bf(i));
+  let count =
+    |n: usize, form: &BooleanFormula| SDDBuilder::with_config(config(n)).build(form).model_count();
+  assert_eq!(count(127, &disj(127)), Some((1 << 127) - 1));
+  assert_eq!(count(128, &disj(128)), Some(u128::MAX));
+  assert_eq!(count(130, &disj(130)), None);
+  assert_eq!(count(130, &conj(130)), Some(1));
+  assert_eq!(count(130, &(bf(0) & !bf(0))), Some(0));
+}
+
+#[test]
+fn test_sdd_models() {
+  for form in formulas() {
+    let sdd = SDDBuilder::with_config(config(4)).build(&form);
+    let found = sdd
+      .models()
+      .into_iter()
+      .map(|m| m.into_iter().collect::<Vec<_>>())
+      .collect::<BTreeSet<_>>();
+    let expected = assignments(4)
+      .into_iter()
+      .filter(|a| form.eval(a))
+      .map(|a| a.into_iter().collect::<BTreeMap<_, _>>().into_iter().collect::<Vec<_>>())
+      .collect::<BTreeSet<_>>();
+    assert_eq!(found, expected);
+  }
+}
+
+#[test]
+fn test_sdd_condition() {
+  for form in formulas() {
+    for value in &[true, false] {
+      let mut builder = SDDBuilder::with_config(config(4));
+      let node = builder.build_sdd(&form);
+      let cond = builder.condition(node, 1, *value);
+      builder.add_root(cond);
+      let sdd = builder.build_arena();
+      for a in assignments(4) {
+        let mut fixed = a.clone();
+        fixed.insert(1, *value);
+        assert_eq!(sdd.eval_root_t(0, &a, &BooleanSemiring), form.eval(&fixed));
+      }
+    }
+  }
+}
+
+#[test]
+fn test_sdd_exists_and_negate() {
+  for form in formulas() {
+    let mut builder = SDDBuilder::with_config(config(4));
+    let node = builder.build_sdd(&form);
+    let ex = builder.exists_many(node, &[0, 2]);
+    let neg = builder.negate(node);
+    builder.add_root(ex);
+    builder.add_root(neg);
+    let sdd = builder.build_arena();
+    for a in assignments(4) {
+      let expected = [true, false].iter().any(|x| {
+        [true, false].iter().any(|z| {
+          let mut b = a.clone();
+          b.insert(0, *x);
+          b.insert(2, *z);
+          form.eval(&b)
+        })
+      });
+      assert_eq!(sdd.eval_root_t(0, &a, &BooleanSemiring), expected);
+      assert_eq!(sdd.eval_root_t(1, &a, &BooleanSemiring), !form.eval(&a));
+    }
+  }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use sdd::*;
 
 #[test]
@@ -112,3 +114,88 @@ fn test_sdd_7() {
     }
   }
 }
+
+#[test]
+fn test_sdd_arena_eval_roots() {
+  let forms = vec![(bf(0) | !bf(1)) & bf(2), bf(1) & bf(2), (bf(0) | !bf(1)) | bf(3)];
+  let config = SDDBuilderConfig::with_formulas(&forms);
+  let mut builder = SDDBuilder::with_config(config);
+  for (i, form) in forms.iter().enumerate() {
+    assert_eq!(builder.add_formula(form), i);
+  }
+  let sdd = builder.build_arena();
+  assert_eq!(sdd.num_roots(), 3);
+  for a in &[true, false] {
+    for b in &[true, false] {
+      for c in &[true, false] {
+        for d in &[true, false] {
+          let var_assign = vec![(0, *a), (1, *b), (2, *c), (3, *d)].into_iter().collect();
+          let expected = vec![(a | !b) & c, b & c, (a | !b) | d];
+          assert_eq!(sdd.eval_roots_t(&var_assign, &BooleanSemiring), expected);
+          for (i, e) in expected.into_iter().enumerate() {
+            assert_eq!(sdd.eval_root_t(i, &var_assign, &BooleanSemiring), e);
+          }
+        }
+      }
+    }
+  }
+}
+
+#[test]
+fn test_sdd_budget() {
+  // A disjunction of pairwise conjunctions over a right-linear vtree
+  let form = (0..8).fold(BooleanFormula::False, |acc, i| acc | (bf(i) & bf(i + 8)));
+  let vars = form.collect_vars();
+
+  let unlimited = SDDBuilderConfig::new(vars.clone(), VTreeType::Right, true);
+  let sdd = SDDBuilder::with_config(unlimited).try_build(&form);
+  assert!(sdd.is_some());
+
+  let budget = SDDBudget::new(Some(10), None);
+  let limited = SDDBuilderConfig::new(vars.clone(), VTreeType::Right, true).with_budget(budget);
+  assert!(SDDBuilder::with_config(limited).try_build(&form).is_none());
+
+  let budget = SDDBudget::new(None, Some(20));
+  let limited = SDDBuilderConfig::new(vars, VTreeType::Right, true).with_budget(budget);
+  assert!(SDDBuilder::with_config(limited).try_build(&form).is_none());
+}
+
+#[test]
+fn test_sdd_mpe() {
+  // (a /\ b) \/ (~a /\ c)
+  let form = (bf(0) & bf(1)) | (!bf(0) & bf(2));
+  let config = SDDBuilderConfig::with_formula(&form);
+  let sdd = SDDBuilder::with_config(config).build(&form);
+  let var_probs: HashMap<usize, f64> = vec![(0, 0.6), (1, 0.3), (2, 0.9)].into_iter().collect();
+
+  // Brute force over all the assignments
+  let mut best = (0.0, vec![]);
+  for a in &[true, false] {
+    for b in &[true, false] {
+      for c in &[true, false] {
+        let assign = vec![(0, *a), (1, *b), (2, *c)];
+        if sdd.eval_i(assign.clone()) {
+          let prob = assign
+            .iter()
+            .map(|(v, x)| if *x { var_probs[v] } else { 1.0 - var_probs[v] })
+            .product::<f64>();
+          if prob > best.0 {
+            best = (prob, assign);
+          }
+        }
+      }
+    }
+  }
+
+  let (prob, assign) = sdd.mpe(&var_probs).unwrap();
+  assert!((prob - best.0).abs() < 1e-9);
+  assert_eq!(assign.into_iter().collect::<Vec<_>>(), best.1);
+}
+
+#[test]
+fn test_sdd_mpe_unsat() {
+  let form = bf(0) & !bf(0);
+  let config = SDDBuilderConfig::with_formula(&form);
+  let sdd = SDDBuilder::with_config(config).build(&form);
+  assert_eq!(sdd.mpe(&vec![(0, 0.5)].into_iter().collect()), None);
+}
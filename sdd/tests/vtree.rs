@@ -41,3 +41,76 @@ fn vtree_mutation_3() {
   vtree.rotate_right(vtree.root_id()).unwrap();
   vtree.save_dot("after.dot").unwrap();
 }
+
+fn assert_equivalent(form: &BooleanFormula, vtree: VTree) {
+  let vars = form.collect_vars();
+  let sdd = SDDBuilder::with_config(SDDBuilderConfig::with_vtree(vtree)).build(form);
+  for bits in 0..1usize << vars.len() {
+    let assign = vars.iter().enumerate().map(|(i, v)| (*v, bits & (1 << i) != 0)).collect();
+    assert_eq!(sdd.eval(&assign), form.eval(&assign));
+  }
+}
+
+fn proofs() -> BooleanFormula {
+  (bf(0) & bf(5)) | (bf(1) & bf(4)) | (bf(2) & bf(3) & !bf(0))
+}
+
+#[test]
+fn vtree_mutations_keep_compilation_sound() {
+  let form = proofs();
+  let vtree = VTree::new(form.collect_vars());
+  for node in vtree.branch_nodes() {
+    for i in 0..3 {
+      let mut mutated = vtree.clone();
+      let result = match i {
+        0 => mutated.rotate_left(node),
+        1 => mutated.rotate_right(node),
+        _ => mutated.swap(node),
+      };
+      if result.is_ok() {
+        assert_eq!(mutated.num_vars_in_subtree(mutated.root_id()), 6);
+        assert_equivalent(&form, mutated);
+      }
+    }
+  }
+}
+
+#[test]
+fn co_occurrence_vtree_clusters_proofs() {
+  let form = proofs();
+  let vtree = VTree::create_from_formulas(vec![&form], &VTreeHeuristic::CoOccurrence);
+  let mut vars = vtree.vars_in_subtree(vtree.root_id());
+  vars.sort();
+  assert_eq!(vars, vec![0, 1, 2, 3, 4, 5]);
+
+  // The two facts of each binary proof are siblings
+  let siblings = vtree
+    .branch_nodes()
+    .into_iter()
+    .map(|node| vtree.vars_in_subtree(node))
+    .collect::<Vec<_>>();
+  assert!(siblings.contains(&vec![0, 5]));
+  assert!(siblings.contains(&vec![1, 4]));
+  assert_equivalent(&form, vtree);
+}
+
+#[test]
+fn grouped_vtree_keeps_groups_together() {
+  let form = proofs();
+  let groups = vec![vec![5, 3], vec![0, 1, 2]];
+  let vtree = VTree::create_from_formulas(vec![&form], &VTreeHeuristic::Groups(groups));
+  let (left, right) = vtree.children(vtree.root_id()).unwrap();
+  assert_eq!(vtree.vars_in_subtree(left), vec![5, 3]);
+  assert_eq!(vtree.vars_in_subtree(right), vec![0, 1, 2, 4]);
+  assert_equivalent(&form, vtree);
+}
+
+#[test]
+fn vtree_search_never_grows_sdd() {
+  let forms = vec![proofs()];
+  let vtree = VTree::create_right_linear_from_vars(forms[0].collect_vars());
+  let initial = sdd_size(&forms, &vtree, SDDBudget::unlimited()).unwrap();
+  let searched = VTreeSearch::new(3).minimize(&forms, vtree);
+  assert!(sdd_size(&forms, &searched, SDDBudget::unlimited()).unwrap() <= initial);
+  assert_equivalent(&forms[0], searched);
+}